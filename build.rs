@@ -0,0 +1,32 @@
+use std::path::Path;
+use std::process::Command;
+use std::{env, str};
+
+fn command_output(cmd: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(cmd).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    str::from_utf8(&output.stdout)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+fn main() {
+    let git_hash = command_output("git", &["rev-parse", "--short", "HEAD"])
+        .unwrap_or_else(|| "unknown".to_string());
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version =
+        command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+    let build_date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+
+    println!("cargo:rustc-env=GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=BUILD_DATE={}", build_date);
+    println!("cargo:rustc-env=RUSTC_VERSION={}", rustc_version);
+
+    if Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs");
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
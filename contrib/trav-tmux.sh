@@ -0,0 +1,42 @@
+#!/bin/sh
+# Picks files with trav in a tmux popup and hands their paths back to tmux.
+#
+# Usage: trav-tmux.sh [--paste] [TRAV OPTIONS] [PATH]
+#
+# The picked paths are stored in the tmux paste buffer, with `--paste` they
+# are also pasted into the current pane. Bind it in ~/.tmux.conf with e.g.
+#
+#     bind-key f run-shell -b "~/bin/trav-tmux.sh --paste"
+
+set -eu
+
+if [ -z "${TMUX:-}" ]; then
+    echo "trav-tmux.sh: not running inside tmux" >&2
+    exit 1
+fi
+
+paste=0
+if [ "${1:-}" = "--paste" ]; then
+    paste=1
+    shift
+fi
+
+out=$(mktemp)
+trap 'rm -f "$out"' EXIT
+
+# trav draws on the popup's terminal and prints the picked paths on exit
+cmd="trav --select-output"
+for arg in "$@"; do
+    cmd="$cmd '$(printf '%s' "$arg" | sed "s/'/'\\\\''/g")'"
+done
+tmux display-popup -E -d "$(tmux display-message -p '#{pane_current_path}')" \
+    "$cmd > '$out'" || true
+
+# paths are joined with spaces to paste them as command arguments
+paths=$(tr '\n' ' ' < "$out" | sed 's/ $//')
+[ -n "$paths" ] || exit 0
+
+tmux set-buffer -- "$paths"
+if [ "$paste" -eq 1 ]; then
+    tmux paste-buffer
+fi
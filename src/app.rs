@@ -1,43 +1,388 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::{Local, SecondsFormat};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::OsStr;
 use std::io::BufRead;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 use std::{env, fs, io, process};
-use termion::event::Key;
+use termion::event::{Key, MouseButton, MouseEvent};
 use tui::{
+    backend::{Backend, TestBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Span, Spans},
-    widgets::{Block, Borders, ListState, Paragraph},
-    Frame,
+    text::{Span, Spans, Text},
+    widgets::{
+        BarChart, Block, Borders, Cell, ListItem, ListState, Paragraph, Row, Table, TableState,
+        Wrap,
+    },
+    Frame, Terminal,
 };
+use unicode_width::UnicodeWidthStr;
 
-use crate::entry::{get_ok_entries, styled_file_entries, DirEntry};
+use crate::archive::Archive;
+use crate::children::ChildEntries;
+use crate::chmod::{EditorAction, ModeEditor};
+use crate::command::Command;
+use crate::config::Config;
+use crate::count::FileCounts;
+use crate::diff::{self, DiffView, LineKind};
+use crate::du::DuMap;
+use crate::duplicates::Duplicates;
+use crate::editor::{EditAction, TextEditor};
+use crate::encoding;
+use crate::entry::{
+    list_sorted, styled_file_entries, styled_file_entries_with_badge, DirEntry, ItemOptions,
+    HIGHLIGHT_SYMBOL,
+};
 use crate::events::{Event, Events};
+use crate::favorites::{self, Favorite};
+use crate::fileops::{self, Clipboard, ClipboardOp};
+use crate::filter::{parse_date_filter, parse_extensions, parse_size_filter, Filters, PermFilter};
+use crate::help;
+use crate::history::{self, HistoryCursor};
+use crate::icons::NERD_FONTS;
+use crate::ignore::IgnoreRules;
+use crate::input::InputWidget;
+use crate::jump::{self, Completion};
+use crate::permfix::PermFix;
+use crate::popup::{Popup, PopupKind};
+use crate::properties::{self, ChainEnd};
+use crate::recent::{self, RecentFile};
+use crate::scan::{PathScan, ScanKind};
+use crate::scrollbar::render_scrollbar;
+use crate::session::Session;
+use crate::sort::SortOptions;
+use crate::split::{Pane, SplitView};
+use crate::stats::ExtStats;
+use crate::tags;
+use crate::template;
+use crate::tree::DirTree;
+use crate::util;
 use crate::util::list::StatefulList;
-use crate::Backend;
+use crate::util::lru::LruMap;
+use crate::util::regex::Regex;
+use crate::visited;
+use crate::watch::Watch;
+use crate::workspaces;
+
+/// Minimum terminal size required to render the main view.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
+
+/// Command used to open files by default.
+const DEFAULT_OPENER: &str = "xdg-open";
+/// Lines of a file shown in the preview.
+const PREVIEW_LINES: usize = 128;
+/// Separates line numbers from the lines in the preview.
+const GUTTER_SEPARATOR: &str = " │ ";
+/// Name suggested for archives created with `Ctrl-T`.
+const ARCHIVE_NAME_FORMAT: &str = "trav_%Y-%m-%d_%H%M%S.tar.gz";
+/// Command opening a directory in the graphical file manager.
+#[cfg(target_os = "macos")]
+const DEFAULT_FILE_MANAGER: &str = "open";
+#[cfg(windows)]
+const DEFAULT_FILE_MANAGER: &str = "explorer";
+#[cfg(not(any(target_os = "macos", windows)))]
+const DEFAULT_FILE_MANAGER: &str = "xdg-open";
+/// Arguments making `stat` print all details of a file.
+#[cfg(target_os = "macos")]
+const STAT_ARGS: &[&str] = &["-x"];
+#[cfg(not(target_os = "macos"))]
+const STAT_ARGS: &[&str] = &[];
+/// Pager used if `$PAGER` isn't set.
+const DEFAULT_PAGER: &str = "less";
+/// Opener picker item prompting for a command to open the file with.
+const CUSTOM_OPENER: &str = "custom command…";
+
+/// Actions offered by the context menu.
+const MENU_OPEN: &str = "Open";
+const MENU_OPEN_WITH: &str = "Open with…";
+const MENU_COPY: &str = "Copy";
+const MENU_CUT: &str = "Cut";
+const MENU_RENAME: &str = "Rename";
+const MENU_DELETE: &str = "Delete";
+const MENU_PROPERTIES: &str = "Properties";
+
+/// Answer confirming a deletion.
+const CONFIRM_YES: &str = "Yes";
+const CONFIRM_NO: &str = "No";
+
+/// Template picker item creating an empty file.
+const NO_TEMPLATE: &str = "empty file";
+
+/// How long a message flashed in the status bar stays visible.
+const STATUS_MSG_DURATION: Duration = Duration::from_secs(2);
+/// Percentages of the width taken by the parent, current and preview panels.
+const DEFAULT_PANEL_WIDTHS: [u16; 3] = [33, 33, 33];
+/// Percentage a divider moves per key press while resizing panels.
+const PANEL_RESIZE_STEP: u16 = 5;
+/// Smallest percentage of the width a panel can be resized to.
+const MIN_PANEL_WIDTH: u16 = 10;
+
+/// Width of bars in the disk usage map.
+const DU_BAR_WIDTH: u16 = 9;
+
+/// Number of lines scrolled by PageUp and PageDown in the diff view.
+const DIFF_PAGE: isize = 20;
+
+/// Maximum number of directories remembered for jump prompt completion.
+const MAX_HISTORY: usize = 100;
+
+/// Maximum number of directories for which the cursor position is remembered.
+const MAX_SAVED_POSITIONS: usize = 512;
+
+#[derive(Debug)]
+/// Content of the main view.
+pub enum PanelMode {
+    /// The parent, current and highlighted directories.
+    Entries,
+    /// Recently modified files shown instead of the current directory.
+    Recent(StatefulList<RecentFile>),
+    /// Comparison of two files.
+    Diff(DiffView),
+    /// Disk usage of the current directory's entries.
+    DuMap(DuMap),
+    /// Groups of files with identical content shown instead of the current
+    /// directory.
+    Duplicates(Duplicates),
+    /// Paths found by a search of the current subtree.
+    Scan(PathScan),
+    /// File counts by extension of the current subtree shown instead of the
+    /// preview.
+    Stats(ExtStats),
+    /// Directories visited in this and previous sessions, most recent first.
+    RecentDirs(StatefulList<PathBuf>),
+    /// Permission changes waiting for confirmation.
+    PermFix(PermFix),
+    /// Two directories stacked vertically instead of the three columns.
+    SplitVertical(SplitView),
+}
+
+impl PanelMode {
+    pub fn is_entries(&self) -> bool {
+        matches!(self, PanelMode::Entries)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Decides where key presses are routed to.
+pub enum InputMode {
+    Normal,
+    ExtensionFilter,
+    Command,
+    Export,
+    OpenWith,
+    Rename,
+    Jump,
+    LinkTarget,
+    LinkName,
+    Archive,
+    NewFile,
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// What the preview panel shows for the highlighted entry.
+pub enum PreviewLayout {
+    /// The file's content or the directory's entries.
+    #[default]
+    Content,
+    /// All properties of the entry.
+    Metadata,
+    /// A summary of the properties above the content.
+    Split,
+}
+
+impl PreviewLayout {
+    pub fn next(self) -> PreviewLayout {
+        match self {
+            PreviewLayout::Content => PreviewLayout::Metadata,
+            PreviewLayout::Metadata => PreviewLayout::Split,
+            PreviewLayout::Split => PreviewLayout::Content,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What the app receives through its event channel, keys from the terminal or
+/// notifications from background work.
+pub enum AppEvent {
+    Key(Key),
+    /// The listing of the highlighted directory can be collected.
+    ChildEntriesLoaded,
+    /// The command the preview is piped through exited.
+    PipeFinished,
+}
+
+impl From<Key> for AppEvent {
+    fn from(key: Key) -> AppEvent {
+        AppEvent::Key(key)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Behavior set from the command line.
+pub struct AppOptions {
+    /// Pick files and keep their paths for `output` on exit.
+    pub select_output: bool,
+    /// Refuse to copy, move, delete, rename, chmod or export to files.
+    pub readonly: bool,
+    /// Don't ask twice before deleting when running as root.
+    pub force_root: bool,
+    /// How many levels below the start directory can be entered.
+    pub max_depth: Option<usize>,
+    /// Highlight changes of the current directory as they happen.
+    pub watch: bool,
+}
 
 #[derive(Debug)]
 pub struct TravApp {
-    pub cwd_path: PathBuf,
-    pub cwd_entries: StatefulList<DirEntry>,
-    pub cwd_idx: Option<usize>,
-    pub parent: Option<(PathBuf, Vec<DirEntry>)>,
-    pub parent_idx: Option<usize>,
-    pub child_entries: Option<Vec<DirEntry>>,
-    pub content: Option<String>,
-    pub events: Events,
-    pub exit: bool,
-    pub err: Option<String>,
+    cwd_path: PathBuf,
+    cwd_entries: StatefulList<DirEntry>,
+    cwd_idx: Option<usize>,
+    parent: Option<(PathBuf, Vec<DirEntry>)>,
+    parent_idx: Option<usize>,
+    child_entries: Option<ChildEntries>,
+    content: Option<String>,
+    /// Line and character counts of the previewed file.
+    preview_counts: Option<FileCounts>,
+    /// Percentages of the width taken by the parent, current and preview
+    /// panels.
+    panel_widths: [u16; 3],
+    /// Divider moved with `Left` and `Right` after `Ctrl-W`, `0` between the
+    /// parent and current panels and `1` between the current and preview ones.
+    resizing: Option<usize>,
+    /// Replaces the preview title while the preview shows command output, like
+    /// that of `:stat` or `:tree`, instead of the highlighted entry.
+    preview_title: Option<String>,
+    /// Output and errors of the `:!` command running on the previewed file.
+    piped: Option<mpsc::Receiver<Result<(String, String)>>>,
+    /// Shows diagnostics like memory use and open files, toggled with `:debug`.
+    show_debug: bool,
+    events: Events<AppEvent>,
+    exit: bool,
+    err: Option<String>,
+    /// When the error is cleared if it isn't cleared by a key before.
+    err_deadline: Option<Instant>,
+    config: Config,
+    sort_reversed: bool,
+    filters: Filters,
+    input_mode: InputMode,
+    input: InputWidget,
+    selection: HashSet<PathBuf>,
+    /// Last cursor position in each visited directory.
+    positions: LruMap<PathBuf, usize>,
+    /// When set the app works as a file picker, `Enter` on a file confirms the
+    /// selection and exits.
+    select_output: bool,
+    /// Paths picked by the user in `select_output` mode.
+    output: Vec<PathBuf>,
+    popup: Option<Popup>,
+    clipboard: Option<Clipboard>,
+    /// Polls the current directory for changes if enabled with `--watch`.
+    watch: Option<Watch>,
+    /// What the main view shows.
+    panel: PanelMode,
+    /// Visited directories, most recent last.
+    history: Vec<PathBuf>,
+    /// Directories visited across sessions, most recent first.
+    recently_visited: VecDeque<PathBuf>,
+    /// Paths pinned with `f` and listed with `Ctrl-F`, most recently accessed
+    /// first.
+    favorites: Vec<Favorite>,
+    /// Color tags of entries assigned with `1`–`5`.
+    tags: HashMap<PathBuf, u8>,
+    /// Directories marked with `W`, jumped to with `Alt-1`–`Alt-9`.
+    workspaces: Vec<PathBuf>,
+    /// Completion cycled with `Tab` in the jump prompt.
+    completion: Option<Completion>,
+    /// Commands entered in the command prompt, oldest first.
+    command_history: Vec<String>,
+    /// Browsed command history, reset when the prompt is left.
+    history_cursor: Option<HistoryCursor>,
+    /// Message flashed in the status bar along with the time it was shown.
+    status_msg: Option<(String, Instant)>,
+    /// Program to run in the terminal while the TUI is suspended.
+    foreground: Option<process::Command>,
+    /// Permission editor opened over the main view.
+    mode_editor: Option<ModeEditor>,
+    /// Inline editor replacing the preview of the highlighted file.
+    editor: Option<TextEditor>,
+    /// Archive of the targeted entries being created with `Ctrl-T`.
+    archive: Option<Archive>,
+    /// Entries archived once the archive name is confirmed.
+    pending_archive: Vec<PathBuf>,
+    /// Target of the symlink being created while its name is prompted for.
+    link_target: Option<PathBuf>,
+    /// Boilerplate offered for new files, read on startup.
+    templates: Vec<PathBuf>,
+    /// New file created once its template is picked.
+    pending_file: Option<PathBuf>,
+    /// Paths removed once the deletion is confirmed.
+    pending_delete: Vec<PathBuf>,
+    /// Files opened once opening all of them is confirmed.
+    pending_open: Vec<PathBuf>,
+    /// Stop the process after restoring the terminal, set by `Ctrl-Z`.
+    suspend: bool,
+    /// Refuses to modify files, set with `--readonly`.
+    readonly: bool,
+    /// Whether trav runs as root, deletions are confirmed twice then.
+    root: bool,
+    /// Skips the second confirmation when running as root, set with `--force-root`.
+    force_root: bool,
+    /// Number of lines inside the current directory panel at the last render.
+    list_height: u16,
+    /// Wrap long lines of the previewed file, toggled with `w`.
+    preview_wrap: bool,
+    /// Show a gutter with line numbers in the preview, toggled with `#`.
+    preview_line_numbers: bool,
+    /// First visible line of the previewed file.
+    preview_scroll: u16,
+    /// Cycled with `P`.
+    preview_layout: PreviewLayout,
+    /// Width and height inside the preview panel at the last render.
+    preview_size: (u16, u16),
+    /// Number of entries in the current directory before filtering.
+    total_entries: usize,
+    /// Directory trav was started in.
+    startup_path: PathBuf,
+    /// How many levels below `startup_path` can be entered, set with `--max-depth`.
+    max_depth: Option<usize>,
 }
 
 impl TravApp {
+    /// Opens `base_dir`, or the current directory if `None`, with the config
+    /// read from `~/.config/trav/config.toml`.
     pub fn new<P: AsRef<Path>>(base_dir: Option<P>) -> Result<TravApp> {
+        TravApp::with_config(base_dir, Config::load()?)
+    }
+
+    /// Opens `base_dir`, or the current directory if `None`, with `config`
+    /// instead of the config file, e.g. one built by an embedding application.
+    pub fn with_config<P: AsRef<Path>>(base_dir: Option<P>, config: Config) -> Result<TravApp> {
+        TravApp::with_events(base_dir, config, Events::new())
+    }
+
+    /// Like `with_config` but handling `events`, e.g. ones made with
+    /// `Events::without_input` to send the keys from tests.
+    pub fn with_events<P: AsRef<Path>>(
+        base_dir: Option<P>,
+        config: Config,
+        events: Events<AppEvent>,
+    ) -> Result<TravApp> {
         let path = if let Some(path) = base_dir {
-            path.as_ref().to_path_buf()
+            let path = env::current_dir()?.join(path);
+            fs::canonicalize(&path)
+                .map_err(|e| anyhow!("failed to open {}: {}", path.display(), e))?
         } else {
             env::current_dir()?
         };
 
+        let state_dir = config.state_dir.clone();
         let mut app = TravApp {
             cwd_path: path.clone(),
             cwd_entries: StatefulList::new(),
@@ -46,20 +391,285 @@ impl TravApp {
             parent_idx: None,
             child_entries: None,
             content: None,
-            events: Events::new(),
+            preview_counts: None,
+            panel_widths: DEFAULT_PANEL_WIDTHS,
+            resizing: None,
+            preview_title: None,
+            piped: None,
+            show_debug: false,
+            events,
             exit: false,
             err: None,
+            err_deadline: None,
+            config,
+            sort_reversed: false,
+            filters: Filters::default(),
+            input_mode: InputMode::Normal,
+            input: InputWidget::new(),
+            selection: HashSet::new(),
+            positions: LruMap::new(MAX_SAVED_POSITIONS),
+            select_output: false,
+            output: vec![],
+            popup: None,
+            clipboard: None,
+            watch: None,
+            panel: PanelMode::Entries,
+            history: vec![path.clone()],
+            recently_visited: visited::load(state_dir.as_deref()).unwrap_or_else(|e| {
+                crate::warn!("{:#}", e);
+                VecDeque::new()
+            }),
+            favorites: favorites::load(state_dir.as_deref()).unwrap_or_else(|e| {
+                crate::warn!("{:#}", e);
+                vec![]
+            }),
+            tags: tags::load(state_dir.as_deref()).unwrap_or_else(|e| {
+                crate::warn!("{:#}", e);
+                HashMap::new()
+            }),
+            workspaces: workspaces::load(state_dir.as_deref()).unwrap_or_else(|e| {
+                crate::warn!("{:#}", e);
+                vec![]
+            }),
+            completion: None,
+            command_history: history::load(state_dir.as_deref()),
+            history_cursor: None,
+            status_msg: None,
+            foreground: None,
+            mode_editor: None,
+            editor: None,
+            archive: None,
+            pending_archive: vec![],
+            link_target: None,
+            templates: template::load(state_dir.as_deref()),
+            pending_file: None,
+            pending_delete: vec![],
+            pending_open: vec![],
+            suspend: false,
+            readonly: false,
+            root: util::is_root(),
+            force_root: false,
+            list_height: 0,
+            preview_wrap: false,
+            preview_line_numbers: false,
+            preview_scroll: 0,
+            preview_size: (0, 0),
+            preview_layout: PreviewLayout::default(),
+            total_entries: 0,
+            startup_path: path.clone(),
+            max_depth: None,
         };
+        visited::record(&mut app.recently_visited, &path);
         app.load_entries(path, Some(1))?;
         app.handle_current_entry()?;
 
         Ok(app)
     }
 
-    pub fn load_entries(&mut self, path: PathBuf, idx: Option<usize>) -> Result<()> {
-        self.cwd_entries = StatefulList::with_items(get_ok_entries(path.as_path())?);
+    pub fn set_options(&mut self, opts: AppOptions) {
+        self.select_output = opts.select_output;
+        self.readonly = opts.readonly;
+        self.force_root = opts.force_root;
+        self.max_depth = opts.max_depth;
+        self.watch = opts.watch.then(Watch::new);
+    }
+
+    /// Path of the highlighted entry.
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        self.cwd_entries.current().map(DirEntry::path)
+    }
+
+    pub fn current_dir(&self) -> &Path {
+        &self.cwd_path
+    }
+
+    /// Index of the highlighted entry in the current directory.
+    pub fn selected_index(&self) -> Option<usize> {
+        self.cwd_idx
+    }
+
+    /// Preview of the highlighted file as shown in the right pane.
+    pub fn preview(&self) -> Option<&str> {
+        self.content.as_deref()
+    }
+
+    /// Paths of the selected entries sorted.
+    pub fn selection(&self) -> Vec<&Path> {
+        let mut selection: Vec<_> = self.selection.iter().map(PathBuf::as_path).collect();
+        selection.sort();
+        selection
+    }
+
+    /// Error shown in the status bar.
+    pub fn error(&self) -> Option<&str> {
+        self.err.as_deref()
+    }
+
+    /// Paths picked with `--select-output`, available once the app exits.
+    pub fn output(&self) -> &[PathBuf] {
+        &self.output
+    }
+
+    /// Highlights the entry of the current directory named `name`. Returns
+    /// `false` if it's not listed.
+    pub fn select_by_name(&mut self, name: &OsStr) -> Result<bool> {
+        if !self.cwd_entries.select_by_name(name) {
+            return Ok(false);
+        }
+        self.cwd_idx = self.cwd_entries.current_idx();
+        self.handle_current_entry()?;
+        Ok(true)
+    }
+
+    /// Navigates to the directory at `path`, relative paths are resolved from
+    /// the current directory.
+    pub fn set_dir(&mut self, path: &Path) -> Result<()> {
+        let path = self.cwd_path.join(path);
+        if !path.is_dir() {
+            return Err(anyhow!("{} is not a directory", path.display()));
+        }
+        let path = fs::canonicalize(&path)?;
+        self.check_depth(&path)?;
+
+        let idx = self.saved_position(&path);
+        self.parent_idx = None;
+        self.load_entries(path, idx.or(Some(0)))?;
+        self.handle_current_entry()
+    }
+
+    /// Draws and handles events in the terminal until trav is quit.
+    pub fn run(&mut self) -> Result<()> {
+        let mut terminal = util::get_terminal()?;
+        loop {
+            terminal.draw(|f| {
+                self.draw_frame(f);
+            })?;
+
+            if let Err(e) = self.handle_event() {
+                crate::error!("{:#}", e);
+                return Err(e);
+            }
+
+            if let Some(mut cmd) = self.foreground.take() {
+                // hand the terminal over to the program until it exits
+                crate::debug!("running {:?}", cmd);
+                self.events.pause();
+                drop(terminal);
+                let res = cmd.status();
+                terminal = util::get_terminal()?;
+                self.events.resume();
+                match res {
+                    Ok(status) if !status.success() => {
+                        crate::warn!("{:?} exited with {}", cmd.get_program(), status);
+                        self.set_err(format!("{:?} exited with {}", cmd.get_program(), status));
+                    }
+                    Ok(_) => {}
+                    Err(e) => self.set_err(format!("failed to run {:?}: {}", cmd.get_program(), e)),
+                }
+            }
+
+            if self.suspend {
+                self.suspend = false;
+                // leave the alternate screen and raw mode while stopped, the new
+                // terminal starts with an empty buffer so the UI is fully redrawn
+                crate::debug!("suspending");
+                self.events.pause();
+                drop(terminal);
+                util::stop_process()?;
+                terminal = util::get_terminal()?;
+                self.events.resume();
+            }
+
+            if self.exit {
+                break;
+            }
+        }
+        // restore the terminal before the picked paths are printed
+        drop(terminal);
+        if let Err(e) = self.save_recently_visited() {
+            crate::warn!("failed to save recently visited directories: {:#}", e);
+        }
+        Ok(())
+    }
+
+    /// Reads entries of the directory at `path` ordered by the configured sort mode.
+    fn sorted_entries(&self, path: &Path) -> Result<Vec<DirEntry>> {
+        list_sorted(
+            path,
+            self.config.show_hidden,
+            self.config.sort_mode,
+            self.sort_options(),
+        )
+    }
+
+    fn sort_options(&self) -> SortOptions {
+        SortOptions {
+            reversed: self.sort_reversed,
+            ..self.config.sort_options()
+        }
+    }
+
+    /// Starts listing the directory at `path` for the preview panel.
+    /// Lists `path` in the child panel. A listing that fails right away
+    /// empties the panel like one failing in the background.
+    fn load_child_entries(&mut self, path: PathBuf) -> Result<()> {
+        let show_hidden = self.config.show_hidden;
+        let mode = self.config.sort_mode;
+        let opts = self.sort_options();
+        let tx = self.events.sender();
+        let res = ChildEntries::load(
+            path,
+            move |dir| list_sorted(dir, show_hidden, mode, opts),
+            move || {
+                let _ = tx.send(Event::Input(AppEvent::ChildEntriesLoaded));
+            },
+        );
+        match res {
+            Ok(entries) => {
+                self.child_entries = Some(entries);
+                Ok(())
+            }
+            Err(e) => {
+                self.child_entries = None;
+                Err(e)
+            }
+        }
+    }
+
+    fn update_child_entries(&mut self) {
+        if let Some(Err(e)) = self.child_entries.as_mut().map(ChildEntries::update) {
+            self.child_entries = None;
+            self.set_err(e.to_string());
+        }
+    }
+
+    fn load_entries(&mut self, path: PathBuf, idx: Option<usize>) -> Result<()> {
+        if path != self.cwd_path {
+            if let Some(idx) = self.cwd_idx {
+                self.positions.insert(self.cwd_path.clone(), idx);
+            }
+            self.history.retain(|p| p != &path);
+            self.history.push(path.clone());
+            if self.history.len() > MAX_HISTORY {
+                self.history.remove(0);
+            }
+            visited::record(&mut self.recently_visited, &path);
+        }
+
+        let entries = self.sorted_entries(path.as_path())?;
+        self.total_entries = entries.len();
+        let mut entries = self.filters.apply(entries);
+        if let Some(tag) = self.filters.tag {
+            entries.retain(|entry| self.tags.get(&entry.path()) == Some(&tag));
+        }
+        if let Some(watch) = &self.watch {
+            if path == self.cwd_path {
+                watch.decorate(&mut entries, std::mem::take(&mut self.cwd_entries.items));
+            }
+        }
+        self.cwd_entries = StatefulList::with_items(entries);
         if let Some(parent) = path.parent() {
-            self.parent = Some((parent.to_path_buf(), get_ok_entries(parent)?));
+            self.parent = Some((parent.to_path_buf(), self.sorted_entries(parent)?));
         }
         self.cwd_path = path;
 
@@ -69,6 +679,11 @@ impl TravApp {
         Ok(())
     }
 
+    /// Cursor position to use when entering the directory at `path`.
+    fn saved_position(&mut self, path: &Path) -> Option<usize> {
+        self.positions.get(&path.to_path_buf()).copied()
+    }
+
     fn next_entry(&mut self) {
         self.cwd_idx = self.cwd_entries.next();
     }
@@ -77,62 +692,2468 @@ impl TravApp {
         self.cwd_idx = self.cwd_entries.previous();
     }
 
-    fn restart_err(&mut self) {
-        if self.err.is_some() {
-            self.err = None;
+    fn go_to_parent(&mut self) -> Result<()> {
+        if let Some(parent) = self.cwd_path.parent() {
+            let parent = parent.to_path_buf();
+            let idx = self.saved_position(&parent).or(self.parent_idx);
+            if !self.enter_dir(parent, idx)? {
+                return Ok(());
+            }
         }
+        self.handle_current_entry()?;
+        self.parent_idx = None;
+
+        Ok(())
     }
 
-    fn handle_current_entry(&mut self) -> Result<()> {
+    /// Levels of `path` below the start directory, `None` if it's outside of
+    /// it.
+    fn depth(&self, path: &Path) -> Option<usize> {
+        path.strip_prefix(&self.startup_path)
+            .ok()
+            .map(|rel| rel.components().count())
+    }
+
+    /// Fails if `--max-depth` doesn't allow listing `path`, directories above
+    /// the start directory are out of bounds too.
+    fn check_depth(&self, path: &Path) -> Result<()> {
+        let max_depth = match self.max_depth {
+            Some(max_depth) => max_depth,
+            None => return Ok(()),
+        };
+        match self.depth(path) {
+            Some(depth) if depth <= max_depth => Ok(()),
+            Some(_) => Err(anyhow!("max depth reached")),
+            None => Err(anyhow!(
+                "--max-depth keeps trav within {}",
+                self.startup_path.display()
+            )),
+        }
+    }
+
+    /// Lists the directory at `path` highlighting `idx` unless it's out of the
+    /// `--max-depth` bounds, shows the error and returns `false` then.
+    fn enter_dir(&mut self, path: PathBuf, idx: Option<usize>) -> Result<bool> {
+        if let Err(e) = self.check_depth(&path) {
+            self.set_err(e.to_string());
+            return Ok(false);
+        }
+        self.load_entries(path, idx)?;
+        Ok(true)
+    }
+
+    /// Enters the highlighted directory or opens the highlighted file.
+    fn enter_current(&mut self) -> Result<()> {
         if let Some(entry) = self.cwd_entries.current() {
-            match entry.metadata() {
-                Ok(ref md) => {
-                    let file_type = md.file_type();
-                    if file_type.is_dir() {
-                        self.child_entries = Some(get_ok_entries(entry.path().as_path())?);
+            if let Ok(md) = entry.metadata() {
+                let path = entry.path();
+                let file_type = md.file_type();
+                if file_type.is_dir() {
+                    let idx = self.cwd_idx;
+                    let pos = self.saved_position(&path).or(Some(0));
+                    if !self.enter_dir(path, pos)? {
                         return Ok(());
-                    } else if file_type.is_symlink() {
-                        if let Ok(entries) = get_ok_entries(entry.path().as_path()) {
-                            self.child_entries = Some(entries);
+                    }
+                    self.handle_current_entry()?;
+                    self.parent_idx = idx;
+                    return Ok(());
+                } else if file_type.is_symlink() {
+                    if let Err(e) = self.check_depth(&path) {
+                        self.set_err(e.to_string());
+                        return Ok(());
+                    }
+                    let idx = self.cwd_idx;
+                    let pos = self.saved_position(&path).or(Some(0));
+                    if self.load_entries(path, pos).is_ok() {
+                        self.handle_current_entry()?;
+                        self.parent_idx = idx;
+                        return Ok(());
+                    }
+                } else if file_type.is_file() {
+                    if let Err(e) = self.open_with(DEFAULT_OPENER) {
+                        self.set_err(e.to_string());
+                    }
+                }
+            }
+        }
+        self.handle_current_entry()
+    }
+
+    /// Opens the highlighted file with `cmd`, a program optionally followed by
+    /// arguments. The path of the file is passed as the last argument.
+    fn open_with(&self, cmd: &str) -> Result<()> {
+        match self.cwd_entries.current() {
+            Some(entry) => spawn_opener(cmd, &entry.path()),
+            None => Ok(()),
+        }
+    }
+
+    /// Opens the selected files, or the highlighted one, each with its first
+    /// configured opener. Asks first if there are more than `open_max`.
+    fn open_selected(&mut self) -> Result<()> {
+        let files: Vec<_> = self
+            .targets()
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect();
+        if files.is_empty() {
+            return Err(anyhow!("no files to open"));
+        }
+        if files.len() <= self.config.open_max {
+            self.pending_open = files;
+            return self.open_pending();
+        }
+
+        let mut items = vec![CONFIRM_NO.to_string(), CONFIRM_YES.to_string()];
+        items.extend(files.iter().map(|path| {
+            format!(
+                "  {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            )
+        }));
+        self.popup = Some(Popup::new(
+            PopupKind::ConfirmOpen,
+            format!("open {} files?", files.len()),
+            items,
+        ));
+        self.pending_open = files;
+        Ok(())
+    }
+
+    fn open_pending(&mut self) -> Result<()> {
+        for path in std::mem::take(&mut self.pending_open) {
+            let opener = self
+                .config
+                .openers_for(&path)
+                .first()
+                .map(String::as_str)
+                .unwrap_or(DEFAULT_OPENER);
+            spawn_opener(opener, &path)?;
+        }
+        Ok(())
+    }
+
+    /// Starts another trav in the current directory, in a new window opened
+    /// with `new_window_cmd` or in a new tmux window when running in tmux.
+    /// Otherwise it takes over the terminal until it exits.
+    fn open_new_instance(&mut self) -> Result<()> {
+        let exe = env::current_exe()?;
+        let in_tmux = env::var_os("TMUX").is_some_and(|tmux| !tmux.is_empty());
+        let mut cmd = match &self.config.new_window_cmd {
+            Some(new_window) => {
+                let mut args = new_window.split_whitespace();
+                let program = args.next().ok_or_else(|| anyhow!("empty new_window_cmd"))?;
+                let mut cmd = process::Command::new(program);
+                cmd.args(args);
+                cmd
+            }
+            None if in_tmux => {
+                let mut cmd = process::Command::new("tmux");
+                cmd.arg("new-window").arg("-c").arg(&self.cwd_path);
+                cmd
+            }
+            None => {
+                let mut cmd = process::Command::new(exe);
+                cmd.arg(&self.cwd_path);
+                self.foreground = Some(cmd);
+                return Ok(());
+            }
+        };
+        cmd.arg(exe)
+            .arg(&self.cwd_path)
+            .current_dir(&self.cwd_path)
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("failed to run {:?}: {}", cmd.get_program(), e))?;
+        Ok(())
+    }
+
+    /// Opens the current directory in the graphical file manager without
+    /// waiting for it.
+    fn open_file_manager(&self) -> Result<()> {
+        let cmd = self
+            .config
+            .gui_file_manager
+            .as_deref()
+            .unwrap_or(DEFAULT_FILE_MANAGER);
+        let mut args = cmd.split_whitespace();
+        let program = args.next().ok_or_else(|| anyhow!("empty command"))?;
+
+        process::Command::new(program)
+            .args(args)
+            .arg(&self.cwd_path)
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("failed to run `{}`: {}", program, e))?;
+
+        Ok(())
+    }
+
+    /// Opens the highlighted text file in `$PAGER`.
+    fn open_in_pager(&mut self) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) => entry.path(),
+            None => return Ok(()),
+        };
+        let md = fs::metadata(&path)?;
+        if !md.is_file() || !properties::is_text_mime(properties::mime_type(&path, &md)) {
+            return Err(anyhow!("{} is not a text file", path.display()));
+        }
+
+        let pager = env::var("PAGER")
+            .ok()
+            .filter(|pager| !pager.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+        let mut args = pager.split_whitespace();
+        let mut cmd = process::Command::new(args.next().unwrap_or(DEFAULT_PAGER));
+        cmd.args(args).args(&self.config.pager_args).arg(path);
+        self.foreground = Some(cmd);
+
+        Ok(())
+    }
+
+    fn show_opener_picker(&mut self) {
+        let path = match self.cwd_entries.current() {
+            Some(entry) if entry.path().is_file() => entry.path(),
+            _ => return,
+        };
+        let mut openers = self.config.openers_for(&path).to_vec();
+        if !openers.iter().any(|o| o == DEFAULT_OPENER) {
+            openers.push(DEFAULT_OPENER.to_string());
+        }
+        openers.push(CUSTOM_OPENER.to_string());
+
+        self.popup = Some(Popup::new(PopupKind::Openers, "open with", openers));
+    }
+
+    fn handle_popup_key(&mut self, key: Key) -> Result<()> {
+        let popup = match &mut self.popup {
+            Some(popup) => popup,
+            None => return Ok(()),
+        };
+        match key {
+            Key::Down | Key::Char('j') => {
+                popup.items.next();
+            }
+            Key::Up | Key::Char('k') => {
+                popup.items.previous();
+            }
+            Key::Esc => self.popup = None,
+            Key::Char('q') => self.exit = true,
+            Key::Char('i') if popup.kind == PopupKind::Properties => self.popup = None,
+            Key::Char('\n') => {
+                let popup = self.popup.take();
+                let choice = popup.as_ref().and_then(Popup::current).unwrap_or_default();
+                let idx = popup.as_ref().and_then(|p| p.items.current_idx());
+                match popup.as_ref().map(|p| p.kind) {
+                    Some(PopupKind::Openers) if choice == CUSTOM_OPENER => {
+                        self.enter_input_mode(InputMode::OpenWith, InputWidget::new());
+                    }
+                    Some(PopupKind::Openers) => self.open_with(choice)?,
+                    Some(PopupKind::ContextMenu) => self.run_menu_action(choice)?,
+                    Some(PopupKind::ConfirmDelete) if choice == CONFIRM_YES => {
+                        if self.root && !self.force_root {
+                            self.confirm_delete_as_root();
+                        } else {
+                            self.delete_pending()?;
                         }
-                    } else if file_type.is_file() {
-                        if let Ok(file) = fs::File::open(entry.path().as_path()) {
-                            let reader = io::BufReader::new(file);
-                            let mut lines = String::new();
-                            for line in reader.lines().take(128) {
-                                if let Ok(line) = line {
-                                    lines.push_str(&line);
-                                    lines.push('\n');
-                                }
+                    }
+                    Some(PopupKind::ConfirmDeleteAsRoot) if choice == CONFIRM_YES => {
+                        self.delete_pending()?;
+                    }
+                    Some(PopupKind::ConfirmOpen) if choice == CONFIRM_YES => {
+                        if let Err(e) = self.open_pending() {
+                            self.set_err(e.to_string());
+                        }
+                    }
+                    Some(PopupKind::Favorites) => {
+                        let path = idx
+                            .and_then(|i| self.favorites.get(i))
+                            .map(|f| f.path.clone());
+                        if let Some(path) = path {
+                            if let Err(e) = self.open_favorite(&path) {
+                                self.set_err(e.to_string());
+                            }
+                        }
+                    }
+                    Some(PopupKind::Template) => {
+                        let template = self
+                            .templates
+                            .iter()
+                            .find(|t| t.file_name().is_some_and(|name| name == choice))
+                            .cloned();
+                        if let Some(path) = self.pending_file.take() {
+                            if let Err(e) = self.create_file(&path, template.as_deref()) {
+                                self.set_err(e.to_string());
                             }
-
-                            self.content = Some(lines);
-                            self.child_entries = None;
-                            self.err = None;
                         }
                     }
+                    Some(PopupKind::ConfirmDelete)
+                    | Some(PopupKind::ConfirmDeleteAsRoot)
+                    | Some(PopupKind::ConfirmOpen)
+                    | Some(PopupKind::Properties)
+                    | None => {}
                 }
-                Err(e) => self.err = Some(e.to_string()),
             }
+            _ => {}
         }
 
         Ok(())
     }
 
-    pub fn handle_event(&mut self) -> Result<()> {
-        match self.events.next()? {
-            Event::Input(input) => match input {
-                Key::Char('q') => {
-                    self.exit = true;
+    fn show_context_menu(&mut self, x: u16, y: u16) {
+        if self.cwd_entries.current().is_none() {
+            return;
+        }
+        let actions = [
+            MENU_OPEN,
+            MENU_OPEN_WITH,
+            MENU_COPY,
+            MENU_CUT,
+            MENU_RENAME,
+            MENU_DELETE,
+            MENU_PROPERTIES,
+        ];
+        let mut popup = Popup::new(
+            PopupKind::ContextMenu,
+            "",
+            actions.iter().map(|a| a.to_string()).collect(),
+        );
+        // mouse coordinates are 1-based
+        popup.anchor = Some((x.saturating_sub(1), y.saturating_sub(1)));
+        self.popup = Some(popup);
+    }
+
+    fn run_menu_action(&mut self, action: &str) -> Result<()> {
+        match action {
+            MENU_OPEN => self.enter_current()?,
+            MENU_OPEN_WITH => self.show_opener_picker(),
+            MENU_COPY => self.mark_targets(ClipboardOp::Copy),
+            MENU_CUT => self.mark_targets(ClipboardOp::Cut),
+            MENU_RENAME => self.start_rename(),
+            MENU_DELETE => self.confirm_delete(self.targets()),
+            MENU_PROPERTIES => self.show_properties()?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Entries targeted by file operations, the selection or if it's empty the
+    /// highlighted entry.
+    fn targets(&self) -> Vec<PathBuf> {
+        let mut targets: Vec<_> = if self.selection.is_empty() {
+            self.cwd_entries
+                .current()
+                .map(DirEntry::path)
+                .into_iter()
+                .collect()
+        } else {
+            self.selection.iter().cloned().collect()
+        };
+        targets.sort();
+        targets
+    }
+
+    fn check_modification(&self) -> Result<()> {
+        if self.readonly {
+            Err(anyhow!("read-only mode"))
+        } else if self.allows_modification() {
+            Ok(())
+        } else {
+            Err(anyhow!("modifying files is disabled"))
+        }
+    }
+
+    /// Shows why modifying files isn't allowed in the status bar. Returns
+    /// whether the operation may proceed.
+    fn modification_allowed_or_warn(&mut self) -> bool {
+        match self.check_modification() {
+            Ok(()) => true,
+            Err(e) => {
+                self.set_err(e.to_string());
+                false
+            }
+        }
+    }
+
+    fn mark_targets(&mut self, op: ClipboardOp) {
+        if !self.modification_allowed_or_warn() {
+            return;
+        }
+        let paths = self.targets();
+        if !paths.is_empty() {
+            self.clipboard = Some(Clipboard { op, paths });
+        }
+    }
+
+    fn paste(&mut self) -> Result<()> {
+        self.check_modification()?;
+        let clipboard = match &self.clipboard {
+            Some(clipboard) => clipboard.clone(),
+            None => return Ok(()),
+        };
+        let res = fileops::paste(&clipboard, &self.cwd_path);
+        if clipboard.op == ClipboardOp::Cut {
+            self.clipboard = None;
+            self.selection.clear();
+        }
+        let pasted = res?;
+
+        self.reload_entries()?;
+        if let Some(path) = pasted.first() {
+            self.select_path(path);
+            self.handle_current_entry()?;
+        }
+        Ok(())
+    }
+
+    fn start_rename(&mut self) {
+        if !self.modification_allowed_or_warn() {
+            return;
+        }
+        if let Some(entry) = self.cwd_entries.current() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            self.enter_input_mode(InputMode::Rename, InputWidget::with_text(name));
+        }
+    }
+
+    fn rename_current(&mut self, name: &str) -> Result<()> {
+        self.check_modification()?;
+        let path = match self.cwd_entries.current() {
+            Some(entry) => entry.path(),
+            None => return Ok(()),
+        };
+        if name.is_empty() || name.contains('/') {
+            return Err(anyhow!("invalid name `{}`", name));
+        }
+        let new_path = self.cwd_path.join(name);
+        if new_path.exists() {
+            return Err(anyhow!("{} already exists", new_path.display()));
+        }
+        fs::rename(&path, &new_path)?;
+        if self.selection.remove(&path) {
+            self.selection.insert(new_path.clone());
+        }
+
+        self.reload_entries()?;
+        self.select_path(&new_path);
+        self.handle_current_entry()
+    }
+
+    /// Prompts for the name of a copy of the highlighted file.
+    fn start_duplicate(&mut self) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) => entry.path(),
+            None => return Ok(()),
+        };
+        if !path.is_file() {
+            return Err(anyhow!("only files can be duplicated"));
+        }
+        if !self.modification_allowed_or_warn() {
+            return Ok(());
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.enter_input_mode(
+            InputMode::Duplicate,
+            InputWidget::with_text(fileops::duplicate_name(&name)),
+        );
+        Ok(())
+    }
+
+    /// Copies the highlighted file to `name` in the current directory and
+    /// highlights the copy.
+    fn duplicate_current(&mut self, name: &str) -> Result<()> {
+        self.check_modification()?;
+        let path = match self.cwd_entries.current() {
+            Some(entry) => entry.path(),
+            None => return Ok(()),
+        };
+        if name.is_empty() || name.contains('/') {
+            return Err(anyhow!("invalid name `{}`", name));
+        }
+        let new_path = self.cwd_path.join(name);
+        fileops::duplicate_file(&path, &new_path)?;
+
+        self.reload_entries()?;
+        self.select_path(&new_path);
+        self.handle_current_entry()
+    }
+
+    /// Prompts for the target of a new symlink starting with the highlighted
+    /// entry.
+    fn start_link(&mut self) {
+        if !self.modification_allowed_or_warn() {
+            return;
+        }
+        let target = self
+            .cwd_entries
+            .current()
+            .map(|entry| entry.path().to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.enter_input_mode(InputMode::LinkTarget, InputWidget::with_text(target));
+    }
+
+    fn start_link_name(&mut self, target: &str) -> Result<()> {
+        if target.is_empty() {
+            return Err(anyhow!("missing link target"));
+        }
+        let target = PathBuf::from(target);
+        let name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.link_target = Some(target);
+        self.enter_input_mode(InputMode::LinkName, InputWidget::with_text(name));
+        Ok(())
+    }
+
+    /// Creates a symlink named `name` in the current directory pointing to the
+    /// target entered before.
+    fn create_link(&mut self, name: &str) -> Result<()> {
+        self.check_modification()?;
+        let target = match self.link_target.take() {
+            Some(target) => target,
+            None => return Ok(()),
+        };
+        if name.is_empty() || name.contains('/') {
+            return Err(anyhow!("invalid name `{}`", name));
+        }
+        let link = self.cwd_path.join(name);
+        fileops::create_symlink(&target, &link)?;
+
+        self.reload_entries()?;
+        self.select_path(&link);
+        self.handle_current_entry()
+    }
+
+    /// Creates the file `name` in the current directory, if templates match its
+    /// extension one can be picked first.
+    fn start_new_file(&mut self, name: &str) -> Result<()> {
+        self.check_modification()?;
+        if name.is_empty() || name.contains('/') {
+            return Err(anyhow!("invalid name `{}`", name));
+        }
+        let path = self.cwd_path.join(name);
+        if path.exists() {
+            return Err(anyhow!("{} already exists", path.display()));
+        }
+        let matching = template::matching(&self.templates, name);
+        if matching.is_empty() {
+            return self.create_file(&path, None);
+        }
+        let mut items = vec![NO_TEMPLATE.to_string()];
+        items.extend(
+            matching
+                .iter()
+                .filter_map(|t| t.file_name())
+                .map(|name| name.to_string_lossy().to_string()),
+        );
+        self.popup = Some(Popup::new(PopupKind::Template, "template", items));
+        self.pending_file = Some(path);
+        Ok(())
+    }
+
+    fn create_file(&mut self, path: &Path, template: Option<&Path>) -> Result<()> {
+        self.check_modification()?;
+        template::create_file(path, template, util::today())?;
+        self.reload_entries()?;
+        self.select_path(path);
+        self.handle_current_entry()
+    }
+
+    /// Prompts for the name of an archive of the targeted entries.
+    fn start_archive(&mut self) {
+        if !self.modification_allowed_or_warn() {
+            return;
+        }
+        if self.archive.as_ref().is_some_and(Archive::is_running) {
+            self.set_err("an archive is already being created".to_string());
+            return;
+        }
+        self.pending_archive = self.targets();
+        if self.pending_archive.is_empty() {
+            return;
+        }
+        let name = Local::now().format(ARCHIVE_NAME_FORMAT).to_string();
+        self.enter_input_mode(InputMode::Archive, InputWidget::with_text(name));
+    }
+
+    fn create_archive(&mut self, name: &str) -> Result<()> {
+        self.check_modification()?;
+        if name.is_empty() || name.contains('/') {
+            return Err(anyhow!("invalid name `{}`", name));
+        }
+        let paths = std::mem::take(&mut self.pending_archive);
+        self.archive = Some(Archive::create(self.cwd_path.join(name), &paths)?);
+        Ok(())
+    }
+
+    /// Reports the result of the archive once `tar` exits.
+    fn archive_tick(&mut self) -> Result<()> {
+        let archive = match &mut self.archive {
+            Some(archive) => archive,
+            None => return Ok(()),
+        };
+        archive.update();
+        if let Some(res) = archive.result.take() {
+            let path = archive.path.clone();
+            self.archive = None;
+            match res {
+                Ok(()) => self.flash(format!(
+                    "created {}",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                )),
+                Err(e) => self.set_err(format!("failed to create archive: {}", e)),
+            }
+            self.reload_entries()?;
+        }
+        Ok(())
+    }
+
+    fn confirm_delete(&mut self, targets: Vec<PathBuf>) {
+        let title = match targets.as_slice() {
+            [] => return,
+            [path] => format!(
+                "delete {}?",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            paths => format!("delete {} entries?", paths.len()),
+        };
+        if !self.modification_allowed_or_warn() {
+            return;
+        }
+        self.pending_delete = targets;
+        self.popup = Some(Popup::new(
+            PopupKind::ConfirmDelete,
+            title,
+            vec![CONFIRM_NO.to_string(), CONFIRM_YES.to_string()],
+        ));
+    }
+
+    /// Second confirmation of a deletion required when running as root.
+    fn confirm_delete_as_root(&mut self) {
+        self.popup = Some(Popup::new(
+            PopupKind::ConfirmDeleteAsRoot,
+            "running as root, really delete?",
+            vec![CONFIRM_NO.to_string(), CONFIRM_YES.to_string()],
+        ));
+    }
+
+    fn delete_pending(&mut self) -> Result<()> {
+        self.check_modification()?;
+        for path in std::mem::take(&mut self.pending_delete) {
+            match &mut self.panel {
+                PanelMode::Duplicates(duplicates) => {
+                    fileops::remove_path(&path)?;
+                    duplicates.remove(&path);
+                }
+                PanelMode::Scan(scan) => {
+                    match scan.kind {
+                        ScanKind::EmptyDirs => fileops::remove_empty_dir(&path)?,
+                        ScanKind::BrokenLinks | ScanKind::Name(_) => fileops::remove_path(&path)?,
+                    }
+                    scan.remove(&path);
+                }
+                _ => fileops::remove_path(&path)?,
+            }
+            self.selection.remove(&path);
+        }
+        self.reload_entries()
+    }
+
+    fn show_properties(&mut self) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) => entry.path(),
+            None => return Ok(()),
+        };
+        let lines = properties::properties(&path)?;
+        self.popup = Some(Popup::new(PopupKind::Properties, "properties", lines));
+        Ok(())
+    }
+
+    /// Highlights changes of the current directory in watch mode.
+    fn watch_tick(&mut self) -> Result<()> {
+        let watch = match &mut self.watch {
+            Some(watch) => watch,
+            None => return Ok(()),
+        };
+        watch.tick();
+        if watch.poll(&self.cwd_path)? {
+            return self.reload_entries();
+        }
+        if watch.is_animating() || self.cwd_entries.items.iter().any(|e| e.change().is_some()) {
+            watch.update(&mut self.cwd_entries.items);
+            let last = self.cwd_entries.items.len().checked_sub(1);
+            self.cwd_entries
+                .select(self.cwd_idx.and_then(|idx| last.map(|l| idx.min(l))));
+            self.cwd_idx = self.cwd_entries.current_idx();
+        }
+        Ok(())
+    }
+
+    fn show_recent_files(&mut self) {
+        let files = recent::recent_files(&self.cwd_path, self.config.recent_depth);
+        let mut list = StatefulList::with_items(files);
+        list.select(Some(0));
+        self.panel = PanelMode::Recent(list);
+    }
+
+    fn handle_recent_key(&mut self, key: Key) -> Result<()> {
+        let recent = match &mut self.panel {
+            PanelMode::Recent(recent) => recent,
+            _ => return Ok(()),
+        };
+        match key {
+            Key::Down | Key::Char('j') => {
+                recent.next();
+            }
+            Key::Up | Key::Char('k') => {
+                recent.previous();
+            }
+            Key::Char('g') | Key::Home => {
+                recent.select_first();
+            }
+            Key::Char('G') | Key::End => {
+                recent.select_last();
+            }
+            Key::Esc => self.panel = PanelMode::Entries,
+            Key::Char('q') => self.exit = true,
+            Key::Char('\n') | Key::Right => {
+                let path = recent.current().map(|file| file.path.clone());
+                self.panel = PanelMode::Entries;
+                if let Some(path) = path {
+                    self.reveal(&path)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn show_recent_dirs(&mut self) {
+        let dirs = self.recently_visited.iter().cloned().collect();
+        let mut list = StatefulList::with_items(dirs);
+        list.select(Some(0));
+        self.panel = PanelMode::RecentDirs(list);
+    }
+
+    fn handle_recent_dirs_key(&mut self, key: Key) -> Result<()> {
+        let dirs = match &mut self.panel {
+            PanelMode::RecentDirs(dirs) => dirs,
+            _ => return Ok(()),
+        };
+        match key {
+            Key::Down | Key::Char('j') => {
+                dirs.next();
+            }
+            Key::Up | Key::Char('k') => {
+                dirs.previous();
+            }
+            Key::Char('g') | Key::Home => {
+                dirs.select_first();
+            }
+            Key::Char('G') | Key::End => {
+                dirs.select_last();
+            }
+            Key::Esc => self.panel = PanelMode::Entries,
+            Key::Char('q') => self.exit = true,
+            Key::Char('\n') | Key::Right => {
+                let dir = dirs.current().cloned();
+                self.panel = PanelMode::Entries;
+                if let Some(dir) = dir {
+                    if let Err(e) = self.set_dir(&dir) {
+                        self.set_err(e.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn show_split_view(&mut self) -> Result<()> {
+        let entries = self.sorted_entries(&self.cwd_path)?;
+        let mut top = Pane::new(self.cwd_path.clone(), entries);
+        if let Some(idx) = self.cwd_idx {
+            top.entries.select(Some(idx));
+        }
+        let bottom = Pane::new(self.cwd_path.clone(), self.sorted_entries(&self.cwd_path)?);
+        self.panel = PanelMode::SplitVertical(SplitView::new(top, bottom));
+        Ok(())
+    }
+
+    /// Lists `path` in the focused pane highlighting `highlight` if given.
+    fn open_pane(&mut self, path: PathBuf, highlight: Option<&Path>) -> Result<()> {
+        if let Err(e) = self.check_depth(&path) {
+            self.set_err(e.to_string());
+            return Ok(());
+        }
+        let mut pane = Pane::new(path.clone(), self.sorted_entries(&path)?);
+        if let Some(idx) =
+            highlight.and_then(|p| pane.entries.items.iter().position(|e| e.path() == p))
+        {
+            pane.entries.select(Some(idx));
+        }
+        if let PanelMode::SplitVertical(split) = &mut self.panel {
+            *split.focused_mut() = pane;
+        }
+        Ok(())
+    }
+
+    /// Rereads both panes keeping the highlighted positions.
+    fn reload_split(&mut self) -> Result<()> {
+        let split = match &self.panel {
+            PanelMode::SplitVertical(split) => split,
+            _ => return Ok(()),
+        };
+        let mut panes = vec![];
+        for pane in &split.panes {
+            let mut reloaded = Pane::new(pane.path.clone(), self.sorted_entries(&pane.path)?);
+            let len = reloaded.entries.items.len();
+            if let (Some(idx), true) = (pane.entries.current_idx(), len > 0) {
+                reloaded.entries.select(Some(idx.min(len - 1)));
+            }
+            panes.push(reloaded);
+        }
+        if let PanelMode::SplitVertical(split) = &mut self.panel {
+            for (i, pane) in panes.into_iter().enumerate() {
+                split.panes[i] = pane;
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies or moves the selected entries of the focused pane, or the
+    /// highlighted one if none are selected, into the other pane's directory.
+    fn transfer_in_split(&mut self, op: ClipboardOp) -> Result<()> {
+        self.check_modification()?;
+        let split = match &self.panel {
+            PanelMode::SplitVertical(split) => split,
+            _ => return Ok(()),
+        };
+        let (src, dst) = (split.focused(), split.unfocused());
+        if src.path == dst.path {
+            return Err(anyhow!("both panes show the same directory"));
+        }
+        let mut paths: Vec<_> = src
+            .entries
+            .items
+            .iter()
+            .map(DirEntry::path)
+            .filter(|path| self.selection.contains(path))
+            .collect();
+        if paths.is_empty() {
+            paths.extend(src.current());
+        }
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let dir = dst.path.clone();
+        let clipboard = Clipboard { op, paths };
+        let res = fileops::paste(&clipboard, &dir);
+        if op == ClipboardOp::Cut {
+            for path in &clipboard.paths {
+                self.selection.remove(path);
+            }
+        }
+        let pasted = res?;
+        self.reload_split()?;
+        self.flash(format!(
+            "{} {} entries to {}",
+            if op == ClipboardOp::Copy {
+                "copied"
+            } else {
+                "moved"
+            },
+            pasted.len(),
+            dir.display()
+        ));
+        Ok(())
+    }
+
+    /// Applies the mode `spec` to every targeted entry, symlinks are skipped.
+    fn batch_chmod(&mut self, spec: &str) -> Result<()> {
+        self.check_modification()?;
+        let mut changed = 0;
+        for path in self.targets() {
+            let md = fs::symlink_metadata(&path)?;
+            if md.file_type().is_symlink() {
+                continue;
+            }
+            let mode = util::parse_symbolic_mode(md.mode(), spec)?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode))
+                .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+            changed += 1;
+        }
+        self.reload_entries()?;
+        self.flash(format!("changed permissions of {} entries", changed));
+        Ok(())
+    }
+
+    fn handle_split_key(&mut self, key: Key) -> Result<()> {
+        let split = match &mut self.panel {
+            PanelMode::SplitVertical(split) => split,
+            _ => return Ok(()),
+        };
+        match key {
+            Key::Down | Key::Char('j') => {
+                split.focused_mut().entries.next();
+            }
+            Key::Up | Key::Char('k') => {
+                split.focused_mut().entries.previous();
+            }
+            Key::Char('g') | Key::Home => {
+                split.focused_mut().entries.select_first();
+            }
+            Key::Char('G') | Key::End => {
+                split.focused_mut().entries.select_last();
+            }
+            Key::Char('\t') => split.switch_focus(),
+            Key::Char('+') => split.grow(),
+            Key::Char('-') => split.shrink(),
+            Key::Char(' ') => {
+                if let Some(path) = split.focused().current() {
+                    if !self.selection.remove(&path) {
+                        self.selection.insert(path);
+                    }
+                }
+            }
+            Key::Char('\n') | Key::Right | Key::Char('l') => {
+                if let Some(path) = split.focused().current().filter(|p| p.is_dir()) {
+                    self.open_pane(path, None)?;
+                }
+            }
+            Key::Left | Key::Char('h') | Key::Backspace => {
+                let path = split.focused().path.clone();
+                if let Some(parent) = path.parent() {
+                    self.open_pane(parent.to_path_buf(), Some(&path))?;
+                }
+            }
+            Key::Char('c') => {
+                if let Err(e) = self.transfer_in_split(ClipboardOp::Copy) {
+                    self.set_err(e.to_string());
+                }
+            }
+            Key::Char('m') => {
+                if let Err(e) = self.transfer_in_split(ClipboardOp::Cut) {
+                    self.set_err(e.to_string());
+                }
+            }
+            // continue in the directory of the focused pane
+            Key::Esc => {
+                let path = split.focused().path.clone();
+                self.panel = PanelMode::Entries;
+                self.parent_idx = None;
+                self.enter_dir(path, Some(0))?;
+                self.handle_current_entry()?;
+            }
+            Key::Char('q') => self.exit = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Adds the highlighted entry to the favorites or removes it.
+    fn toggle_favorite(&mut self) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) => entry.path(),
+            None => return Ok(()),
+        };
+        let added = favorites::toggle(&mut self.favorites, &path);
+        favorites::save(&self.favorites, self.config.state_dir.as_deref())?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if added {
+            self.flash(format!("added {} to favorites", name));
+        } else {
+            self.flash(format!("removed {} from favorites", name));
+        }
+        Ok(())
+    }
+
+    /// Lists the favorites with their paths and last access in a popup.
+    fn show_favorites(&mut self) {
+        if self.favorites.is_empty() {
+            self.set_err("no favorites, add some with `f`".to_string());
+            return;
+        }
+        let names: Vec<_> = self
+            .favorites
+            .iter()
+            .map(|f| f.path.file_name().unwrap_or_default().to_string_lossy())
+            .collect();
+        let home = self.config.home_dir.as_deref();
+        let name_width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0);
+        let path_width = self
+            .favorites
+            .iter()
+            .map(|f| util::abbreviate_home(&f.path, home).chars().count())
+            .max()
+            .unwrap_or(0);
+        let items = self
+            .favorites
+            .iter()
+            .zip(&names)
+            .map(|(f, name)| {
+                format!(
+                    "{:name_width$}  {:path_width$}  {}",
+                    name,
+                    util::abbreviate_home(&f.path, home),
+                    f.accessed_at()
+                )
+            })
+            .collect();
+        self.popup = Some(Popup::new(PopupKind::Favorites, "favorites", items));
+    }
+
+    /// Navigates to the favorite directory or highlights the favorite file.
+    fn open_favorite(&mut self, path: &Path) -> Result<()> {
+        if path.is_dir() {
+            self.set_dir(path)?;
+        } else if path.exists() {
+            self.reveal(path)?;
+        } else {
+            return Err(anyhow!("{} no longer exists", path.display()));
+        }
+        favorites::touch(&mut self.favorites, path);
+        favorites::save(&self.favorites, self.config.state_dir.as_deref())
+    }
+
+    /// Marks the highlighted directory as a workspace root or unmarks it.
+    fn toggle_workspace(&mut self) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) if entry.path().is_dir() => entry.path(),
+            Some(_) => return Err(anyhow!("only directories can be workspaces")),
+            None => return Ok(()),
+        };
+        let added = workspaces::toggle(&mut self.workspaces, &path)?;
+        workspaces::save(&self.workspaces, self.config.state_dir.as_deref())
+            .map_err(|e| anyhow!("failed to save workspaces: {}", e))?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if added {
+            self.flash(format!(
+                "{} is workspace {}, jump to it with Alt-{}",
+                name,
+                self.workspaces.len(),
+                self.workspaces.len()
+            ));
+        } else {
+            self.flash(format!("{} is no longer a workspace", name));
+        }
+        Ok(())
+    }
+
+    /// Navigates to the `n`th workspace root counting from 1.
+    fn open_workspace(&mut self, n: usize) -> Result<()> {
+        let root = match n.checked_sub(1).and_then(|i| self.workspaces.get(i)) {
+            Some(root) => root.clone(),
+            None => return Err(anyhow!("no workspace {}, mark one with `W`", n)),
+        };
+        if !root.is_dir() {
+            return Err(anyhow!("{} no longer exists", root.display()));
+        }
+        self.set_dir(&root)
+    }
+
+    /// Tags the highlighted entry with `tag` or removes the tag if it already
+    /// has it.
+    fn toggle_tag(&mut self, tag: u8) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) => entry.path(),
+            None => return Ok(()),
+        };
+        if self.tags.get(&path) == Some(&tag) {
+            self.tags.remove(&path);
+        } else {
+            self.tags.insert(path, tag);
+        }
+        tags::save(&self.tags, self.config.state_dir.as_deref())?;
+        if self.filters.tag.is_some() {
+            self.reload_entries()?;
+        }
+        Ok(())
+    }
+
+    /// Saves the recently visited directories for the next session.
+    fn save_recently_visited(&self) -> Result<()> {
+        visited::save(&self.recently_visited, self.config.state_dir.as_deref())
+    }
+
+    /// Navigates to the directory containing `path` and highlights it.
+    fn reveal(&mut self, path: &Path) -> Result<()> {
+        let dir = match path.parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => return Ok(()),
+        };
+        self.parent_idx = None;
+        if !self.enter_dir(dir, None)? {
+            return Ok(());
+        }
+        if !self.select_path(path) {
+            self.set_err(format!(
+                "{} is hidden by the active filters",
+                path.display()
+            ));
+            self.cwd_entries.select(Some(0));
+            self.cwd_idx = self.cwd_entries.current_idx();
+        }
+        self.handle_current_entry()
+    }
+
+    /// Navigates to the directory entered in the jump prompt.
+    fn jump_to(&mut self, input: &str) -> Result<()> {
+        if input.trim().is_empty() {
+            return Ok(());
+        }
+        let path = jump::resolve(input, &self.cwd_path, &self.config.bookmarks);
+        self.set_dir(&path)
+    }
+
+    /// Copies the path of the highlighted entry or of the current directory
+    /// to the system clipboard.
+    fn copy_path(&mut self, dir: bool) -> Result<()> {
+        let path = if dir {
+            self.cwd_path.clone()
+        } else {
+            match self.cwd_entries.current() {
+                Some(entry) => entry.path(),
+                None => return Ok(()),
+            }
+        };
+        util::clipboard::copy(&path.to_string_lossy())?;
+        self.flash(if dir {
+            "directory path copied"
+        } else {
+            "path copied"
+        });
+        Ok(())
+    }
+
+    /// Copies the path of the highlighted entry relative to the directory
+    /// trav was started in.
+    fn copy_relative_path(&mut self) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) => util::relative_path(&entry.path(), &self.startup_path),
+            None => return Ok(()),
+        };
+        util::clipboard::copy(&path.to_string_lossy())?;
+        self.flash("relative path copied");
+        Ok(())
+    }
+
+    /// Copies the tree of the current directory to the clipboard.
+    fn copy_tree(&mut self) -> Result<()> {
+        let tree = DirTree::generate(
+            &self.cwd_path,
+            self.config.tree_depth,
+            &IgnoreRules::load(&self.cwd_path),
+            self.config.show_hidden,
+        );
+        util::clipboard::copy(&tree.text())?;
+        self.flash(format!(
+            "tree of {} directories and {} files copied",
+            tree.dirs, tree.files
+        ));
+        Ok(())
+    }
+
+    /// Shows `msg` as an error for the configured time.
+    pub fn set_err<S: Into<String>>(&mut self, msg: S) {
+        self.err = Some(msg.into());
+        self.err_deadline = match self.config.error_display_secs {
+            0 => None,
+            secs => Some(Instant::now() + Duration::from_secs(secs)),
+        };
+    }
+
+    fn flash<S: Into<String>>(&mut self, msg: S) {
+        self.status_msg = Some((msg.into(), Instant::now()));
+    }
+
+    /// Compares the file in the clipboard with the highlighted file.
+    fn diff_with_clipboard(&mut self) -> Result<()> {
+        let old = match &self.clipboard {
+            Some(Clipboard {
+                op: ClipboardOp::Copy,
+                paths,
+            }) if paths.len() == 1 && paths[0].is_file() => paths[0].clone(),
+            _ => return Err(anyhow!("copy a single file with `c` to compare it")),
+        };
+        let new = match self.cwd_entries.current() {
+            Some(entry) if entry.path().is_file() => entry.path(),
+            _ => return Err(anyhow!("highlight a file to compare with")),
+        };
+        self.panel = PanelMode::Diff(DiffView::new(old, new)?);
+        Ok(())
+    }
+
+    /// Compares two files in the configured diff tool run in the foreground.
+    /// These are the two selected files or the targeted file and the copied one.
+    fn run_diff_tool(&mut self) -> Result<()> {
+        let template = match &self.config.diff_tool {
+            Some(template) => template.clone(),
+            None => {
+                return Err(anyhow!(
+                    "set `diff_tool` in the config or pass --diff-tool to compare files"
+                ))
+            }
+        };
+        let mut files = self.targets();
+        if files.len() == 1 {
+            match &self.clipboard {
+                Some(clipboard) if clipboard.paths.len() == 1 => {
+                    files.insert(0, clipboard.paths[0].clone())
+                }
+                _ => return Err(anyhow!("select a second file or copy it with `c`")),
+            }
+        }
+        let (old, new) = match files.as_slice() {
+            [old, new] if old.is_file() && new.is_file() => (old, new),
+            _ => return Err(anyhow!("select two files to compare")),
+        };
+        self.foreground = Some(diff::diff_tool_command(&template, old, new)?);
+        Ok(())
+    }
+
+    fn handle_diff_key(&mut self, key: Key) {
+        let diff = match &mut self.panel {
+            PanelMode::Diff(diff) => diff,
+            _ => return,
+        };
+        match key {
+            Key::Down | Key::Char('j') => diff.scroll_by(1),
+            Key::Up | Key::Char('k') => diff.scroll_by(-1),
+            Key::PageDown => diff.scroll_by(DIFF_PAGE),
+            Key::PageUp => diff.scroll_by(-DIFF_PAGE),
+            Key::Char('g') | Key::Home => diff.scroll = 0,
+            Key::Char('G') | Key::End => diff.scroll_by(isize::MAX / 2),
+            Key::Esc => self.panel = PanelMode::Entries,
+            Key::Char('q') => self.exit = true,
+            _ => {}
+        }
+    }
+
+    fn handle_du_key(&mut self, key: Key) -> Result<()> {
+        let du = match &mut self.panel {
+            PanelMode::DuMap(du) => du,
+            _ => return Ok(()),
+        };
+        match key {
+            Key::Right | Key::Char('l') | Key::Down | Key::Char('j') => du.select_next(),
+            Key::Left | Key::Char('h') | Key::Up | Key::Char('k') => du.select_previous(),
+            Key::Esc => self.panel = PanelMode::Entries,
+            Key::Char('q') => self.exit = true,
+            Key::Char('\n') => {
+                let dir = match du.current() {
+                    Some(entry) if entry.is_dir => entry.path.clone(),
+                    _ => return Ok(()),
+                };
+                let idx = self.saved_position(&dir);
+                if !self.enter_dir(dir.clone(), idx.or(Some(0)))? {
+                    return Ok(());
+                }
+                self.parent_idx = None;
+                self.handle_current_entry()?;
+                self.panel = PanelMode::DuMap(DuMap::scan(dir));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_duplicates_key(&mut self, key: Key) -> Result<()> {
+        let duplicates = match &mut self.panel {
+            PanelMode::Duplicates(duplicates) => duplicates,
+            _ => return Ok(()),
+        };
+        match key {
+            Key::Down | Key::Char('j') => duplicates.select_next(),
+            Key::Up | Key::Char('k') => duplicates.select_previous(),
+            Key::Esc => self.panel = PanelMode::Entries,
+            Key::Char('q') => self.exit = true,
+            Key::Char('d') => {
+                if let Some(path) = duplicates.current().cloned() {
+                    self.confirm_delete(vec![path]);
+                }
+            }
+            Key::Char('\n') | Key::Right => {
+                let path = duplicates.current().cloned();
+                self.panel = PanelMode::Entries;
+                if let Some(path) = path {
+                    self.reveal(&path)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_scan_key(&mut self, key: Key) -> Result<()> {
+        let scan = match &mut self.panel {
+            PanelMode::Scan(scan) => scan,
+            _ => return Ok(()),
+        };
+        match key {
+            Key::Down | Key::Char('j') => {
+                scan.found.next();
+            }
+            Key::Up | Key::Char('k') => {
+                scan.found.previous();
+            }
+            Key::Char('g') | Key::Home => {
+                scan.found.select_first();
+            }
+            Key::Char('G') | Key::End => {
+                scan.found.select_last();
+            }
+            Key::Esc => self.panel = PanelMode::Entries,
+            Key::Char('q') => self.exit = true,
+            Key::Char('d') => {
+                if let Some(path) = scan.found.current().cloned() {
+                    self.confirm_delete(vec![path]);
+                }
+            }
+            Key::Char('D') if !scan.is_scanning() => {
+                let paths = scan.found.items.clone();
+                self.confirm_delete(paths);
+            }
+            Key::Char('\n') | Key::Right => {
+                let path = scan.found.current().cloned();
+                self.panel = PanelMode::Entries;
+                if let Some(path) = path {
+                    self.reveal(&path)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_stats_key(&mut self, key: Key) -> Result<()> {
+        let stats = match &mut self.panel {
+            PanelMode::Stats(stats) => stats,
+            _ => return Ok(()),
+        };
+        match key {
+            Key::Down | Key::Char('j') => stats.select_next(),
+            Key::Up | Key::Char('k') => stats.select_previous(),
+            Key::Esc => self.panel = PanelMode::Entries,
+            Key::Char('q') => self.exit = true,
+            Key::Char('\n') => {
+                let extension = match stats.current().map(|stat| stat.extension.clone()) {
+                    Some(Some(extension)) => extension,
+                    Some(None) => {
+                        self.set_err("files without extension can't be filtered".to_string());
+                        return Ok(());
+                    }
+                    None => return Ok(()),
+                };
+                self.panel = PanelMode::Entries;
+                self.filters.extensions = Some(vec![extension]);
+                self.reload_entries()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn show_mode_editor(&mut self) -> Result<()> {
+        if !cfg!(unix) {
+            return Err(anyhow!(
+                "changing permissions is not supported on this platform"
+            ));
+        }
+        self.check_modification()?;
+        if let Some(entry) = self.cwd_entries.current() {
+            self.mode_editor = Some(ModeEditor::new(entry.path())?);
+        }
+        Ok(())
+    }
+
+    /// Lists the permission changes removing the bits matched by the permission
+    /// filter from the targeted entries, or all listed ones if nothing is
+    /// selected.
+    fn show_perm_fix(&mut self) -> Result<()> {
+        if !cfg!(unix) {
+            return Err(anyhow!(
+                "changing permissions is not supported on this platform"
+            ));
+        }
+        self.check_modification()?;
+        let bits = match self.filters.perm {
+            Some(perm @ PermFilter::WorldWritable) | Some(perm @ PermFilter::Suid) => perm.bits(),
+            _ => {
+                return Err(anyhow!(
+                    "filter entries with `:perm world-writable` or `:perm suid` first"
+                ))
+            }
+        };
+        let paths: Vec<_> = if self.selection.is_empty() {
+            self.cwd_entries.items.iter().map(DirEntry::path).collect()
+        } else {
+            let mut paths: Vec<_> = self.selection.iter().cloned().collect();
+            paths.sort();
+            paths
+        };
+        self.panel = PanelMode::PermFix(PermFix::new(&paths, bits)?);
+        Ok(())
+    }
+
+    fn handle_perm_fix_key(&mut self, key: Key) -> Result<()> {
+        let fix = match &mut self.panel {
+            PanelMode::PermFix(fix) => fix,
+            _ => return Ok(()),
+        };
+        match key {
+            Key::Down | Key::Char('j') => {
+                fix.changes.next();
+            }
+            Key::Up | Key::Char('k') => {
+                fix.changes.previous();
+            }
+            Key::Esc | Key::Char('n') => self.panel = PanelMode::Entries,
+            Key::Char('q') => self.exit = true,
+            Key::Char('\n') | Key::Char('y') => {
+                let res = fix.apply();
+                self.panel = PanelMode::Entries;
+                if let Err(e) = res {
+                    self.set_err(format!("failed to fix permissions: {}", e));
+                }
+                self.reload_entries()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_mode_editor_key(&mut self, key: Key) -> Result<()> {
+        let editor = match &mut self.mode_editor {
+            Some(editor) => editor,
+            None => return Ok(()),
+        };
+        if key == Key::Char('q') {
+            self.exit = true;
+            return Ok(());
+        }
+        match editor.handle_key(key) {
+            EditorAction::None => {}
+            EditorAction::Cancel => self.mode_editor = None,
+            EditorAction::Commit => {
+                let res = editor.commit();
+                self.mode_editor = None;
+                res?;
+                self.reload_entries()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open_editor(&mut self) -> Result<()> {
+        self.check_modification()?;
+        let path = match self.cwd_entries.current() {
+            Some(entry) if entry.path().is_file() => entry.path(),
+            _ => return Err(anyhow!("highlight a file to edit it")),
+        };
+        self.editor = Some(TextEditor::open(path, self.config.inline_edit_max_bytes)?);
+        self.events.disable_exit_key();
+        Ok(())
+    }
+
+    fn close_editor(&mut self) -> Result<()> {
+        self.editor = None;
+        self.events.enable_exit_key();
+        self.handle_current_entry()
+    }
+
+    fn handle_editor_key(&mut self, key: Key) -> Result<()> {
+        let editor = match &mut self.editor {
+            Some(editor) => editor,
+            None => return Ok(()),
+        };
+        match editor.handle_key(key) {
+            EditAction::None => {}
+            EditAction::Save => {
+                editor.save()?;
+                self.flash("saved");
+            }
+            EditAction::Discard => self.close_editor()?,
+        }
+        Ok(())
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if let MouseEvent::Press(MouseButton::Right, x, y) = mouse {
+            self.restart_err();
+            self.show_context_menu(x, y);
+        }
+    }
+
+    /// Options for rendering entries of the side panels.
+    fn entry_options(&self) -> ItemOptions<'static> {
+        ItemOptions {
+            height: self.config.entry_height,
+            width_mode: self.config.entry_width_mode,
+            icons: self.config.nerd_fonts.then_some(&NERD_FONTS),
+            ..ItemOptions::default()
+        }
+    }
+
+    /// Number of listed entries shown in the title, `[7/42]` if some are
+    /// filtered out.
+    fn entry_count(&self) -> String {
+        if self.filters.is_active() {
+            format!("[{}/{}]", self.cwd_entries.items.len(), self.total_entries)
+        } else {
+            format!("[{}]", self.total_entries)
+        }
+    }
+
+    /// Last line the preview can be scrolled to, counting wrapped lines.
+    fn preview_max_scroll(&self) -> u16 {
+        let (width, height) = self.preview_size;
+        let lines = match &self.content {
+            Some(content) => {
+                let width = width.saturating_sub(self.gutter_width(content) as u16);
+                visual_line_count(content, width, self.preview_wrap)
+            }
+            None => 0,
+        };
+        lines.saturating_sub(height as usize).min(u16::MAX as usize) as u16
+    }
+
+    /// Columns taken by the line number gutter of the preview.
+    fn gutter_width(&self, content: &str) -> usize {
+        if self.preview_line_numbers {
+            gutter_digits(content) + GUTTER_SEPARATOR.chars().count() + 1
+        } else {
+            0
+        }
+    }
+
+    fn scroll_preview(&mut self, by: i32) {
+        let scroll = (self.preview_scroll as i32 + by).max(0) as u16;
+        self.preview_scroll = scroll.min(self.preview_max_scroll());
+    }
+
+    /// Number of entries fitting in the current directory panel.
+    fn page_size(&self) -> usize {
+        (self.list_height / self.config.entry_height.max(1) as u16).max(1) as usize
+    }
+
+    fn first_entry(&mut self) {
+        self.cwd_idx = self.cwd_entries.select_first();
+    }
+
+    fn last_entry(&mut self) {
+        self.cwd_idx = self.cwd_entries.select_last();
+    }
+
+    /// Rereads the current directory keeping the cursor on the same entry if it
+    /// still exists, otherwise at the same position.
+    fn reload_entries(&mut self) -> Result<()> {
+        let current = self.cwd_entries.current().map(DirEntry::path);
+        self.load_entries(self.cwd_path.clone(), self.cwd_idx)?;
+        if let Some(path) = current {
+            self.select_path(&path);
+        }
+        self.handle_current_entry()
+    }
+
+    /// Moves the cursor to the entry at `path`. Returns `false` if it's not listed.
+    fn select_path(&mut self, path: &Path) -> bool {
+        let found = path.parent() == Some(self.cwd_path.as_path())
+            && path
+                .file_name()
+                .is_some_and(|name| self.cwd_entries.select_by_name(name));
+        if found {
+            self.cwd_idx = self.cwd_entries.current_idx();
+        }
+        found
+    }
+
+    fn cycle_sort_mode(&mut self) -> Result<()> {
+        self.config.sort_mode = self.config.sort_mode.cycle();
+        self.reload_entries()
+    }
+
+    fn toggle_sort_reversed(&mut self) -> Result<()> {
+        self.sort_reversed = !self.sort_reversed;
+        self.reload_entries()
+    }
+
+    fn toggle_dirs_first(&mut self) -> Result<()> {
+        self.config.dirs_first = !self.config.dirs_first;
+        self.reload_entries()
+    }
+
+    fn enter_input_mode(&mut self, mode: InputMode, input: InputWidget) {
+        self.input_mode = mode;
+        self.input = input;
+        self.events.disable_exit_key();
+    }
+
+    fn leave_input_mode(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.input.clear();
+        self.completion = None;
+        self.history_cursor = None;
+        self.events.enable_exit_key();
+    }
+
+    fn handle_input_key(&mut self, key: Key) -> Result<()> {
+        match key {
+            Key::Esc => {
+                if self.input_mode == InputMode::ExtensionFilter {
+                    self.filters.extensions = None;
+                    self.filters.end_search();
+                    self.reload_entries()?;
+                }
+                self.leave_input_mode();
+            }
+            Key::Char('\n') => {
+                let text = self.input.text().to_string();
+                let mode = self.input_mode;
+                // leave first as commands may open another prompt
+                self.leave_input_mode();
+                if mode == InputMode::Command {
+                    self.remember_command(&text);
+                }
+                let res = match mode {
+                    InputMode::Command => self.run_command(&text),
+                    InputMode::Export => self.export_paths(&text),
+                    InputMode::OpenWith => self.open_with(&text),
+                    InputMode::Rename => self.rename_current(&text),
+                    InputMode::LinkTarget => self.start_link_name(&text),
+                    InputMode::LinkName => self.create_link(&text),
+                    InputMode::Archive => self.create_archive(&text),
+                    InputMode::NewFile => self.start_new_file(&text),
+                    InputMode::Duplicate => self.duplicate_current(&text),
+                    InputMode::Jump => self.jump_to(&text),
+                    _ => Ok(()),
+                };
+                if let Err(e) = res {
+                    self.set_err(e.to_string());
+                }
+            }
+            Key::Up | Key::Ctrl('p') if self.input_mode == InputMode::Command => {
+                let (history, text) = (&self.command_history, self.input.text());
+                let cursor = self
+                    .history_cursor
+                    .get_or_insert_with(|| HistoryCursor::new(history, text));
+                if let Some(cmd) = cursor.previous(history) {
+                    self.input = InputWidget::with_text(cmd);
+                }
+            }
+            Key::Down | Key::Ctrl('n') if self.input_mode == InputMode::Command => {
+                let history = &self.command_history;
+                if let Some(cmd) = self
+                    .history_cursor
+                    .as_mut()
+                    .and_then(|cursor| cursor.next(history))
+                {
+                    self.input = InputWidget::with_text(cmd);
+                }
+            }
+            // Ctrl-I arrives as Tab
+            Key::Char('\t') if self.is_searching() => {
+                self.filters.toggle_case()?;
+                self.reload_entries()?;
+            }
+            Key::Char('\t') if self.input_mode == InputMode::Jump => {
+                let (history, bookmarks) = (&self.history, &self.config.bookmarks);
+                let text = self.input.text();
+                let completion = self
+                    .completion
+                    .get_or_insert_with(|| Completion::new(text, history, bookmarks));
+                if let Some(candidate) = completion.cycle() {
+                    self.input = InputWidget::with_text(candidate).expanding_tilde();
+                }
+            }
+            key => {
+                if self.input_mode == InputMode::Jump {
+                    self.completion = None;
+                }
+                if self.input.handle_key(key) && self.input_mode == InputMode::ExtensionFilter {
+                    self.filters.extensions = parse_extensions(self.input.text());
+                    self.reload_entries()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the prompt edits the extension or regex filter.
+    fn is_searching(&self) -> bool {
+        match self.input_mode {
+            InputMode::ExtensionFilter => true,
+            InputMode::Command => self.input.text().starts_with("regex "),
+            _ => false,
+        }
+    }
+
+    /// Adds `cmd` to the command history and saves it for later sessions.
+    fn remember_command(&mut self, cmd: &str) {
+        history::push(&mut self.command_history, cmd);
+        if let Err(e) = history::save(&self.command_history, self.config.state_dir.as_deref()) {
+            crate::warn!("failed to save command history: {}", e);
+        }
+    }
+
+    fn run_command(&mut self, cmd: &str) -> Result<()> {
+        match cmd.parse()? {
+            Command::Size(size) => {
+                self.filters.size = size;
+                self.reload_entries()?;
+            }
+            Command::Date(date) => {
+                self.filters.date = date;
+                self.reload_entries()?;
+            }
+            Command::Regex(regex) => {
+                self.filters.regex = regex
+                    .map(|re| self.filters.regex(re.as_str()))
+                    .transpose()?;
+                self.filters.end_search();
+                self.reload_entries()?;
+            }
+            Command::Cd(Some(path)) => self.jump_to(&path)?,
+            Command::Cd(None) => {
+                self.enter_input_mode(InputMode::Jump, InputWidget::new().expanding_tilde())
+            }
+            Command::Pipe(cmd) => self.pipe_preview(&cmd)?,
+            Command::Duplicates => {
+                self.panel = PanelMode::Duplicates(Duplicates::scan(self.cwd_path.clone()))
+            }
+            Command::Scan(kind) => {
+                self.panel = PanelMode::Scan(PathScan::start(kind, self.cwd_path.clone()))
+            }
+            Command::Perm(perm) => {
+                self.filters.perm = perm;
+                self.reload_entries()?;
+            }
+            Command::Tagged(tag) => {
+                self.filters.tag = tag;
+                self.reload_entries()?;
+            }
+            Command::Select(regex) => self.select_matching(&regex, true),
+            Command::Deselect(regex) => self.select_matching(&regex, false),
+            Command::DiffTool => self.run_diff_tool()?,
+            Command::Chmod(spec) => self.batch_chmod(&spec)?,
+            Command::Encoding(encoding) => self.decode_preview(&encoding)?,
+            Command::TabWidth(width) => {
+                self.config.preview_tab_width = width;
+                self.handle_current_entry()?;
+            }
+            Command::Stat => self.show_stat()?,
+            Command::Tree(depth) => self.show_tree(depth),
+            Command::Debug => self.show_debug = !self.show_debug,
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the preview of the highlighted file with the output of `cmd`
+    /// run on its content in the background until another entry is previewed
+    /// or `Esc` is pressed.
+    fn pipe_preview(&mut self, cmd: &str) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) if entry.path().is_file() => entry.path(),
+            _ => return Err(anyhow!("highlight a file to pipe through `{}`", cmd)),
+        };
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.show_output(format!("{} | {}", name, cmd), "Running...".to_string());
+
+        let (tx, rx) = mpsc::channel();
+        let events = self.events.sender();
+        let cmd = cmd.to_string();
+        thread::spawn(move || {
+            let res = fs::read(&path)
+                .map_err(Into::into)
+                .and_then(|input| util::pipe_through(&cmd, input));
+            // the receiver is gone if the preview changed meanwhile
+            if tx.send(res).is_ok() {
+                let _ = events.send(Event::Input(AppEvent::PipeFinished));
+            }
+        });
+        self.piped = Some(rx);
+        Ok(())
+    }
+
+    /// Shows the output of the command started by `pipe_preview` once it
+    /// exits.
+    fn update_piped(&mut self) {
+        let res = match self.piped.as_ref().map(mpsc::Receiver::try_recv) {
+            Some(Ok(res)) => res,
+            _ => return,
+        };
+        self.piped = None;
+        match res {
+            Ok((stdout, stderr)) => {
+                self.content = Some(stdout);
+                if !stderr.trim().is_empty() {
+                    self.set_err(stderr.trim_end().to_string());
+                }
+            }
+            Err(e) => {
+                self.content = Some(String::new());
+                self.set_err(e.to_string());
+            }
+        }
+    }
+
+    /// Replaces the preview with the output of `stat` for the highlighted entry
+    /// until another entry is previewed or `Esc` is pressed.
+    fn show_stat(&mut self) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) => entry.path(),
+            None => return Ok(()),
+        };
+        let output = process::Command::new("stat")
+            .args(STAT_ARGS)
+            .arg(&path)
+            .stdin(process::Stdio::null())
+            .output()
+            .map_err(|e| anyhow!("failed to run stat: {}", e))?;
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("stat failed: {}", err.trim()));
+        }
+        let text = self.preview_text(&String::from_utf8_lossy(&output.stdout));
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.show_output(format!("{} [stat]", name), text);
+        Ok(())
+    }
+
+    /// Replaces the preview with the tree of the current directory `depth`
+    /// levels deep, `tree_depth` if not given, and a summary line.
+    fn show_tree(&mut self, depth: Option<usize>) {
+        let tree = DirTree::generate(
+            &self.cwd_path,
+            depth.unwrap_or(self.config.tree_depth),
+            &IgnoreRules::load(&self.cwd_path),
+            self.config.show_hidden,
+        );
+        let text = format!("{}\n{}\n", tree.text(), tree.summary());
+        self.show_output(format!("{}/ [tree]", dir_name(&self.cwd_path)), text);
+    }
+
+    /// Shows `text` titled `title` in the preview until another entry is
+    /// previewed or `Esc` is pressed.
+    fn show_output(&mut self, title: String, text: String) {
+        self.piped = None;
+        self.content = Some(text);
+        self.child_entries = None;
+        self.preview_scroll = 0;
+        self.preview_title = Some(title);
+    }
+
+    /// Copies the command output shown in the preview to the clipboard.
+    fn copy_output(&mut self) -> Result<()> {
+        if let Some(content) = &self.content {
+            util::clipboard::copy(content)?;
+            self.flash("preview copied");
+        }
+        Ok(())
+    }
+
+    /// First lines of `text` with tabs expanded as shown in the preview.
+    fn preview_text(&self, text: &str) -> String {
+        let tab_width = self.config.preview_tab_width as usize;
+        let mut content = String::new();
+        for line in text.lines().take(PREVIEW_LINES) {
+            content.push_str(&util::expand_tabs(line, tab_width));
+            content.push('\n');
+        }
+        content
+    }
+
+    /// Rereads the previewed file decoding it from `encoding`.
+    fn decode_preview(&mut self, encoding: &str) -> Result<()> {
+        let path = match self.cwd_entries.current() {
+            Some(entry) if entry.path().is_file() => entry.path(),
+            _ => return Err(anyhow!("highlight a file to decode")),
+        };
+        let (text, name) = encoding::decode(&fs::read(&path)?, encoding)?;
+        self.content = Some(self.preview_text(&text));
+        self.flash(format!("decoded as {}", name));
+        Ok(())
+    }
+
+    fn clear_filters(&mut self) -> Result<()> {
+        if self.filters.is_active() {
+            self.filters = Filters::default();
+            self.reload_entries()?;
+        }
+        Ok(())
+    }
+
+    fn toggle_selected(&mut self) {
+        if let Some(entry) = self.cwd_entries.current() {
+            let path = entry.path();
+            if !self.selection.remove(&path) {
+                self.selection.insert(path);
+            }
+        }
+    }
+
+    /// Adds all visible entries of the current directory to the selection.
+    fn select_all(&mut self) {
+        self.selection
+            .extend(self.cwd_entries.items.iter().map(DirEntry::path));
+    }
+
+    /// Selects or unselects visible entries of the current directory whose
+    /// full path matches `regex`.
+    fn select_matching(&mut self, regex: &Regex, select: bool) {
+        for path in self.cwd_entries.items.iter().map(DirEntry::path) {
+            if regex.is_match(&path.to_string_lossy()) {
+                if select {
+                    self.selection.insert(path);
+                } else {
+                    self.selection.remove(&path);
+                }
+            }
+        }
+    }
+
+    /// Inverts the selection of all visible entries of the current directory,
+    /// selected entries outside of it are left untouched.
+    fn invert_selection(&mut self) {
+        for path in self.cwd_entries.items.iter().map(DirEntry::path) {
+            if !self.selection.remove(&path) {
+                self.selection.insert(path);
+            }
+        }
+    }
+
+    /// Writes paths of the selected entries, or all visible entries if nothing is
+    /// selected, to the file given in `args`. Paths are separated by newlines or
+    /// by null bytes if `--null-separated` is passed.
+    /// Prompts for the file the listed or selected paths are written to.
+    fn start_export(&mut self) {
+        if !self.modification_allowed_or_warn() {
+            return;
+        }
+        self.enter_input_mode(InputMode::Export, InputWidget::new());
+    }
+
+    fn export_paths(&self, args: &str) -> Result<()> {
+        self.check_modification()?;
+        let mut null_separated = false;
+        let mut target = vec![];
+        for arg in args.split_whitespace() {
+            match arg {
+                "--null-separated" | "-0" => null_separated = true,
+                arg => target.push(arg),
+            }
+        }
+        if target.is_empty() {
+            return Err(anyhow!("no output file given"));
+        }
+        let target = self.cwd_path.join(target.join(" "));
+        if fs::symlink_metadata(&target).is_ok() {
+            return Err(anyhow!("{} already exists", target.display()));
+        }
+
+        let mut paths: Vec<_> = if self.selection.is_empty() {
+            self.cwd_entries.items.iter().map(DirEntry::path).collect()
+        } else {
+            self.selection.iter().cloned().collect()
+        };
+        paths.sort();
+
+        let sep = if null_separated { '\0' } else { '\n' };
+        let mut out = String::new();
+        for path in paths {
+            out.push_str(&path.to_string_lossy());
+            out.push(sep);
+        }
+
+        util::write_atomic(&target, out.as_bytes())
+    }
+
+    /// Captures the current state of the app as a `Session`.
+    pub fn session(&self) -> Session {
+        let mut selection: Vec<_> = self.selection.iter().cloned().collect();
+        selection.sort();
+
+        Session {
+            cwd_path: self.cwd_path.clone(),
+            highlighted: self
+                .cwd_entries
+                .current()
+                .map(|entry| entry.file_name().to_string_lossy().to_string()),
+            selection,
+            sort_mode: self.config.sort_mode,
+            sort_reversed: self.sort_reversed,
+            dirs_first: self.config.dirs_first,
+            extensions: self.filters.extensions.clone(),
+            size_filter: self.filters.size.as_ref().map(|f| f.spec()),
+            date_filter: self.filters.date.as_ref().map(|f| f.spec().to_string()),
+            regex_filter: self.filters.regex.as_ref().map(|f| f.to_string()),
+            perm_filter: self.filters.perm.map(|f| f.to_string()),
+            tag_filter: self.filters.tag.map(|tag| tags::name(tag).to_string()),
+            case_sensitive: self.filters.case_sensitive,
+        }
+    }
+
+    pub fn restore_session(&mut self, session: Session) -> Result<()> {
+        self.config.sort_mode = session.sort_mode;
+        self.config.dirs_first = session.dirs_first;
+        self.sort_reversed = session.sort_reversed;
+        let case_sensitive = session.case_sensitive;
+        self.filters = Filters {
+            extensions: session.extensions,
+            size: session
+                .size_filter
+                .as_deref()
+                .map(parse_size_filter)
+                .transpose()?,
+            date: session
+                .date_filter
+                .as_deref()
+                .map(parse_date_filter)
+                .transpose()?,
+            regex: session
+                .regex_filter
+                .as_deref()
+                .map(|re| Regex::with_case(re, !case_sensitive))
+                .transpose()?,
+            perm: session.perm_filter.as_deref().map(str::parse).transpose()?,
+            tag: session
+                .tag_filter
+                .as_deref()
+                .map(tags::parse_tag)
+                .transpose()?,
+            case_sensitive,
+        };
+        self.selection = session.selection.into_iter().collect();
+        self.parent_idx = None;
+        self.check_depth(&session.cwd_path)?;
+        self.load_entries(session.cwd_path, Some(0))?;
+        if let Some(name) = &session.highlighted {
+            self.cwd_entries.select_by_name(OsStr::new(name));
+            self.cwd_idx = self.cwd_entries.current_idx();
+        }
+        self.handle_current_entry()
+    }
+
+    /// Whether operations modifying the filesystem are permitted. They are
+    /// disabled in read-only mode and when trav is used as a file picker.
+    fn allows_modification(&self) -> bool {
+        !self.readonly && !self.select_output
+    }
+
+    /// Confirms the picked paths in `select_output` mode. These are the selected
+    /// entries or, if nothing is selected, the highlighted entry.
+    fn confirm_output(&mut self) {
+        let mut output: Vec<_> = if self.selection.is_empty() {
+            self.cwd_entries
+                .current()
+                .map(DirEntry::path)
+                .into_iter()
+                .collect()
+        } else {
+            self.selection.iter().cloned().collect()
+        };
+        output.sort();
+        self.output = output;
+        self.exit = true;
+    }
+
+    fn restart_err(&mut self) {
+        if self.err.is_some() {
+            self.err = None;
+            self.err_deadline = None;
+        }
+    }
+
+    fn handle_current_entry(&mut self) -> Result<()> {
+        self.preview_title = None;
+        self.piped = None;
+        if let Some(entry) = self.cwd_entries.current() {
+            match entry.metadata() {
+                Ok(ref md) => {
+                    let file_type = md.file_type();
+                    if !file_type.is_file() {
+                        self.preview_counts = None;
+                    }
+                    if file_type.is_dir() {
+                        if let Err(e) = self.load_child_entries(entry.path()) {
+                            self.set_err(e.to_string());
+                        }
+                        return Ok(());
+                    } else if file_type.is_symlink() {
+                        let _ = self.load_child_entries(entry.path());
+                    } else if file_type.is_file() {
+                        if let Ok(file) = fs::File::open(entry.path().as_path()) {
+                            let reader = io::BufReader::new(file);
+                            let mut lines = String::new();
+                            let tab_width = self.config.preview_tab_width as usize;
+                            for line in reader.lines().take(PREVIEW_LINES).flatten() {
+                                lines.push_str(&util::expand_tabs(&line, tab_width));
+                                lines.push('\n');
+                            }
+
+                            self.content = Some(lines);
+                            self.child_entries = None;
+                            self.err = None;
+                            self.err_deadline = None;
+                            let path = entry.path();
+                            if self.preview_counts.as_ref().map(|c| &c.path) != Some(&path) {
+                                self.preview_counts = Some(FileCounts::start(path));
+                                self.preview_scroll = 0;
+                            }
+                        }
+                    }
+                }
+                Err(e) => self.set_err(e.to_string()),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_resize_key(&mut self, key: Key) {
+        let divider = match self.resizing {
+            Some(divider) => divider,
+            None => return,
+        };
+        match key {
+            Key::Left | Key::Char('h') => self.move_divider(divider, false),
+            Key::Right | Key::Char('l') => self.move_divider(divider, true),
+            Key::Char('\t') => self.resizing = Some(1 - divider),
+            Key::Char('=') => self.panel_widths = DEFAULT_PANEL_WIDTHS,
+            Key::Esc | Key::Char('\n') | Key::Ctrl('w') => self.resizing = None,
+            Key::Char('q') => self.exit = true,
+            _ => {}
+        }
+    }
+
+    /// Moves the `divider` between two panels by a step, widening the panel on
+    /// its left if `right` is set. Neither panel gets narrower than
+    /// `MIN_PANEL_WIDTH`.
+    fn move_divider(&mut self, divider: usize, right: bool) {
+        let (grown, shrunk) = if right {
+            (divider, divider + 1)
+        } else {
+            (divider + 1, divider)
+        };
+        let step = PANEL_RESIZE_STEP.min(self.panel_widths[shrunk].saturating_sub(MIN_PANEL_WIDTH));
+        self.panel_widths[shrunk] -= step;
+        self.panel_widths[grown] += step;
+    }
+
+    /// Sender of events handled by `handle_event`, e.g. keys to drive the app
+    /// without a terminal.
+    pub fn event_sender(&self) -> mpsc::Sender<Event<AppEvent>> {
+        self.events.sender()
+    }
+
+    pub fn handle_event(&mut self) -> Result<()> {
+        let event = match self.events.next()? {
+            Event::Input(AppEvent::Key(key)) => Event::Input(key),
+            Event::CtrlInput(AppEvent::Key(key)) => Event::CtrlInput(key),
+            Event::Input(AppEvent::ChildEntriesLoaded) => {
+                self.update_child_entries();
+                return Ok(());
+            }
+            Event::Input(AppEvent::PipeFinished) => {
+                self.update_piped();
+                return Ok(());
+            }
+            Event::CtrlInput(_) => return Ok(()),
+            Event::Mouse(mouse) => Event::Mouse(mouse),
+            Event::Tick => Event::Tick,
+        };
+        match event {
+            Event::Input(input) if self.popup.is_some() => {
+                self.handle_popup_key(input)?;
+            }
+            Event::Input(input) if self.editor.is_some() => {
+                self.restart_err();
+                if let Err(e) = self.handle_editor_key(input) {
+                    self.set_err(format!("failed to save: {}", e));
+                }
+            }
+            Event::Input(input) if self.mode_editor.is_some() => {
+                self.restart_err();
+                if let Err(e) = self.handle_mode_editor_key(input) {
+                    self.set_err(format!("failed to change permissions: {}", e));
+                }
+            }
+            Event::Input(input) if self.resizing.is_some() => self.handle_resize_key(input),
+            Event::Input(input) if self.input_mode != InputMode::Normal => {
+                self.handle_input_key(input)?;
+            }
+            Event::Input(input) if !self.panel.is_entries() => {
+                self.restart_err();
+                match self.panel {
+                    PanelMode::Recent(_) => self.handle_recent_key(input)?,
+                    PanelMode::RecentDirs(_) => self.handle_recent_dirs_key(input)?,
+                    PanelMode::PermFix(_) => self.handle_perm_fix_key(input)?,
+                    PanelMode::SplitVertical(_) => self.handle_split_key(input)?,
+                    PanelMode::Diff(_) => self.handle_diff_key(input),
+                    PanelMode::DuMap(_) => self.handle_du_key(input)?,
+                    PanelMode::Duplicates(_) => self.handle_duplicates_key(input)?,
+                    PanelMode::Scan(_) => self.handle_scan_key(input)?,
+                    PanelMode::Stats(_) => self.handle_stats_key(input)?,
+                    PanelMode::Entries => {}
+                }
+            }
+            Event::Input(input) => match input {
+                Key::Char('q') => {
+                    self.exit = true;
+                }
+                Key::Esc => {
+                    self.restart_err();
+                    if self.preview_title.is_some() {
+                        self.handle_current_entry()?;
+                    } else {
+                        self.clear_filters()?;
+                    }
+                }
+                Key::Char(':') => {
+                    self.restart_err();
+                    self.enter_input_mode(InputMode::Command, InputWidget::new());
+                }
+                Key::Char('?') => {
+                    self.restart_err();
+                    self.show_output("keys".to_string(), help::text());
+                }
+                Key::Ctrl('r') => {
+                    self.restart_err();
+                    let cmd = match &self.filters.regex {
+                        Some(regex) => format!("regex {}", regex),
+                        None => "regex ".to_string(),
+                    };
+                    self.enter_input_mode(InputMode::Command, InputWidget::with_text(cmd));
+                }
+                Key::Ctrl('c') => {
+                    self.restart_err();
+                    if let Err(e) = self.copy_path(false) {
+                        self.set_err(format!("failed to copy path: {}", e));
+                    }
+                }
+                // terminals can't distinguish Ctrl-Shift-C from Ctrl-C
+                Key::Alt('c') => {
+                    self.restart_err();
+                    if let Err(e) = self.copy_path(true) {
+                        self.set_err(format!("failed to copy path: {}", e));
+                    }
+                }
+                // Ctrl-Alt-C is sent as Escape followed by Ctrl-C
+                Key::Alt('\x03') => {
+                    self.restart_err();
+                    if let Err(e) = self.copy_tree() {
+                        self.set_err(format!("failed to copy tree: {}", e));
+                    }
+                }
+                // terminals can't distinguish Ctrl-Shift-N from Ctrl-N
+                Key::Ctrl('n') => {
+                    self.restart_err();
+                    if let Err(e) = self.open_new_instance() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                // Ctrl-D already compares with the copied file
+                Key::Alt('d') => {
+                    self.restart_err();
+                    if let Err(e) = self.start_duplicate() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                // Ctrl-Alt-T is sent as Escape followed by Ctrl-T
+                Key::Alt('\x14') => {
+                    self.restart_err();
+                    self.show_tree(None);
+                }
+                Key::Char('y') if self.preview_title.is_some() => {
+                    self.restart_err();
+                    if let Err(e) = self.copy_output() {
+                        self.set_err(format!("failed to copy preview: {}", e));
+                    }
+                }
+                // terminals can't distinguish Ctrl-Shift-R from Ctrl-R
+                Key::Alt('r') => {
+                    self.restart_err();
+                    if let Err(e) = self.copy_relative_path() {
+                        self.set_err(format!("failed to copy path: {}", e));
+                    }
+                }
+                Key::Ctrl('e') => {
+                    self.restart_err();
+                    if let Err(e) = self.open_editor() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Ctrl('p') | Key::Char('M') => {
+                    self.restart_err();
+                    if let Err(e) = self.show_mode_editor() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Ctrl('u') => {
+                    self.restart_err();
+                    self.panel = PanelMode::DuMap(DuMap::scan(self.cwd_path.clone()));
+                }
+                Key::Ctrl('z') => {
+                    self.suspend = true;
+                }
+                // Ctrl-S already saves the session
+                Key::Ctrl('x') => {
+                    self.restart_err();
+                    self.panel = PanelMode::Stats(ExtStats::scan(self.cwd_path.clone()));
+                }
+                Key::Ctrl('d') => {
+                    self.restart_err();
+                    if let Err(e) = self.diff_with_clipboard() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Char('p') => {
+                    self.restart_err();
+                    if let Err(e) = self.open_in_pager() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Ctrl('g') => {
+                    self.restart_err();
+                    self.enter_input_mode(InputMode::Jump, InputWidget::new().expanding_tilde());
+                }
+                Key::Char('f') => {
+                    self.restart_err();
+                    if let Err(e) = self.toggle_favorite() {
+                        self.set_err(format!("failed to save favorites: {}", e));
+                    }
+                }
+                Key::Ctrl('w') => {
+                    self.restart_err();
+                    self.resizing = Some(0);
+                }
+                // terminals send Ctrl-M as Enter
+                Key::Char('W') => {
+                    self.restart_err();
+                    if let Err(e) = self.toggle_workspace() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Alt(c @ '1'..='9') => {
+                    self.restart_err();
+                    if let Err(e) = self.open_workspace((c as u8 - b'0') as usize) {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Ctrl('f') => {
+                    self.restart_err();
+                    self.show_favorites();
+                }
+                // Ctrl-R already opens the regex filter prompt
+                Key::Alt('e') => {
+                    self.restart_err();
+                    self.show_recent_files();
+                }
+                Key::Char(' ') => {
+                    self.restart_err();
+                    self.toggle_selected();
+                    self.next_entry();
+                    self.handle_current_entry()?;
+                }
+                Key::Ctrl('a') => {
+                    self.restart_err();
+                    self.select_all();
+                }
+                // terminals send Ctrl-I as a tab character
+                Key::Char('\t') => {
+                    self.restart_err();
+                    self.invert_selection();
+                }
+                Key::Ctrl('l') => {
+                    self.restart_err();
+                    self.reload_entries()?;
+                }
+                Key::Ctrl('s') => {
+                    self.restart_err();
+                    if let Err(e) = self.session().save(self.config.state_dir.as_deref()) {
+                        self.set_err(format!("failed to save session: {}", e));
+                    }
+                }
+                Key::Char('E') => {
+                    self.restart_err();
+                    self.start_export();
+                }
+                Key::Char('e') => {
+                    self.restart_err();
+                    let exts = self
+                        .filters
+                        .extensions
+                        .as_ref()
+                        .map(|exts| exts.join(","))
+                        .unwrap_or_default();
+                    self.enter_input_mode(InputMode::ExtensionFilter, InputWidget::with_text(exts));
+                }
+                Key::Char('s') => {
+                    self.restart_err();
+                    self.cycle_sort_mode()?;
+                }
+                Key::Char('r') => {
+                    self.restart_err();
+                    self.toggle_sort_reversed()?;
+                }
+                Key::Char('d') => {
+                    self.restart_err();
+                    self.toggle_dirs_first()?;
+                }
+                Key::Char('O') => {
+                    self.restart_err();
+                    if let Err(e) = self.open_selected() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Ctrl('o') => {
+                    self.restart_err();
+                    if let Err(e) = self.open_file_manager() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Char('w') => {
+                    self.preview_wrap = !self.preview_wrap;
+                    self.preview_scroll = self.preview_scroll.min(self.preview_max_scroll());
+                }
+                Key::Char(c @ '1'..='5') => {
+                    self.restart_err();
+                    if let Err(e) = self.toggle_tag(c as u8 - b'0') {
+                        self.set_err(format!("failed to save tags: {}", e));
+                    }
+                }
+                Key::Char('#') => self.preview_line_numbers = !self.preview_line_numbers,
+                // p already opens the pager
+                Key::Char('P') => self.preview_layout = self.preview_layout.next(),
+                Key::Char('J') => self.scroll_preview(1),
+                Key::Char('K') => self.scroll_preview(-1),
+                Key::Char('n') => {
+                    self.restart_err();
+                    if self.modification_allowed_or_warn() {
+                        self.enter_input_mode(InputMode::NewFile, InputWidget::new());
+                    }
+                }
+                Key::Char('S') => {
+                    self.restart_err();
+                    self.show_split_view()?;
                 }
-                Key::Left => {
+                Key::Char('D') => {
                     self.restart_err();
-                    if let Some(parent) = self.cwd_path.parent() {
-                        let parent = parent.to_path_buf();
-                        self.load_entries(parent, self.parent_idx)?;
+                    if let Err(e) = self.run_diff_tool() {
+                        self.set_err(e.to_string());
                     }
-                    self.handle_current_entry()?;
-                    self.parent_idx = None;
+                }
+                Key::Left => {
+                    self.restart_err();
+                    self.go_to_parent()?;
                 }
                 Key::Down => {
                     self.restart_err();
@@ -144,95 +3165,315 @@ impl TravApp {
                     self.prev_entry();
                     self.handle_current_entry()?;
                 }
-                Key::Right | Key::Char('\n') => {
+                Key::Char('\n')
+                    if self.select_output
+                        && (!self.selection.is_empty()
+                            || self
+                                .cwd_entries
+                                .current()
+                                .map(|e| !e.path().is_dir())
+                                .unwrap_or(false)) =>
+                {
+                    self.confirm_output();
+                }
+                Key::Char('g') | Key::Home => {
                     self.restart_err();
-                    if let Some(entry) = self.cwd_entries.current() {
-                        if let Ok(md) = entry.metadata() {
-                            let path = entry.path();
-                            let file_type = md.file_type();
-                            if file_type.is_dir() {
-                                let idx = self.cwd_idx;
-                                self.load_entries(path, Some(0))?;
-                                self.handle_current_entry()?;
-                                self.parent_idx = idx;
-                                return Ok(());
-                            } else if file_type.is_symlink() {
-                                let idx = self.cwd_idx;
-                                if let Ok(_) = self.load_entries(path, Some(0)) {
-                                    self.handle_current_entry()?;
-                                    self.parent_idx = idx;
-                                    return Ok(());
-                                }
-                            } else if file_type.is_file() {
-                                if let Err(e) = process::Command::new("xdg-open")
-                                    .args(&[entry.path().to_string_lossy().to_string()])
-                                    .spawn()
-                                {
-                                    self.err = Some(e.to_string());
-                                }
-                            }
-                        }
-                    }
+                    self.first_entry();
+                    self.handle_current_entry()?;
+                }
+                Key::PageDown => {
+                    self.restart_err();
+                    self.cwd_idx = self.cwd_entries.page_down(self.page_size());
+                    self.handle_current_entry()?;
+                }
+                Key::PageUp => {
+                    self.restart_err();
+                    self.cwd_idx = self.cwd_entries.page_up(self.page_size());
+                    self.handle_current_entry()?;
+                }
+                Key::Char('G') | Key::End => {
+                    self.restart_err();
+                    self.last_entry();
                     self.handle_current_entry()?;
                 }
+                Key::Char('c') => {
+                    self.restart_err();
+                    self.mark_targets(ClipboardOp::Copy);
+                }
+                Key::Char('x') => {
+                    self.restart_err();
+                    self.mark_targets(ClipboardOp::Cut);
+                }
+                Key::Char('v') => {
+                    self.restart_err();
+                    if let Err(e) = self.paste() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                Key::Char('i') => {
+                    self.restart_err();
+                    self.show_properties()?;
+                }
+                Key::Char('R') => {
+                    self.restart_err();
+                    self.start_rename();
+                }
+                Key::Ctrl('t') => {
+                    self.restart_err();
+                    self.start_archive();
+                }
+                Key::Char('F') => {
+                    self.restart_err();
+                    if let Err(e) = self.show_perm_fix() {
+                        self.set_err(e.to_string());
+                    }
+                }
+                // Ctrl-R already opens the regex filter prompt
+                Key::Char('H') => {
+                    self.restart_err();
+                    self.show_recent_dirs();
+                }
+                // Ctrl-L already reloads the directory
+                Key::Char('L') => {
+                    self.restart_err();
+                    self.start_link();
+                }
+                Key::Delete => {
+                    self.restart_err();
+                    self.confirm_delete(self.targets());
+                }
+                Key::Alt('\r') | Key::Alt('\n') => {
+                    self.restart_err();
+                    self.show_opener_picker();
+                }
+                Key::Right | Key::Char('\n') => {
+                    self.restart_err();
+                    self.enter_current()?;
+                }
                 _ => {}
             },
-            Event::Tick => {}
+            Event::CtrlInput(Key::Left) => {
+                self.restart_err();
+                self.go_to_parent()?;
+            }
+            Event::CtrlInput(Key::Right) => {
+                self.restart_err();
+                self.enter_current()?;
+            }
+            Event::CtrlInput(_) => {}
+            Event::Mouse(mouse)
+                if self.popup.is_none() && self.mode_editor.is_none() && self.editor.is_none() =>
+            {
+                self.handle_mouse(mouse)
+            }
+            Event::Mouse(_) => {}
+            Event::Tick => {
+                if let Err(e) = self.watch_tick() {
+                    self.set_err(format!("watch: {}", e));
+                }
+                if let Some(counts) = &mut self.preview_counts {
+                    counts.update();
+                }
+                self.update_child_entries();
+                self.archive_tick()?;
+                match &mut self.panel {
+                    PanelMode::Diff(diff) => {
+                        if let Err(e) = diff.refresh() {
+                            self.set_err(e.to_string());
+                            self.panel = PanelMode::Entries;
+                        }
+                    }
+                    PanelMode::DuMap(du) => du.update(),
+                    PanelMode::Duplicates(duplicates) => duplicates.update(),
+                    PanelMode::Scan(scan) => scan.update(),
+                    PanelMode::Stats(stats) => stats.update(),
+                    _ => {}
+                }
+            }
         }
         Ok(())
     }
 
-    fn render_main_view(&mut self, mut f: &mut Frame<Backend>, rect: Rect) {
+    fn render_main_view<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let opts = self.entry_options();
+        let home = self.config.home_dir.as_deref();
+        match &mut self.panel {
+            PanelMode::Diff(diff) => return render_diff(diff, home, f, rect),
+            PanelMode::DuMap(du) => return render_du_map(du, home, f, rect),
+            PanelMode::SplitVertical(split) => {
+                let opts = ItemOptions {
+                    tags: Some(&self.tags),
+                    workspaces: Some(&self.workspaces),
+                    ..opts
+                };
+                return render_split(split, opts, &self.selection, home, f, rect);
+            }
+            _ => {}
+        }
+
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(vec![
-                Constraint::Percentage(33),
-                Constraint::Percentage(33),
-                Constraint::Percentage(33),
-            ])
+            .constraints(
+                self.panel_widths
+                    .iter()
+                    .map(|&width| Constraint::Percentage(width))
+                    .collect::<Vec<_>>(),
+            )
             .split(rect);
 
         if let Some((path, entries)) = &self.parent {
             render_entries(
                 entries.iter(),
-                path.to_string_lossy().to_string(),
-                &mut f,
+                dir_name(path),
+                ItemOptions {
+                    tags: Some(&self.tags),
+                    workspaces: Some(&self.workspaces),
+                    ..self.entry_options()
+                },
+                f,
                 chunks[0],
             );
         }
 
+        match &mut self.panel {
+            PanelMode::Recent(recent) => {
+                return render_recent_files(recent, &self.cwd_path, f, chunks[1]);
+            }
+            PanelMode::RecentDirs(dirs) => return render_recent_dirs(dirs, home, f, chunks[1]),
+            PanelMode::PermFix(fix) => return render_perm_fix(fix, &self.cwd_path, f, chunks[1]),
+            PanelMode::Duplicates(duplicates) => {
+                return render_duplicates(duplicates, f, chunks[1]);
+            }
+            PanelMode::Scan(scan) => return render_scan(scan, f, chunks[1]),
+            _ => {}
+        }
+
+        let title = format!(
+            "{}{} {}",
+            util::abbreviate_home(&self.cwd_path, home),
+            self.filters.describe(),
+            self.entry_count()
+        );
         render_stateful_entries(
-            self.cwd_entries.items.iter(),
-            self.cwd_path.to_string_lossy().to_string(),
-            &mut self.cwd_entries.state,
-            &mut f,
+            &mut self.cwd_entries,
+            title,
+            self.filters.perm.map(|perm| format!(" [perm: {}]", perm)),
+            ItemOptions {
+                highlight: self.filters.regex.as_ref(),
+                tags: Some(&self.tags),
+                workspaces: Some(&self.workspaces),
+                ..opts
+            },
+            &self.selection,
+            f,
             chunks[1],
         );
+        self.list_height = chunks[1].height.saturating_sub(2);
+        let (metadata_rect, content_rect) = preview_rects(self.preview_layout, chunks[2]);
+        let size = content_rect.unwrap_or(chunks[2]);
+        self.preview_size = (size.width.saturating_sub(2), size.height.saturating_sub(2));
 
-        if let Some(current) = self.cwd_entries.current() {
-            self.render_entry_info(current, &mut f, chunks[2]);
+        if let PanelMode::Stats(stats) = &self.panel {
+            render_stats(stats, f, chunks[2]);
+        } else if let Some(editor) = &mut self.editor {
+            editor.render(f, chunks[2]);
+        } else if let Some(current) = self.cwd_entries.current() {
+            if let Some(rect) = metadata_rect {
+                self.render_entry_metadata(current, f, rect);
+            }
+            if let Some(rect) = content_rect {
+                self.render_entry_info(current, f, rect);
+            }
+        }
+        if let Some(divider) = self.resizing {
+            render_divider(chunks[divider], chunks[divider + 1], f);
         }
     }
 
-    fn render_entry_info(&self, entry: &DirEntry, mut frame: &mut Frame<Backend>, rect: Rect) {
+    /// Properties of `entry`, only the most common ones above the content.
+    fn render_entry_metadata<B: Backend>(
+        &self,
+        entry: &DirEntry,
+        frame: &mut Frame<B>,
+        rect: Rect,
+    ) {
+        let path = entry.path();
+        let lines = match self.preview_layout {
+            PreviewLayout::Split => properties::summary(&path),
+            _ => properties::properties(&path),
+        };
+        let text = match lines {
+            Ok(lines) => lines.join("\n"),
+            Err(e) => e.to_string(),
+        };
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            "metadata",
+            Style::default()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+        frame.render_widget(Paragraph::new(text).block(block), rect);
+    }
+
+    fn render_entry_info<B: Backend>(&self, entry: &DirEntry, frame: &mut Frame<B>, rect: Rect) {
         let _path = entry.path();
+        let rect = if _path.is_symlink() && rect.height > 6 {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(0)])
+                .split(rect);
+            self.render_symlink_chain(&_path, frame, chunks[0]);
+            chunks[1]
+        } else {
+            rect
+        };
         let name = _path
             .file_name()
             .map(|name| name.to_string_lossy().to_string())
             .unwrap_or_else(|| _path.to_string_lossy().to_string());
 
-        if let Some(child_entries) = &self.child_entries {
-            render_entries(child_entries.iter(), name, &mut frame, rect);
+        if let Some(children @ ChildEntries::Loading { .. }) = &self.child_entries {
+            let block = Block::default().borders(Borders::ALL).title(name);
+            let loading = Paragraph::new(format!("{} Loading...", children.spinner())).block(block);
+            frame.render_widget(loading, rect);
+        } else if let Some(ChildEntries::Loaded(child_entries)) = &self.child_entries {
+            render_entries(
+                child_entries.iter(),
+                name,
+                ItemOptions {
+                    tags: Some(&self.tags),
+                    workspaces: Some(&self.workspaces),
+                    ..self.entry_options()
+                },
+                frame,
+                rect,
+            );
         } else {
+            let title = match (&self.preview_title, &self.preview_counts) {
+                (Some(title), _) => title.clone(),
+                (None, Some(counts)) => format!("{} {}", name, counts.describe()),
+                (None, None) => name,
+            };
             let block = Block::default().borders(Borders::ALL).title(Span::styled(
-                name,
+                title,
                 Style::default()
                     .fg(Color::LightCyan)
                     .add_modifier(Modifier::BOLD),
             ));
 
             let paragraph = if let Some(content) = &self.content {
-                Paragraph::new(content.as_str()).block(block)
+                let scroll = self.preview_scroll.min(self.preview_max_scroll());
+                let text = if self.preview_line_numbers {
+                    Text::from(numbered_lines(content))
+                } else {
+                    Text::from(content.as_str())
+                };
+                let paragraph = Paragraph::new(text).block(block).scroll((scroll, 0));
+                if self.preview_wrap {
+                    paragraph.wrap(Wrap { trim: true })
+                } else {
+                    paragraph
+                }
             } else {
                 Paragraph::new("...").block(block)
             };
@@ -241,28 +3482,249 @@ impl TravApp {
         }
     }
 
-    pub fn draw_frame(&mut self, mut f: &mut Frame<Backend>) {
+    /// Shows every link followed from the symlink at `path` to its final target.
+    fn render_symlink_chain<B: Backend>(&self, path: &Path, frame: &mut Frame<B>, rect: Rect) {
+        let red = Style::default().fg(Color::Red);
+        let mut spans = vec![Span::raw(
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        )];
+        match properties::symlink_chain(path, self.config.symlink_max_depth) {
+            Ok(chain) => {
+                for hop in &chain.hops {
+                    spans.push(Span::raw(format!(" → {}", hop.display())));
+                }
+                match chain.end {
+                    ChainEnd::Resolved => {}
+                    ChainEnd::Broken => spans.push(Span::styled(" [broken]", red)),
+                    ChainEnd::Cycle => spans.push(Span::styled(" → [cycle]", red)),
+                    ChainEnd::TooDeep => spans.push(Span::styled(" → …", red)),
+                }
+            }
+            Err(e) => spans.push(Span::styled(format!(" → [{}]", e), red)),
+        }
+        let block = Block::default().borders(Borders::ALL).title("symlink");
+        frame.render_widget(Paragraph::new(Spans::from(spans)).block(block), rect);
+    }
+
+    pub fn draw_frame<B: Backend>(&mut self, f: &mut Frame<B>) {
+        let size = f.size();
+        if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+            render_too_small(f, size);
+            return;
+        }
+
+        if self
+            .err_deadline
+            .is_some_and(|deadline| deadline <= Instant::now())
+        {
+            self.err = None;
+            self.err_deadline = None;
+        }
         let error = &self.err;
         let mut idx = 0;
 
-        let chunks = main_layout(&mut f, error.is_some());
+        let with_input = self.input_mode != InputMode::Normal;
+
+        let chunks = main_layout(f, error.is_some(), self.show_debug, with_input);
 
         if let Some(error) = error {
-            render_error_msg(&error, &mut f, chunks[idx]);
+            render_error_msg(error, f, chunks[idx]);
+            idx += 1;
+        }
+
+        if self.show_debug {
+            self.render_dbg(f, chunks[idx]);
+            idx += 1;
+        }
+
+        self.render_main_view(f, chunks[idx]);
+        idx += 1;
+
+        if with_input {
+            self.render_input(f, chunks[idx]);
             idx += 1;
         }
 
-        //self.render_dbg(&mut f, chunks[idx]);
-        //idx += 1;
+        self.render_status_bar(f, chunks[idx]);
+
+        if let Some(editor) = &self.mode_editor {
+            editor.render(f);
+        }
+        if let Some(popup) = &mut self.popup {
+            popup.render(f);
+        }
+    }
+
+    /// Draws a `width`x`height` frame off screen and returns it as text, one
+    /// line per row without trailing whitespace. With events made by
+    /// `Events::without_input` no terminal is needed at all.
+    pub fn render_to_string(&mut self, width: u16, height: u16) -> String {
+        // drawing into memory can't fail
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| self.draw_frame(f)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let mut lines = vec![];
+        for cells in buffer.content.chunks(width as usize) {
+            let mut line = String::new();
+            let mut skip = 0;
+            for cell in cells {
+                // cells covered by a wide symbol hold a space
+                if skip == 0 {
+                    line.push_str(&cell.symbol);
+                }
+                skip = cell.symbol.width().max(skip).saturating_sub(1);
+            }
+            lines.push(line.trim_end().to_string());
+        }
+        lines.join("\n")
+    }
+
+    fn render_input<B: Backend>(&self, frame: &mut Frame<B>, rect: Rect) {
+        let title = match self.input_mode {
+            InputMode::ExtensionFilter => "extensions",
+            InputMode::Command => "command",
+            InputMode::Export => "export to (--null-separated for \\0 separators)",
+            InputMode::OpenWith => "open with",
+            InputMode::Rename => "rename to",
+            InputMode::LinkTarget => "link to (absolute or relative to the link)",
+            InputMode::LinkName => "link name",
+            InputMode::Archive => "archive name",
+            InputMode::NewFile => "new file",
+            InputMode::Duplicate => "duplicate as",
+            InputMode::Jump => "jump to (Tab to complete)",
+            InputMode::Normal => return,
+        };
+        match self.completion.as_ref().and_then(Completion::position) {
+            Some((i, n)) => self
+                .input
+                .render(format!("{} [{}/{}]", title, i, n), frame, rect),
+            None if self.input_mode == InputMode::Command
+                && self.input.text().starts_with("chmod ") =>
+            {
+                self.input.render(
+                    "command (X adds execute only to directories and executables)",
+                    frame,
+                    rect,
+                )
+            }
+            None if self.is_searching() => self.input.render(
+                format!(
+                    "{} {} (Ctrl-I to toggle case)",
+                    title,
+                    self.filters.case_indicator()
+                ),
+                frame,
+                rect,
+            ),
+            None => self.input.render(title, frame, rect),
+        }
+    }
+
+    fn render_status_bar<B: Backend>(&self, frame: &mut Frame<B>, rect: Rect) {
+        let mut spans = vec![];
+        if self.root {
+            spans.push(Span::styled(
+                "[ROOT] ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+        spans.extend(vec![
+            Span::styled(
+                util::abbreviate_home(&self.cwd_path, self.config.home_dir.as_deref()),
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("  sort: "),
+            Span::styled(
+                format!(
+                    "{} {}",
+                    self.config.sort_mode,
+                    if self.sort_reversed { "↑" } else { "↓" }
+                ),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]);
+        if let Some(max_depth) = self.max_depth {
+            spans.push(Span::raw(format!(
+                "  depth: {}/{}",
+                self.depth(&self.cwd_path).unwrap_or(0),
+                max_depth
+            )));
+        }
+        if self.config.dirs_first {
+            spans.push(Span::styled(
+                "  [dirs first]",
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if !self.selection.is_empty() {
+            spans.push(Span::styled(
+                format!("  {} selected", self.selection.len()),
+                Style::default().fg(Color::LightGreen),
+            ));
+        }
+        if let Some(clipboard) = &self.clipboard {
+            let op = match clipboard.op {
+                ClipboardOp::Copy => "copy",
+                ClipboardOp::Cut => "move",
+            };
+            spans.push(Span::styled(
+                format!("  [{} to {}]", clipboard.paths.len(), op),
+                Style::default().fg(Color::LightMagenta),
+            ));
+        }
+        if self.resizing.is_some() {
+            spans.push(Span::styled(
+                "  [resize: ←/→ move, Tab next divider, = reset, Esc done]",
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if let Some(archive) = &self.archive {
+            let (done, total) = archive.progress;
+            let total = total.map_or("...".to_string(), |total| total.to_string());
+            spans.push(Span::styled(
+                format!("  archiving {}/{}", done, total),
+                Style::default().fg(Color::LightMagenta),
+            ));
+        }
+        if let Some((msg, _)) = self
+            .status_msg
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < STATUS_MSG_DURATION)
+        {
+            spans.push(Span::styled(
+                format!("  {}", msg),
+                Style::default()
+                    .fg(Color::LightGreen)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        let status = Paragraph::new(Spans::from(spans))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .alignment(Alignment::Left);
 
-        self.render_main_view(&mut f, chunks[idx]);
+        frame.render_widget(status, rect);
     }
 
-    #[allow(dead_code)]
-    fn render_dbg(&self, frame: &mut Frame<Backend>, rect: Rect) {
+    fn render_dbg<B: Backend>(&self, frame: &mut Frame<B>, rect: Rect) {
+        let bytes = |field| util::process_memory(field).map_or("?".to_string(), util::conv_b);
         let dbg = Paragraph::new(Spans::from(vec![
             Span::raw(format!("cwd_idx: {:?} ", self.cwd_idx)),
-            Span::raw(format!("parent_idx: {:?}", self.parent_idx)),
+            Span::raw(format!("parent_idx: {:?} ", self.parent_idx)),
+            Span::raw(format!("positions: {} ", self.positions.len())),
+            Span::raw(format!("history: {} ", self.history.len())),
+            Span::raw(format!("selected: {} ", self.selection.len())),
+            Span::raw(format!("rss: {} ", bytes("VmRSS"))),
+            Span::raw(format!("heap: {} ", bytes("VmData"))),
+            Span::raw(format!(
+                "fds: {}",
+                util::open_fds().map_or("?".to_string(), |fds| fds.to_string())
+            )),
         ]))
         .block(Block::default().borders(Borders::ALL))
         .style(
@@ -277,8 +3739,42 @@ impl TravApp {
     }
 }
 
-fn render_error_msg<S>(error: S, frame: &mut Frame<Backend>, rect: Rect)
+/// Runs `cmd`, a program optionally followed by arguments, with `path` as the
+/// last argument without waiting for it.
+fn spawn_opener(cmd: &str, path: &Path) -> Result<()> {
+    let mut args = cmd.split_whitespace();
+    let program = args.next().ok_or_else(|| anyhow!("empty command"))?;
+
+    process::Command::new(program)
+        .args(args)
+        .arg(path)
+        .spawn()
+        .map_err(|e| anyhow!("failed to run `{}`: {}", program, e))?;
+
+    Ok(())
+}
+
+/// Marks the borders between the adjacent `left` and `right` panels with a
+/// double line while the divider is moved.
+fn render_divider<B: Backend>(left: Rect, right: Rect, frame: &mut Frame<B>) {
+    let style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    for x in [left.x + left.width.saturating_sub(1), right.x] {
+        let area = Rect {
+            x,
+            y: left.y + 1,
+            width: 1,
+            height: left.height.saturating_sub(2),
+        };
+        let lines: Vec<_> = (0..area.height).map(|_| Spans::from("║")).collect();
+        frame.render_widget(Paragraph::new(lines).style(style), area);
+    }
+}
+
+fn render_error_msg<B, S>(error: S, frame: &mut Frame<B>, rect: Rect)
 where
+    B: Backend,
     S: AsRef<str>,
 {
     let err = Paragraph::new(error.as_ref())
@@ -294,33 +3790,557 @@ where
     frame.render_widget(err, rect);
 }
 
-fn render_entries<'entry, I>(entries: I, title: String, frame: &mut Frame<Backend>, rect: Rect)
-where
-    I: Iterator<Item = &'entry DirEntry>,
-{
-    let entries: Vec<_> = entries.map(DirEntry::as_list_item).collect();
-    frame.render_widget(styled_file_entries(title, entries), rect);
+fn render_too_small<B: Backend>(frame: &mut Frame<B>, rect: Rect) {
+    let msg = Paragraph::new(format!(
+        "Terminal too small ({}x{}, need {}x{})",
+        rect.width, rect.height, MIN_WIDTH, MIN_HEIGHT
+    ))
+    .style(Style::default().fg(Color::LightRed).bg(Color::Black))
+    .wrap(Wrap { trim: true });
+
+    frame.render_widget(msg, rect);
+}
+
+/// Entries of the directory at `path` sorted by `mode`, hidden ones only if
+/// `show_hidden` is set.
+/// Last component of `path` used as a panel title, the full path is shown in
+/// the status bar.
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
 }
 
-fn render_stateful_entries<'entry, I>(
+fn render_entries<'entry, B: Backend, I>(
     entries: I,
     title: String,
-    mut state: &mut ListState,
-    frame: &mut Frame<Backend>,
+    opts: ItemOptions,
+    frame: &mut Frame<B>,
     rect: Rect,
 ) where
     I: Iterator<Item = &'entry DirEntry>,
 {
-    let entries: Vec<_> = entries.map(DirEntry::as_list_item).collect();
-    frame.render_stateful_widget(styled_file_entries(title, entries), rect, &mut state);
+    let opts = ItemOptions {
+        width: Some(rect.width.saturating_sub(2)),
+        ..opts
+    };
+    let entries: Vec<_> = entries.map(|e| e.as_list_item(&opts)).collect();
+    let title = util::truncate_path(&title, rect.width.saturating_sub(2));
+    frame.render_widget(styled_file_entries(title, entries), rect);
+}
+
+fn render_stateful_entries<B: Backend>(
+    list: &mut StatefulList<DirEntry>,
+    title: String,
+    badge: Option<String>,
+    opts: ItemOptions,
+    selection: &HashSet<PathBuf>,
+    frame: &mut Frame<B>,
+    rect: Rect,
+) {
+    let entries: Vec<_> = list
+        .items
+        .iter()
+        .map(|e| {
+            e.as_list_item(&ItemOptions {
+                selected: selection.contains(&e.path()),
+                width: Some(rect.width.saturating_sub(2 + HIGHLIGHT_SYMBOL.len() as u16)),
+                ..opts
+            })
+        })
+        .collect();
+    let total = entries.len();
+    let badge_len = badge.as_ref().map_or(0, |badge| badge.chars().count()) as u16;
+    let title = util::truncate_path(&title, rect.width.saturating_sub(2 + badge_len));
+    frame.render_stateful_widget(
+        styled_file_entries_with_badge(title, badge, entries),
+        rect,
+        &mut list.state,
+    );
+
+    let visible = rect.height.saturating_sub(2) as usize / opts.height.max(1) as usize;
+    render_scrollbar(
+        list.state.selected().unwrap_or(0),
+        total,
+        visible,
+        frame,
+        rect,
+    );
+}
+
+fn render_diff<B: Backend>(diff: &DiffView, home: Option<&Path>, frame: &mut Frame<B>, rect: Rect) {
+    let lines: Vec<_> = diff
+        .lines
+        .iter()
+        .skip(diff.scroll)
+        .take(rect.height as usize)
+        .map(|line| {
+            let (prefix, color) = match line.kind {
+                LineKind::Same => (' ', Color::White),
+                LineKind::Removed => ('-', Color::LightRed),
+                LineKind::Added => ('+', Color::LightGreen),
+            };
+            Spans::from(Span::styled(
+                format!("{}{}", prefix, line.text),
+                Style::default().fg(color),
+            ))
+        })
+        .collect();
+    let title = format!(
+        "{} → {}{} (Esc to go back)",
+        util::abbreviate_home(&diff.old, home),
+        util::abbreviate_home(&diff.new, home),
+        if diff.is_identical() {
+            " [identical]"
+        } else {
+            ""
+        }
+    );
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default().borders(Borders::ALL).title(Span::styled(
+                title,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        )
+        .style(Style::default().bg(Color::Black));
+    frame.render_widget(paragraph, rect);
+}
+
+fn render_du_map<B: Backend>(du: &DuMap, home: Option<&Path>, frame: &mut Frame<B>, rect: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Min(5), Constraint::Length(3)])
+        .split(rect);
+
+    let total = du.total();
+    let bar_width = DU_BAR_WIDTH;
+    // only the bars fitting in the view starting with the selected one
+    let fitting = (chunks[0].width.saturating_sub(2) / (bar_width + 1)).max(1) as usize;
+    let first = du.selected.saturating_sub(fitting - 1);
+    let data: Vec<_> = du
+        .entries
+        .iter()
+        .skip(first)
+        .take(fitting)
+        .map(|e| (e.name.as_str(), share_percent(e.size, total)))
+        .collect();
+
+    let title = format!(
+        "disk usage of {} ({}){} (Esc to go back)",
+        util::abbreviate_home(&du.root, home),
+        util::conv_b(total),
+        if du.is_scanning() { " scanning…" } else { "" }
+    );
+    let chart = BarChart::default()
+        .block(
+            Block::default().borders(Borders::ALL).title(Span::styled(
+                title,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        )
+        .data(&data)
+        .max(100)
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::LightCyan))
+        .value_style(Style::default().fg(Color::Black).bg(Color::LightCyan))
+        .label_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::Black));
+    frame.render_widget(chart, chunks[0]);
+
+    let info = match du.current() {
+        Some(entry) => Spans::from(vec![
+            Span::styled(
+                format!("{}{}", entry.name, if entry.is_dir { "/" } else { "" }),
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                "  {}  {}%  ({}/{})",
+                util::conv_b(entry.size),
+                share_percent(entry.size, total),
+                du.selected + 1,
+                du.entries.len()
+            )),
+        ]),
+        None => Spans::from("empty"),
+    };
+    let info = Paragraph::new(info)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    frame.render_widget(info, chunks[1]);
+}
+
+/// Share of `size` in `total` as whole percents.
+fn share_percent(size: u64, total: u64) -> u64 {
+    if total == 0 {
+        0
+    } else {
+        (size as u128 * 100 / total as u128) as u64
+    }
+}
+
+fn render_recent_files<B: Backend>(
+    recent: &mut StatefulList<RecentFile>,
+    root: &Path,
+    frame: &mut Frame<B>,
+    rect: Rect,
+) {
+    let items: Vec<_> = recent
+        .items
+        .iter()
+        .map(|file| {
+            let path = file.path.strip_prefix(root).unwrap_or(&file.path);
+            let time = util::system_time_to_date_time(file.modified)
+                .to_rfc3339_opts(SecondsFormat::Secs, true);
+            ListItem::new(vec![
+                Spans::from(Span::styled(
+                    path.to_string_lossy().to_string(),
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                )),
+                Spans::from(Span::styled(time, Style::default().fg(Color::Gray))),
+            ])
+        })
+        .collect();
+    frame.render_stateful_widget(
+        styled_file_entries("recent files (Esc to go back)".to_string(), items),
+        rect,
+        &mut recent.state,
+    );
 }
 
-pub fn main_layout(f: &mut Frame<Backend>, with_error: bool) -> Vec<Rect> {
-    let constraints = if with_error {
-        [Constraint::Min(3), Constraint::Percentage(90)].as_ref()
+/// Digits of the largest line number of `content`.
+fn gutter_digits(content: &str) -> usize {
+    content.lines().count().max(1).to_string().len()
+}
+
+/// Lines of `content` prefixed with their numbers in a dim gutter.
+fn numbered_lines(content: &str) -> Vec<Spans<'_>> {
+    let digits = gutter_digits(content);
+    let dim = Style::default().fg(Color::DarkGray);
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            Spans::from(vec![
+                Span::styled(
+                    format!(" {:>width$}{}", i + 1, GUTTER_SEPARATOR, width = digits),
+                    dim,
+                ),
+                Span::raw(line),
+            ])
+        })
+        .collect()
+}
+
+/// Number of lines `text` takes up in a panel `width` columns wide.
+fn visual_line_count(text: &str, width: u16, wrap: bool) -> usize {
+    if !wrap || width == 0 {
+        return text.lines().count();
+    }
+    let width = width as usize;
+    text.lines()
+        .map(|line| line.width().max(1).div_ceil(width))
+        .sum()
+}
+
+fn render_split<B: Backend>(
+    split: &mut SplitView,
+    opts: ItemOptions,
+    selection: &HashSet<PathBuf>,
+    home: Option<&Path>,
+    frame: &mut Frame<B>,
+    rect: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Percentage(split.ratio),
+            Constraint::Percentage(100 - split.ratio),
+        ])
+        .split(rect);
+    let focus = split.focus;
+    for (i, pane) in split.panes.iter_mut().enumerate() {
+        let title = if i == focus {
+            format!("{} [focused]", util::abbreviate_home(&pane.path, home))
+        } else {
+            util::abbreviate_home(&pane.path, home)
+        };
+        render_stateful_entries(
+            &mut pane.entries,
+            title,
+            None,
+            opts,
+            selection,
+            frame,
+            chunks[i],
+        );
+    }
+}
+
+fn render_recent_dirs<B: Backend>(
+    dirs: &mut StatefulList<PathBuf>,
+    home: Option<&Path>,
+    frame: &mut Frame<B>,
+    rect: Rect,
+) {
+    let items: Vec<_> = dirs
+        .items
+        .iter()
+        .map(|dir| {
+            ListItem::new(Span::styled(
+                util::abbreviate_home(dir, home),
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        })
+        .collect();
+    frame.render_stateful_widget(
+        styled_file_entries("recently visited (Esc to go back)".to_string(), items),
+        rect,
+        &mut dirs.state,
+    );
+}
+
+fn render_perm_fix<B: Backend>(fix: &mut PermFix, root: &Path, frame: &mut Frame<B>, rect: Rect) {
+    let gray = Style::default().fg(Color::Gray);
+    let items: Vec<_> = fix
+        .changes
+        .items
+        .iter()
+        .map(|change| {
+            let path = change.path.strip_prefix(root).unwrap_or(&change.path);
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!(
+                        "{} ({:04o}) → ",
+                        &properties::format_mode(change.old)[1..],
+                        change.old
+                    ),
+                    gray,
+                ),
+                Span::styled(
+                    format!(
+                        "{} ({:04o})  ",
+                        &properties::format_mode(change.new)[1..],
+                        change.new
+                    ),
+                    Style::default().fg(Color::LightGreen),
+                ),
+                Span::styled(
+                    path.to_string_lossy().to_string(),
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]))
+        })
+        .collect();
+    let title = format!(
+        "fix permissions of {} entries? (Enter applies, Esc cancels)",
+        fix.changes.items.len()
+    );
+    frame.render_stateful_widget(
+        styled_file_entries(title, items),
+        rect,
+        &mut fix.changes.state,
+    );
+}
+
+fn render_duplicates<B: Backend>(duplicates: &Duplicates, frame: &mut Frame<B>, rect: Rect) {
+    let gray = Style::default().fg(Color::Gray);
+    let mut items = vec![];
+    for group in &duplicates.groups {
+        for (i, file) in group.files.iter().enumerate() {
+            let path = file.strip_prefix(&duplicates.root).unwrap_or(file);
+            let branch = if i + 1 == group.files.len() {
+                "└ "
+            } else {
+                "├ "
+            };
+            let mut spans = vec![
+                Span::styled(branch, gray),
+                Span::styled(
+                    path.to_string_lossy().to_string(),
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ];
+            if i == 0 {
+                spans.push(Span::styled(
+                    " (original)",
+                    Style::default().fg(Color::LightGreen),
+                ));
+            }
+            let mut lines = vec![];
+            if i == 0 {
+                lines.push(Spans::from(Span::styled(
+                    format!(
+                        "{} copies of {} ({})",
+                        group.files.len(),
+                        util::conv_b(group.size),
+                        &group.hash[..12]
+                    ),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+            lines.push(Spans::from(spans));
+            items.push(ListItem::new(lines));
+        }
+    }
+
+    let title = if duplicates.is_scanning() {
+        let (done, total) = duplicates.progress;
+        format!("duplicates, hashing {}/{}…", done, total)
+    } else if duplicates.is_empty() {
+        "no duplicates found (Esc to go back)".to_string()
+    } else {
+        format!(
+            "{} duplicate groups (d delete, Esc to go back)",
+            duplicates.groups.len()
+        )
+    };
+    let mut state = ListState::default();
+    state.select(Some(duplicates.selected).filter(|_| !duplicates.is_empty()));
+    frame.render_stateful_widget(styled_file_entries(title, items), rect, &mut state);
+}
+
+fn render_scan<B: Backend>(scan: &mut PathScan, frame: &mut Frame<B>, rect: Rect) {
+    let items: Vec<_> = scan
+        .found
+        .items
+        .iter()
+        .map(|path| {
+            let path = path.strip_prefix(&scan.root).unwrap_or(path);
+            ListItem::new(Span::styled(
+                path.to_string_lossy().to_string(),
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        })
+        .collect();
+    let title = if scan.is_scanning() {
+        format!(
+            "{}, {} found, {} directories scanned…",
+            scan.kind.describe(),
+            scan.found.items.len(),
+            scan.scanned
+        )
     } else {
-        [Constraint::Percentage(97)].as_ref()
+        format!(
+            "{}, {} found (d delete, D delete all, Esc to go back)",
+            scan.kind.describe(),
+            scan.found.items.len()
+        )
     };
+    frame.render_stateful_widget(
+        styled_file_entries(title, items),
+        rect,
+        &mut scan.found.state,
+    );
+}
+
+/// Areas of the preview panel taken by the metadata and the content in
+/// `layout`.
+fn preview_rects(layout: PreviewLayout, rect: Rect) -> (Option<Rect>, Option<Rect>) {
+    match layout {
+        PreviewLayout::Content => (None, Some(rect)),
+        PreviewLayout::Metadata => (Some(rect), None),
+        PreviewLayout::Split => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(vec![Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
+                .split(rect);
+            (Some(chunks[0]), Some(chunks[1]))
+        }
+    }
+}
+
+fn render_stats<B: Backend>(stats: &ExtStats, frame: &mut Frame<B>, rect: Rect) {
+    let rows: Vec<_> = stats
+        .top
+        .iter()
+        .map(|stat| {
+            let extension = match &stat.extension {
+                Some(extension) => format!(".{}", extension),
+                None => "(none)".to_string(),
+            };
+            Row::new(vec![
+                Cell::from(extension),
+                Cell::from(stat.count.to_string()),
+                Cell::from(util::conv_b(stat.size)),
+            ])
+        })
+        .collect();
+    let title = format!(
+        "{} files{} (Enter filter, Esc to go back)",
+        stats.files,
+        if stats.is_scanning() {
+            ", scanning…"
+        } else {
+            ""
+        }
+    );
+    let table = Table::new(rows)
+        .header(
+            Row::new(vec!["extension", "files", "size"])
+                .style(Style::default().fg(Color::Gray))
+                .bottom_margin(1),
+        )
+        .block(
+            Block::default().borders(Borders::ALL).title(Span::styled(
+                title,
+                Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        )
+        .widths(&[
+            Constraint::Percentage(40),
+            Constraint::Percentage(25),
+            Constraint::Percentage(35),
+        ])
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = TableState::default();
+    state.select(Some(stats.selected).filter(|_| !stats.top.is_empty()));
+    frame.render_stateful_widget(table, rect, &mut state);
+}
+
+pub fn main_layout<B: Backend>(
+    f: &mut Frame<B>,
+    with_error: bool,
+    with_debug: bool,
+    with_input: bool,
+) -> Vec<Rect> {
+    let mut constraints = vec![];
+    if with_error {
+        constraints.push(Constraint::Length(3));
+    }
+    if with_debug {
+        constraints.push(Constraint::Length(3));
+    }
+    constraints.push(Constraint::Min(3));
+    if with_input {
+        constraints.push(Constraint::Length(3));
+    }
+    constraints.push(Constraint::Length(1));
 
     Layout::default()
         .direction(Direction::Vertical)
@@ -0,0 +1,169 @@
+//! Creating `.tar.gz` archives in the background with the system `tar`.
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+#[derive(Debug)]
+enum Message {
+    /// Number of entries to add.
+    Total(usize),
+    /// An entry was added to the archive.
+    Added,
+    Done(Result<()>),
+}
+
+/// Number of entries `tar` adds for `path`, directories included.
+fn count_entries(path: &Path) -> usize {
+    match fs::symlink_metadata(path) {
+        Ok(md) if md.is_dir() => {
+            1 + fs::read_dir(path)
+                .map(|entries| entries.flatten().map(|e| count_entries(&e.path())).sum())
+                .unwrap_or(0)
+        }
+        Ok(_) => 1,
+        Err(_) => 0,
+    }
+}
+
+/// Reports each entry `tar -v` lists in `stream` and returns its error
+/// messages. GNU tar lists entries on stdout when writing to a file while BSD
+/// tar lists them on stderr. Both prefix errors with their name.
+fn read_listing<R: Read>(stream: Option<R>, tx: &mpsc::Sender<Message>) -> String {
+    let mut errors = String::new();
+    let stream = match stream {
+        Some(stream) => stream,
+        None => return errors,
+    };
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        if line.starts_with("tar: ") || line.starts_with("bsdtar: ") {
+            errors.push_str(&line);
+            errors.push('\n');
+        } else {
+            let _ = tx.send(Message::Added);
+        }
+    }
+    errors
+}
+
+#[derive(Debug)]
+/// An archive being written by `tar` running in the background.
+pub struct Archive {
+    pub path: PathBuf,
+    /// Entries added so far and the number of entries to add once counted.
+    pub progress: (usize, Option<usize>),
+    rx: Option<mpsc::Receiver<Message>>,
+    /// Set once `tar` exits.
+    pub result: Option<Result<()>>,
+}
+
+impl Archive {
+    /// Starts archiving `paths` into the gzip compressed tarball at `path`.
+    /// Each entry is stored under its file name.
+    pub fn create(path: PathBuf, paths: &[PathBuf]) -> Result<Archive> {
+        if path.exists() {
+            return Err(anyhow!("{} already exists", path.display()));
+        }
+        let mut cmd = Command::new("tar");
+        cmd.arg("-czvf").arg(&path);
+        for src in paths {
+            let (dir, name) = match (src.parent(), src.file_name()) {
+                (Some(dir), Some(name)) => (dir, name),
+                _ => return Err(anyhow!("can't archive {}", src.display())),
+            };
+            cmd.arg("-C").arg(dir);
+            // `--` would stop the following `-C` from being parsed as an option
+            if name.to_string_lossy().starts_with('-') {
+                cmd.arg(Path::new(".").join(name));
+            } else {
+                cmd.arg(name);
+            }
+        }
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("failed to run tar: {}", e))?;
+
+        let (tx, rx) = mpsc::channel();
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let paths = paths.to_vec();
+        thread::spawn(move || {
+            // both streams are read at once so that a full pipe doesn't block tar
+            let readers = vec![
+                thread::spawn({
+                    let tx = tx.clone();
+                    move || read_listing(stdout, &tx)
+                }),
+                thread::spawn({
+                    let tx = tx.clone();
+                    move || read_listing(stderr, &tx)
+                }),
+            ];
+            let total = paths.iter().map(|p| count_entries(p)).sum();
+            let _ = tx.send(Message::Total(total));
+            let err: String = readers
+                .into_iter()
+                .map(|reader| reader.join().unwrap_or_default())
+                .collect();
+            let res = match child.wait() {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => Err(anyhow!("tar exited with {}: {}", status, err.trim())),
+                Err(e) => Err(e.into()),
+            };
+            let _ = tx.send(Message::Done(res));
+        });
+
+        Ok(Archive {
+            path,
+            progress: (0, None),
+            rx: Some(rx),
+            result: None,
+        })
+    }
+
+    /// Collects messages from the thread waiting for `tar`.
+    pub fn update(&mut self) {
+        let rx = match &self.rx {
+            Some(rx) => rx,
+            None => return,
+        };
+        while let Ok(msg) = rx.try_recv() {
+            match msg {
+                Message::Total(total) => self.progress.1 = Some(total),
+                Message::Added => self.progress.0 += 1,
+                Message::Done(res) => {
+                    self.result = Some(res);
+                    self.rx = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.rx.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_listing_counts_entries_and_keeps_errors() {
+        let (tx, rx) = mpsc::channel();
+        let gnu = read_listing(Some(&b"dir/\ndir/a\ntar: b: Cannot stat\n"[..]), &tx);
+        let bsd = read_listing(Some(&b"a dir\na dir/a\nbsdtar: b: Cannot stat\n"[..]), &tx);
+        drop(tx);
+        assert_eq!(gnu, "tar: b: Cannot stat\n");
+        assert_eq!(bsd, "bsdtar: b: Cannot stat\n");
+        assert_eq!(rx.iter().count(), 4);
+        assert_eq!(read_listing(None::<&[u8]>, &mpsc::channel().0), "");
+    }
+}
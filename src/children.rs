@@ -0,0 +1,74 @@
+//! Entries of the highlighted directory listed in the background so that
+//! moving over large directories doesn't block the interface.
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::entry::DirEntry;
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const SPINNER_FRAME: Duration = Duration::from_millis(100);
+/// How long to wait for a listing before showing the spinner.
+const QUICK_LOAD: Duration = Duration::from_millis(20);
+
+#[derive(Debug)]
+pub enum ChildEntries {
+    /// Listing the directory in a background thread.
+    Loading {
+        started: Instant,
+        rx: mpsc::Receiver<Result<Vec<DirEntry>>>,
+    },
+    Loaded(Vec<DirEntry>),
+}
+
+impl ChildEntries {
+    /// Starts listing `path` with `list` in a background thread calling `done`
+    /// once the listing can be collected with `update`. Listings that finish
+    /// quickly are returned right away.
+    pub fn load<F, D>(path: PathBuf, list: F, done: D) -> Result<ChildEntries>
+    where
+        F: FnOnce(&Path) -> Result<Vec<DirEntry>> + Send + 'static,
+        D: FnOnce() + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            // the receiver is gone if another entry got highlighted meanwhile
+            if tx.send(list(&path)).is_ok() {
+                done();
+            }
+        });
+        // small directories are shown without flashing the spinner
+        match rx.recv_timeout(QUICK_LOAD) {
+            Ok(res) => Ok(ChildEntries::Loaded(res?)),
+            Err(_) => Ok(ChildEntries::Loading {
+                started: Instant::now(),
+                rx,
+            }),
+        }
+    }
+
+    /// Collects the listing once it's ready, returns the error if it failed.
+    pub fn update(&mut self) -> Result<()> {
+        if let ChildEntries::Loading { rx, .. } = self {
+            match rx.try_recv() {
+                Ok(res) => *self = ChildEntries::Loaded(res?),
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => *self = ChildEntries::Loaded(vec![]),
+            }
+        }
+        Ok(())
+    }
+
+    /// Frame of the loading animation for the time spent loading.
+    pub fn spinner(&self) -> char {
+        match self {
+            ChildEntries::Loading { started, .. } => {
+                let frame = started.elapsed().as_millis() / SPINNER_FRAME.as_millis();
+                SPINNER[frame as usize % SPINNER.len()]
+            }
+            ChildEntries::Loaded(_) => ' ',
+        }
+    }
+}
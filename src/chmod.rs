@@ -0,0 +1,174 @@
+use anyhow::Result;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::properties::format_mode;
+
+/// Permission bits in the order they're shown, `rwxrwxrwx`.
+const PERMISSION_BITS: [u32; 9] = [
+    0o400, 0o200, 0o100, 0o040, 0o020, 0o010, 0o004, 0o002, 0o001,
+];
+/// setuid, setgid and sticky bits shown in the second row.
+const SPECIAL_BITS: [u32; 3] = [0o4000, 0o2000, 0o1000];
+const SPECIAL_NAMES: [&str; 3] = ["setuid", "setgid", "sticky"];
+
+const WIDTH: u16 = 54;
+const HEIGHT: u16 = 9;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Popup editing the permissions of a file as a grid of checkboxes.
+pub struct ModeEditor {
+    pub path: PathBuf,
+    /// Full `st_mode` including the file type bits.
+    pub mode: u32,
+    /// Highlighted `(row, column)` of the grid.
+    cursor: (usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Result of a key press in the editor.
+pub enum EditorAction {
+    None,
+    Commit,
+    Cancel,
+}
+
+impl ModeEditor {
+    pub fn new(path: PathBuf) -> Result<ModeEditor> {
+        let mode = fs::symlink_metadata(&path)?.permissions().mode();
+        Ok(ModeEditor {
+            path,
+            mode,
+            cursor: (0, 0),
+        })
+    }
+
+    fn row_len(row: usize) -> usize {
+        if row == 0 {
+            PERMISSION_BITS.len()
+        } else {
+            SPECIAL_BITS.len()
+        }
+    }
+
+    fn bit(&self, (row, col): (usize, usize)) -> u32 {
+        if row == 0 {
+            PERMISSION_BITS[col]
+        } else {
+            SPECIAL_BITS[col]
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> EditorAction {
+        let (row, col) = self.cursor;
+        match key {
+            Key::Left | Key::Char('h') => self.cursor.1 = col.saturating_sub(1),
+            Key::Right | Key::Char('l') => self.cursor.1 = (col + 1).min(Self::row_len(row) - 1),
+            Key::Up | Key::Char('k') | Key::Down | Key::Char('j') => {
+                let row = 1 - row;
+                self.cursor = (row, col.min(Self::row_len(row) - 1));
+            }
+            Key::Char(' ') => self.mode ^= self.bit(self.cursor),
+            Key::Char('\n') => return EditorAction::Commit,
+            Key::Esc => return EditorAction::Cancel,
+            _ => {}
+        }
+        EditorAction::None
+    }
+
+    /// Applies the edited permissions to the file.
+    pub fn commit(&self) -> Result<()> {
+        fs::set_permissions(&self.path, fs::Permissions::from_mode(self.mode & 0o7777))?;
+        Ok(())
+    }
+
+    fn checkbox(&self, cell: (usize, usize)) -> Span<'static> {
+        let checked = self.mode & self.bit(cell) != 0;
+        let style = if cell == self.cursor {
+            Style::default()
+                .fg(Color::Yellow)
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        Span::styled(if checked { "[x]" } else { "[ ]" }, style)
+    }
+
+    pub fn render<B: Backend>(&self, frame: &mut Frame<B>) {
+        let size = frame.size();
+        let width = WIDTH.min(size.width);
+        let height = HEIGHT.min(size.height);
+        let area = Rect {
+            x: size.x + (size.width - width) / 2,
+            y: size.y + (size.height - height) / 2,
+            width,
+            height,
+        };
+
+        let mut bits = vec![Span::raw(" ")];
+        for col in 0..PERMISSION_BITS.len() {
+            if col > 0 && col % 3 == 0 {
+                bits.push(Span::raw(" "));
+            }
+            bits.push(self.checkbox((0, col)));
+        }
+        let mut specials = vec![];
+        for col in 0..SPECIAL_BITS.len() {
+            specials.push(Span::raw(if col == 0 { "   " } else { "     " }));
+            specials.push(self.checkbox((1, col)));
+        }
+
+        let gray = Style::default().fg(Color::Gray);
+        let lines = vec![
+            Spans::from(Span::styled("  user      group     other", gray)),
+            Spans::from(Span::styled("  r  w  x   r  w  x   r  w  x", gray)),
+            Spans::from(bits),
+            Spans::from(Span::styled(
+                format!(
+                    "  {}  {}  {}",
+                    SPECIAL_NAMES[0], SPECIAL_NAMES[1], SPECIAL_NAMES[2]
+                ),
+                gray,
+            )),
+            Spans::from(specials),
+            Spans::from(Span::styled(
+                format!(
+                    "  {} ({:04o})  Space toggles, Enter applies",
+                    format_mode(self.mode),
+                    self.mode & 0o7777
+                ),
+                Style::default().fg(Color::LightCyan),
+            )),
+        ];
+
+        let name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default().borders(Borders::ALL).title(Span::styled(
+                    format!("permissions of {}", name),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )),
+            )
+            .style(Style::default().bg(Color::Black));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph, area);
+    }
+}
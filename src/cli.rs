@@ -0,0 +1,153 @@
+use anyhow::{anyhow, Result};
+use std::env;
+use std::path::PathBuf;
+
+use crate::log::Level;
+use crate::output::ListFormat;
+
+pub const USAGE: &str = "\
+Usage: trav [OPTIONS] [PATH]
+
+Options:
+    --restore-session    restore the session saved with Ctrl-S
+    --select <NAME>      highlight the entry NAME of PATH on startup
+    --list               print entries of PATH to stdout and exit
+    --null               separate listed paths with null bytes
+    --json               print entries of PATH as a JSON array and exit
+    --compact            print the JSON array on a single line
+    --select-output      pick files and print their paths on exit
+    --watch              highlight changes of the current directory as they happen
+    --tmux-popup         run in a popup over the current tmux window
+    --max-depth <N>      don't enter directories more than N levels below PATH
+    --readonly           refuse to copy, move, delete, rename, chmod or export to files
+    --force-root         don't ask twice before deleting when running as root
+    --diff-tool <CMD>    compare files with CMD, e.g. \"vimdiff %1 %2\"
+    --log-file <PATH>    append debug output to PATH
+    --log-level <LEVEL>  error, warn, info (default), debug or trace
+    -V, --version        print version information
+    -h, --help           print this help message
+";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+/// Command line arguments.
+pub struct Args {
+    /// Directory to start in, defaults to the current directory.
+    pub path: Option<PathBuf>,
+    pub restore_session: bool,
+    /// Name of the entry highlighted on startup.
+    pub select: Option<String>,
+    pub list: bool,
+    pub list_format: ListFormat,
+    /// Print JSON listings on a single line.
+    pub compact: bool,
+    pub select_output: bool,
+    pub watch: bool,
+    pub readonly: bool,
+    pub max_depth: Option<usize>,
+    pub tmux_popup: bool,
+    pub force_root: bool,
+    /// Overrides the `diff_tool` configured in the config file.
+    pub diff_tool: Option<String>,
+    /// File receiving log messages, logging is disabled without it.
+    pub log_file: Option<PathBuf>,
+    pub log_level: Level,
+    pub help: bool,
+    pub version: bool,
+}
+
+/// Version and build information printed by `--version`.
+pub fn version() -> String {
+    format!(
+        "trav {} ({} {})\nbuilt with {}",
+        env!("CARGO_PKG_VERSION"),
+        env!("GIT_HASH"),
+        env!("BUILD_DATE"),
+        env!("RUSTC_VERSION"),
+    )
+}
+
+impl Args {
+    pub fn from_env() -> Result<Args> {
+        Args::parse(env::args().skip(1))
+    }
+
+    pub fn parse<I>(args: I) -> Result<Args>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("missing value for `{}`\n\n{}", arg, USAGE))
+            };
+            match arg.as_str() {
+                "--diff-tool" => parsed.diff_tool = Some(value()?),
+                "--log-file" => parsed.log_file = Some(PathBuf::from(value()?)),
+                "--log-level" => parsed.log_level = value()?.parse()?,
+                "--restore-session" => parsed.restore_session = true,
+                "--select" => parsed.select = Some(value()?),
+                "-h" | "--help" => parsed.help = true,
+                "-V" | "--version" => parsed.version = true,
+                "--list" => parsed.list = true,
+                "--select-output" => parsed.select_output = true,
+                "--watch" => parsed.watch = true,
+                "--readonly" => parsed.readonly = true,
+                "--tmux-popup" => parsed.tmux_popup = true,
+                "--max-depth" => {
+                    let depth = value()?;
+                    parsed.max_depth = Some(
+                        depth
+                            .parse()
+                            .map_err(|_| anyhow!("invalid max depth `{}`", depth))?,
+                    );
+                }
+                "--force-root" => parsed.force_root = true,
+                "--null" => parsed.list_format = ListFormat::Null,
+                "--json" => {
+                    parsed.list = true;
+                    parsed.list_format = ListFormat::Json;
+                }
+                "--compact" => parsed.compact = true,
+                arg if !arg.starts_with('-') && parsed.path.is_none() => {
+                    parsed.path = Some(PathBuf::from(arg))
+                }
+                arg => return Err(anyhow!("unexpected argument `{}`\n\n{}", arg, USAGE)),
+            }
+        }
+
+        if parsed.compact {
+            if parsed.list_format != ListFormat::Json {
+                return Err(anyhow!("`--compact` requires `--json`\n\n{}", USAGE));
+            }
+            parsed.list_format = ListFormat::CompactJson;
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn compact_json() {
+        let args = parse(&["--json", "--compact"]).unwrap();
+        assert_eq!(args.list_format, ListFormat::CompactJson);
+        let args = parse(&["--compact", "--json"]).unwrap();
+        assert_eq!(args.list_format, ListFormat::CompactJson);
+    }
+
+    #[test]
+    fn compact_requires_json() {
+        assert!(parse(&["--compact"]).is_err());
+        assert!(parse(&["--list", "--compact"]).is_err());
+        assert!(parse(&["--json", "--null", "--compact"]).is_err());
+    }
+}
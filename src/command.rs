@@ -0,0 +1,125 @@
+use anyhow::{anyhow, Error, Result};
+use std::str::FromStr;
+
+use crate::filter::{parse_date_filter, parse_size_filter, DateFilter, PermFilter, SizeFilter};
+use crate::scan::ScanKind;
+use crate::tags;
+use crate::util;
+use crate::util::regex::Regex;
+
+const MAX_TAB_WIDTH: u8 = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A command entered in the command prompt opened with `:`.
+pub enum Command {
+    /// `:size <bounds>` or `:size clear`
+    Size(Option<SizeFilter>),
+    /// `:date <range>` or `:date clear`
+    Date(Option<DateFilter>),
+    /// `:regex <pattern>` or `:regex` to clear
+    Regex(Option<Regex>),
+    /// `:cd <path>` or `:cd` to open the jump prompt
+    Cd(Option<String>),
+    /// `! <shell command>` to filter the previewed file through a command
+    Pipe(String),
+    /// `:duplicates` to find files with identical content
+    Duplicates,
+    /// `:empty-dirs` to find directories without any files, `:broken-links`
+    /// to find dangling symlinks or `:ff <glob>` to find entries by name
+    Scan(ScanKind),
+    /// `:perm world-writable|suid|executable` or `:perm` to clear
+    Perm(Option<PermFilter>),
+    /// `:tagged <color>` to show only entries with a color tag or `:tagged` to
+    /// clear
+    Tagged(Option<u8>),
+    /// `:select <regex>` to select entries whose path matches
+    Select(Regex),
+    /// `:deselect <regex>` to unselect entries whose path matches
+    Deselect(Regex),
+    /// `:difftool` to compare two files with the configured diff tool
+    DiffTool,
+    /// `:chmod <mode>` to change permissions of the targeted entries, the mode
+    /// is octal like `644` or symbolic like `u+x,go-w`. `X` instead of `x`
+    /// adds execute permission only to directories and executable files
+    Chmod(String),
+    /// `:encoding <name>` to decode the previewed file from another encoding
+    /// or `:encoding auto` to guess it
+    Encoding(String),
+    /// `:tabwidth <n>` to set the columns between tab stops in the preview
+    TabWidth(u8),
+    /// `:stat` to show the output of `stat` for the highlighted entry
+    Stat,
+    /// `:tree [depth]` to show the tree of the current directory in the preview
+    Tree(Option<usize>),
+    /// `:debug` to toggle diagnostics about trav itself above the main view
+    Debug,
+}
+
+impl FromStr for Command {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().trim_start_matches(':');
+        if let Some(cmd) = s.strip_prefix('!') {
+            let cmd = cmd.trim();
+            if cmd.is_empty() {
+                return Err(anyhow!("missing command after `!`"));
+            }
+            return Ok(Command::Pipe(cmd.to_string()));
+        }
+        let mut it = s.splitn(2, char::is_whitespace);
+        let name = it.next().unwrap_or_default();
+        let args = it.next().unwrap_or_default().trim();
+
+        match name {
+            "size" if args == "clear" => Ok(Command::Size(None)),
+            "size" => Ok(Command::Size(Some(parse_size_filter(args)?))),
+            "date" if args == "clear" => Ok(Command::Date(None)),
+            "date" => Ok(Command::Date(Some(parse_date_filter(args)?))),
+            "regex" if args.is_empty() => Ok(Command::Regex(None)),
+            "regex" => Ok(Command::Regex(Some(Regex::new(args)?))),
+            "cd" if args.is_empty() => Ok(Command::Cd(None)),
+            "cd" => Ok(Command::Cd(Some(args.to_string()))),
+            "duplicates" => Ok(Command::Duplicates),
+            "empty-dirs" => Ok(Command::Scan(ScanKind::EmptyDirs)),
+            "broken-links" => Ok(Command::Scan(ScanKind::BrokenLinks)),
+            "ff" if args.is_empty() => Err(anyhow!("missing pattern after `ff`")),
+            "ff" => Ok(Command::Scan(ScanKind::Name(args.to_string()))),
+            "perm" if args.is_empty() || args == "clear" => Ok(Command::Perm(None)),
+            "perm" => Ok(Command::Perm(Some(args.parse()?))),
+            "tagged" if args.is_empty() || args == "clear" => Ok(Command::Tagged(None)),
+            "tagged" => Ok(Command::Tagged(Some(tags::parse_tag(args)?))),
+            "select" | "deselect" if args.is_empty() => {
+                Err(anyhow!("missing pattern after `{}`", name))
+            }
+            "select" => Ok(Command::Select(Regex::new(args)?)),
+            "deselect" => Ok(Command::Deselect(Regex::new(args)?)),
+            "difftool" => Ok(Command::DiffTool),
+            "chmod" if args.is_empty() => Err(anyhow!("missing mode after `chmod`")),
+            "chmod" => {
+                // reject invalid modes before touching any file
+                util::parse_symbolic_mode(0, args)?;
+                Ok(Command::Chmod(args.to_string()))
+            }
+            "encoding" if args.is_empty() => Err(anyhow!("missing encoding after `encoding`")),
+            "encoding" => Ok(Command::Encoding(args.to_string())),
+            "stat" => Ok(Command::Stat),
+            "debug" => Ok(Command::Debug),
+            "tree" if args.is_empty() => Ok(Command::Tree(None)),
+            "tree" => match args.parse() {
+                Ok(depth) if depth > 0 => Ok(Command::Tree(Some(depth))),
+                _ => Err(anyhow!("invalid tree depth `{}`", args)),
+            },
+            "tabwidth" => match args.parse() {
+                Ok(width @ 1..=MAX_TAB_WIDTH) => Ok(Command::TabWidth(width)),
+                _ => Err(anyhow!(
+                    "tab width must be between 1 and {}, got `{}`",
+                    MAX_TAB_WIDTH,
+                    args
+                )),
+            },
+            "" => Err(anyhow!("empty command")),
+            _ => Err(anyhow!("unknown command `{}`", name)),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use crate::entry::{EntryWidthMode, DEFAULT_ENTRY_HEIGHT, MAX_ENTRY_HEIGHT};
+use crate::sort::{SortMode, SortOptions};
+use crate::tree::DEFAULT_TREE_DEPTH;
+use crate::util;
+use crate::util::toml::{parse_bool, parse_int, parse_line, parse_str, parse_str_array};
+
+const CONFIG_FILE: &str = "config.toml";
+const DEFAULT_RECENT_DEPTH: usize = 3;
+const DEFAULT_INLINE_EDIT_MAX_BYTES: u64 = 4096;
+const DEFAULT_OPEN_MAX: usize = 5;
+const DEFAULT_ERROR_DISPLAY_SECS: u64 = 5;
+const DEFAULT_PREVIEW_TAB_WIDTH: u8 = 4;
+const DEFAULT_SYMLINK_MAX_DEPTH: usize = 10;
+
+#[derive(Debug, Clone)]
+/// User configuration read from `~/.config/trav/config.toml`.
+pub struct Config {
+    pub dirs_first: bool,
+    /// List entries whose name starts with a dot.
+    pub show_hidden: bool,
+    /// Show Nerd Fonts glyphs instead of emoji icons next to entry names.
+    pub nerd_fonts: bool,
+    /// Levels included in directory trees copied with `Ctrl-Alt-C`.
+    pub tree_depth: usize,
+    pub sort_mode: SortMode,
+    /// List entries without an extension first when sorting by extension.
+    pub no_ext_first: bool,
+    /// Commands offered for opening files by lowercase extension, configured
+    /// with `openers.<ext> = ["cmd --flag", ...]`.
+    pub openers: HashMap<String, Vec<String>>,
+    /// Most files opened at once with `O` without asking first.
+    pub open_max: usize,
+    /// Seconds an error stays on screen, `0` keeps it until the next key.
+    pub error_display_secs: u64,
+    /// How many directory levels are searched for the recent files view.
+    pub recent_depth: usize,
+    /// Named directories offered in the jump prompt, configured with
+    /// `bookmarks.<name> = "path"`.
+    pub bookmarks: BTreeMap<String, PathBuf>,
+    /// Extra arguments passed to the pager, e.g. `["-R", "+G"]`.
+    pub pager_args: Vec<String>,
+    /// Most links followed when showing the chain of a highlighted symlink.
+    pub symlink_max_depth: usize,
+    /// Columns between tab stops in the preview.
+    pub preview_tab_width: u8,
+    /// Largest file that can be edited in the preview panel.
+    pub inline_edit_max_bytes: u64,
+    /// Lines rendered per entry in the lists, from 1 to 4.
+    pub entry_height: u8,
+    /// How names wider than their panel are shown, `truncate`, `wrap` or
+    /// `ellipsis`.
+    pub entry_width_mode: EntryWidthMode,
+    /// Command comparing two files with `%1` and `%2` standing for them, e.g.
+    /// `vimdiff %1 %2`.
+    pub diff_tool: Option<String>,
+    /// Command opening the current directory with `Ctrl-O`, `xdg-open` by
+    /// default.
+    pub gui_file_manager: Option<String>,
+    /// Command opening a new terminal window running the command appended to
+    /// it, e.g. `alacritty -e`, used by `Ctrl-N` instead of a tmux window.
+    pub new_window_cmd: Option<String>,
+    /// Directory the favorites, tags, history and other state are kept in,
+    /// `config_dir()` by default. Not read from the config file.
+    pub state_dir: Option<PathBuf>,
+    /// Directory shown as `~`, `$HOME` by default. Not read from the config
+    /// file.
+    pub home_dir: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            dirs_first: false,
+            show_hidden: true,
+            nerd_fonts: false,
+            tree_depth: DEFAULT_TREE_DEPTH,
+            sort_mode: SortMode::default(),
+            no_ext_first: false,
+            openers: HashMap::new(),
+            open_max: DEFAULT_OPEN_MAX,
+            error_display_secs: DEFAULT_ERROR_DISPLAY_SECS,
+            recent_depth: DEFAULT_RECENT_DEPTH,
+            bookmarks: BTreeMap::new(),
+            pager_args: vec![],
+            symlink_max_depth: DEFAULT_SYMLINK_MAX_DEPTH,
+            preview_tab_width: DEFAULT_PREVIEW_TAB_WIDTH,
+            inline_edit_max_bytes: DEFAULT_INLINE_EDIT_MAX_BYTES,
+            entry_height: DEFAULT_ENTRY_HEIGHT,
+            entry_width_mode: EntryWidthMode::default(),
+            diff_tool: None,
+            gui_file_manager: None,
+            new_window_cmd: None,
+            state_dir: config_dir(),
+            home_dir: util::home_dir(None).map(PathBuf::from),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the configuration from the default location falling back to
+    /// defaults if the file doesn't exist.
+    pub fn load() -> Result<Config> {
+        match config_dir().map(|dir| dir.join(CONFIG_FILE)) {
+            Some(path) if path.exists() => {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                Config::parse(&content)
+                    .with_context(|| format!("invalid config {}", path.display()))
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Config> {
+        let mut config = Config::default();
+
+        for (n, line) in content.lines().enumerate() {
+            let (key, value) = match parse_line(line) {
+                Some(kv) => kv,
+                None => continue,
+            };
+
+            let res = match key {
+                "dirs_first" => parse_bool(value).map(|v| config.dirs_first = v),
+                "show_hidden" => parse_bool(value).map(|v| config.show_hidden = v),
+                "nerd_fonts" => parse_bool(value).map(|v| config.nerd_fonts = v),
+                "tree_depth" => parse_int(value).map(|v| config.tree_depth = v),
+                "sort_mode" => parse_str(value)
+                    .and_then(|v| v.parse())
+                    .map(|v| config.sort_mode = v),
+                "no_ext_first" => parse_bool(value).map(|v| config.no_ext_first = v),
+                "entry_width_mode" => parse_str(value)
+                    .and_then(|v| v.parse())
+                    .map(|v| config.entry_width_mode = v),
+                "diff_tool" => parse_str(value).map(|v| config.diff_tool = Some(v)),
+                "gui_file_manager" => parse_str(value).map(|v| config.gui_file_manager = Some(v)),
+                "new_window_cmd" => parse_str(value).map(|v| config.new_window_cmd = Some(v)),
+                "pager_args" => parse_str_array(value).map(|v| config.pager_args = v),
+                "symlink_max_depth" => parse_int(value).map(|v| config.symlink_max_depth = v),
+                "preview_tab_width" => parse_int(value).map(|v| config.preview_tab_width = v),
+                "inline_edit_max_bytes" => {
+                    parse_int(value).map(|v| config.inline_edit_max_bytes = v)
+                }
+                "open_max" => parse_int(value).map(|v| config.open_max = v),
+                "error_display_secs" => parse_int(value).map(|v| config.error_display_secs = v),
+                "recent_depth" => parse_int(value).map(|v| config.recent_depth = v),
+                "entry_height" => parse_int(value).and_then(|v| match v {
+                    1..=MAX_ENTRY_HEIGHT => {
+                        config.entry_height = v;
+                        Ok(())
+                    }
+                    _ => Err(anyhow!(
+                        "entry_height must be between 1 and {}",
+                        MAX_ENTRY_HEIGHT
+                    )),
+                }),
+                key if key.starts_with("openers.") => parse_str_array(value).map(|v| {
+                    config
+                        .openers
+                        .insert(key["openers.".len()..].to_lowercase(), v);
+                }),
+                key if key.starts_with("bookmarks.") => parse_str(value).map(|v| {
+                    config
+                        .bookmarks
+                        .insert(key["bookmarks.".len()..].to_string(), PathBuf::from(v));
+                }),
+                _ => Err(anyhow!("unknown key `{}`", key)),
+            };
+            res.with_context(|| format!("line {}", n + 1))?;
+        }
+
+        Ok(config)
+    }
+
+    /// Options of sorting the entries in ascending order.
+    pub fn sort_options(&self) -> SortOptions {
+        SortOptions {
+            reversed: false,
+            dirs_first: self.dirs_first,
+            no_ext_first: self.no_ext_first,
+        }
+    }
+
+    /// Configured openers for the file at `path`.
+    pub fn openers_for(&self, path: &Path) -> &[String] {
+        path.extension()
+            .and_then(|ext| self.openers.get(&ext.to_string_lossy().to_lowercase()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Directory holding all of trav's configuration and state files.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("trav"))
+}
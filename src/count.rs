@@ -0,0 +1,98 @@
+//! Line and character counts of the previewed file computed in the background.
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::properties::{is_text_mime, mime_type};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counts {
+    Text { lines: u64, chars: u64 },
+    Binary { lines: u64, bytes: u64 },
+}
+
+impl Counts {
+    /// Counts of the file at `path`, characters are only counted in text files.
+    pub fn of(path: &Path) -> io::Result<Counts> {
+        let md = fs::metadata(path)?;
+        let text = is_text_mime(mime_type(path, &md));
+
+        let mut file = fs::File::open(path)?;
+        let mut buf = [0; 64 * 1024];
+        let (mut lines, mut chars, mut bytes) = (0, 0, 0);
+        let mut last = b'\n';
+        loop {
+            let n = match file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            for &b in &buf[..n] {
+                if b == b'\n' {
+                    lines += 1;
+                }
+                // continuation bytes of UTF-8 sequences don't start a character
+                if b & 0xc0 != 0x80 {
+                    chars += 1;
+                }
+            }
+            bytes += n as u64;
+            last = buf[n - 1];
+        }
+        // a last line without a trailing newline still counts
+        if last != b'\n' {
+            lines += 1;
+        }
+
+        Ok(if text {
+            Counts::Text { lines, chars }
+        } else {
+            Counts::Binary { lines, bytes }
+        })
+    }
+}
+
+#[derive(Debug)]
+/// Counts of a previewed file, `None` until the background count finishes.
+pub struct FileCounts {
+    pub path: PathBuf,
+    pub counts: Option<Counts>,
+    rx: Option<mpsc::Receiver<io::Result<Counts>>>,
+}
+
+impl FileCounts {
+    /// Starts counting `path` in a background thread.
+    pub fn start(path: PathBuf) -> FileCounts {
+        let (tx, rx) = mpsc::channel();
+        let file = path.clone();
+        thread::spawn(move || {
+            let _ = tx.send(Counts::of(&file));
+        });
+        FileCounts {
+            path,
+            counts: None,
+            rx: Some(rx),
+        }
+    }
+
+    /// Collects the result of the background count.
+    pub fn update(&mut self) {
+        if let Some(Ok(res)) = self.rx.as_ref().map(mpsc::Receiver::try_recv) {
+            self.counts = res.ok();
+            self.rx = None;
+        }
+    }
+
+    /// Counts as shown in the preview title, e.g. `(123L 4567C)`.
+    pub fn describe(&self) -> String {
+        match self.counts {
+            Some(Counts::Text { lines, chars }) => format!("({}L {}C)", lines, chars),
+            Some(Counts::Binary { lines, bytes }) => format!("({}L {}B)", lines, bytes),
+            None if self.rx.is_some() => "(...)".to_string(),
+            None => String::new(),
+        }
+    }
+}
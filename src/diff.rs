@@ -0,0 +1,193 @@
+//! Line based diff of two files computed with Myers' algorithm.
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::{fs, process};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Same,
+    Removed,
+    Added,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    pub text: String,
+}
+
+/// Diffs with more edits than this aren't computed, memory use of the
+/// algorithm grows with the square of the number of edits.
+pub const MAX_EDITS: usize = 2000;
+
+/// Computes the shortest edit script turning `old` into `new`. Returns `None`
+/// if it's longer than `MAX_EDITS`.
+pub fn diff_lines(old: &[&str], new: &[&str]) -> Option<Vec<DiffLine>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    // furthest reaching x on each diagonal k, stored at `k + offset`
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    // diagonals `-d - 1..=d + 1` of `v` before each round `d`
+    let mut trace = vec![];
+
+    'search: for d in 0..=max {
+        if d as usize > MAX_EDITS {
+            return None;
+        }
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // walk the trace backwards collecting the edits
+    let mut lines = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            lines.push(line(LineKind::Same, old[x as usize]));
+        }
+        if d > 0 {
+            if x == prev_x {
+                lines.push(line(LineKind::Added, new[prev_y as usize]));
+            } else {
+                lines.push(line(LineKind::Removed, old[prev_x as usize]));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    lines.reverse();
+    Some(lines)
+}
+
+fn line(kind: LineKind, text: &str) -> DiffLine {
+    DiffLine {
+        kind,
+        text: text.to_string(),
+    }
+}
+
+/// Modification time and size used to notice changes on disk.
+type Stamp = (Option<SystemTime>, u64);
+
+fn stamp(path: &Path) -> Stamp {
+    match fs::metadata(path) {
+        Ok(md) => (md.modified().ok(), md.len()),
+        Err(_) => (None, 0),
+    }
+}
+
+#[derive(Debug)]
+/// Differences between two files kept up to date with their content.
+pub struct DiffView {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    pub lines: Vec<DiffLine>,
+    /// Index of the first visible line.
+    pub scroll: usize,
+    stamps: (Stamp, Stamp),
+}
+
+impl DiffView {
+    pub fn new(old: PathBuf, new: PathBuf) -> Result<DiffView> {
+        let mut view = DiffView {
+            old,
+            new,
+            lines: vec![],
+            scroll: 0,
+            stamps: Default::default(),
+        };
+        view.reload()?;
+        Ok(view)
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        let read = |path: &Path| {
+            fs::read_to_string(path)
+                .map_err(|e| anyhow!("failed to read {}: {}", path.display(), e))
+        };
+        let (old, new) = (read(&self.old)?, read(&self.new)?);
+        let old: Vec<_> = old.lines().collect();
+        let new: Vec<_> = new.lines().collect();
+
+        self.lines = diff_lines(&old, &new)
+            .ok_or_else(|| anyhow!("the files differ in more than {} lines", MAX_EDITS))?;
+        self.scroll = self.scroll.min(self.lines.len().saturating_sub(1));
+        self.stamps = (stamp(&self.old), stamp(&self.new));
+        Ok(())
+    }
+
+    /// Recomputes the diff if either file changed on disk. Returns `true` if
+    /// it did.
+    pub fn refresh(&mut self) -> Result<bool> {
+        if (stamp(&self.old), stamp(&self.new)) == self.stamps {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.lines.iter().all(|line| line.kind == LineKind::Same)
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.lines.len().saturating_sub(1) as isize;
+        self.scroll = (self.scroll as isize + delta).max(0).min(max) as usize;
+    }
+}
+
+/// Builds the command running an external diff tool from `template`, e.g.
+/// `vimdiff %1 %2`. `%1` and `%2` are replaced with the compared files which
+/// are appended if the template contains neither.
+pub fn diff_tool_command(template: &str, old: &Path, new: &Path) -> Result<process::Command> {
+    let (old, new) = (old.to_string_lossy(), new.to_string_lossy());
+    let mut args: Vec<String> = template.split_whitespace().map(str::to_string).collect();
+    if args.is_empty() {
+        return Err(anyhow!("empty diff tool command"));
+    }
+    if !args
+        .iter()
+        .any(|arg| arg.contains("%1") || arg.contains("%2"))
+    {
+        args.push("%1".to_string());
+        args.push("%2".to_string());
+    }
+    let mut args = args
+        .into_iter()
+        .map(|arg| arg.replace("%1", &old).replace("%2", &new));
+
+    let mut cmd = process::Command::new(args.next().unwrap_or_default());
+    cmd.args(args);
+    Ok(cmd)
+}
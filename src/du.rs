@@ -0,0 +1,125 @@
+//! Disk usage of the entries of a directory computed in the background.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuEntry {
+    pub path: PathBuf,
+    pub name: String,
+    /// Apparent size in bytes including everything below directories.
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Total size of the tree at `path` without following symlinks.
+pub fn tree_size(path: &Path) -> u64 {
+    let md = match fs::symlink_metadata(path) {
+        Ok(md) => md,
+        Err(_) => return 0,
+    };
+    if !md.is_dir() {
+        return md.len();
+    }
+
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| tree_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+#[derive(Debug)]
+/// Sizes of the entries of `root`, filled in as the background scan proceeds.
+pub struct DuMap {
+    pub root: PathBuf,
+    /// Scanned entries sorted by size, largest first.
+    pub entries: Vec<DuEntry>,
+    pub selected: usize,
+    rx: Option<mpsc::Receiver<DuEntry>>,
+}
+
+impl DuMap {
+    /// Starts scanning `root` in a background thread.
+    pub fn scan(root: PathBuf) -> DuMap {
+        let (tx, rx) = mpsc::channel();
+        let dir = root.clone();
+        thread::spawn(move || {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => return,
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                let entry = DuEntry {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    size: tree_size(&path),
+                    path,
+                    is_dir,
+                };
+                // the view was closed
+                if tx.send(entry).is_err() {
+                    return;
+                }
+            }
+        });
+
+        DuMap {
+            root,
+            entries: vec![],
+            selected: 0,
+            rx: Some(rx),
+        }
+    }
+
+    /// Collects entries scanned since the last call.
+    pub fn update(&mut self) {
+        let rx = match &self.rx {
+            Some(rx) => rx,
+            None => return,
+        };
+        let selected = self.current().map(|e| e.path.clone());
+        loop {
+            match rx.try_recv() {
+                Ok(entry) => self.entries.push(entry),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.rx = None;
+                    break;
+                }
+            }
+        }
+        self.entries
+            .sort_by(|a, b| b.size.cmp(&a.size).then(a.name.cmp(&b.name)));
+        // keep the selection on the same entry as the order changes
+        if let Some(selected) = selected {
+            self.selected = self
+                .entries
+                .iter()
+                .position(|e| e.path == selected)
+                .unwrap_or(0);
+        }
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.entries.iter().map(|e| e.size).sum()
+    }
+
+    pub fn current(&self) -> Option<&DuEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.entries.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
@@ -0,0 +1,194 @@
+//! Finding files with identical content under a directory.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::SystemTime;
+
+use crate::util::sha256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Files sharing the same content, the oldest one first.
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: u64,
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug)]
+enum Message {
+    /// Number of files hashed so far and the number of files to hash.
+    Progress(usize, usize),
+    Done(Vec<DuplicateGroup>),
+}
+
+/// Regular files under `root` grouped by size, symlinks aren't followed.
+/// Sends an empty progress for each listed directory to stop once nobody is
+/// receiving.
+fn files_by_size(
+    root: &Path,
+    tx: &mpsc::Sender<Message>,
+) -> Result<HashMap<u64, Vec<PathBuf>>, mpsc::SendError<Message>> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        tx.send(Message::Progress(0, 0))?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => dirs.push(entry.path()),
+                Ok(t) if t.is_file() => {
+                    if let Ok(md) = entry.metadata() {
+                        by_size.entry(md.len()).or_default().push(entry.path());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(by_size)
+}
+
+fn modified(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|md| md.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Groups files under `root` with identical content. Only files of equal size
+/// are hashed. Sends the progress after each hashed file and stops when it
+/// can't be sent.
+fn find_duplicates(
+    root: &Path,
+    tx: &mpsc::Sender<Message>,
+) -> Result<Vec<DuplicateGroup>, mpsc::SendError<Message>> {
+    let candidates: Vec<_> = files_by_size(root, tx)?
+        .into_iter()
+        .filter(|(size, files)| *size > 0 && files.len() > 1)
+        .collect();
+    let total = candidates.iter().map(|(_, files)| files.len()).sum();
+
+    let mut hashed = 0;
+    let mut groups = vec![];
+    for (size, files) in candidates {
+        let mut by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for file in files {
+            if let Ok(digest) = fs::File::open(&file).and_then(sha256::digest_reader) {
+                by_hash
+                    .entry(sha256::to_hex(&digest))
+                    .or_default()
+                    .push(file);
+            }
+            hashed += 1;
+            tx.send(Message::Progress(hashed, total))?;
+        }
+        for (hash, mut files) in by_hash {
+            if files.len() > 1 {
+                files.sort_by_key(|file| (modified(file), file.clone()));
+                groups.push(DuplicateGroup { hash, size, files });
+            }
+        }
+    }
+
+    // most wasted space first
+    groups.sort_by_key(|g| {
+        (
+            std::cmp::Reverse(g.size * (g.files.len() as u64 - 1)),
+            g.files[0].clone(),
+        )
+    });
+    Ok(groups)
+}
+
+#[derive(Debug)]
+/// Duplicate files under `root` found in the background.
+pub struct Duplicates {
+    pub root: PathBuf,
+    pub groups: Vec<DuplicateGroup>,
+    /// Index into the files of all groups laid out one after another.
+    pub selected: usize,
+    pub progress: (usize, usize),
+    rx: Option<mpsc::Receiver<Message>>,
+}
+
+impl Duplicates {
+    /// Starts searching `root` in a background thread.
+    pub fn scan(root: PathBuf) -> Duplicates {
+        let (tx, rx) = mpsc::channel();
+        let dir = root.clone();
+        thread::spawn(move || {
+            // the receiver is dropped once the panel is left
+            if let Ok(groups) = find_duplicates(&dir, &tx) {
+                let _ = tx.send(Message::Done(groups));
+            }
+        });
+
+        Duplicates {
+            root,
+            groups: vec![],
+            selected: 0,
+            progress: (0, 0),
+            rx: Some(rx),
+        }
+    }
+
+    /// Collects messages from the scanning thread.
+    pub fn update(&mut self) {
+        let rx = match &self.rx {
+            Some(rx) => rx,
+            None => return,
+        };
+        while let Ok(msg) = rx.try_recv() {
+            match msg {
+                Message::Progress(done, total) => self.progress = (done, total),
+                Message::Done(groups) => {
+                    self.groups = groups;
+                    self.rx = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(|g| g.files.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn current(&self) -> Option<&PathBuf> {
+        self.groups
+            .iter()
+            .flat_map(|g| g.files.iter())
+            .nth(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Forgets a deleted file dropping groups that no longer have duplicates.
+    pub fn remove(&mut self, path: &Path) {
+        for group in &mut self.groups {
+            group.files.retain(|file| file != path);
+        }
+        self.groups.retain(|g| g.files.len() > 1);
+        self.selected = self.selected.min(self.len().saturating_sub(1));
+    }
+}
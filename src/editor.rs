@@ -0,0 +1,286 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Number of lines moved by PageUp and PageDown.
+const PAGE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Result of a key press in the editor.
+pub enum EditAction {
+    None,
+    Save,
+    Discard,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A minimal editor for small text files shown in the preview panel.
+pub struct TextEditor {
+    pub path: PathBuf,
+    lines: Vec<String>,
+    /// Cursor position as `(row, column)` with the column counted in characters.
+    cursor: (usize, usize),
+    /// First visible line and column.
+    scroll: (usize, usize),
+    trailing_newline: bool,
+    /// `\r\n` or `\n`, whichever the file uses.
+    line_ending: &'static str,
+    pub modified: bool,
+}
+
+impl TextEditor {
+    /// Opens `path` for editing if it's a UTF-8 text file of at most `max_bytes`.
+    pub fn open(path: PathBuf, max_bytes: u64) -> Result<TextEditor> {
+        let size = fs::metadata(&path)?.len();
+        if size > max_bytes {
+            return Err(anyhow!(
+                "{} is larger than the inline edit limit of {} bytes",
+                path.display(),
+                max_bytes
+            ));
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow!("can't edit {}: {}", path.display(), e))?;
+        TextEditor::with_content(path, &content)
+    }
+
+    /// Edits `content` of the file at `path`. Files mixing line endings are
+    /// refused as saving them would change the lines not edited.
+    pub fn with_content(path: PathBuf, content: &str) -> Result<TextEditor> {
+        let crlf = content.matches("\r\n").count();
+        let line_ending = if crlf == 0 { "\n" } else { "\r\n" };
+        if content.matches('\r').count() != crlf
+            || (crlf > 0 && content.matches('\n').count() != crlf)
+        {
+            return Err(anyhow!("can't edit {}: mixed line endings", path.display()));
+        }
+
+        let mut lines: Vec<_> = content.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        Ok(TextEditor {
+            path,
+            lines,
+            cursor: (0, 0),
+            scroll: (0, 0),
+            trailing_newline: content.ends_with('\n'),
+            line_ending,
+            modified: false,
+        })
+    }
+
+    pub fn content(&self) -> String {
+        let mut content = self.lines.join(self.line_ending);
+        if self.trailing_newline {
+            content.push_str(self.line_ending);
+        }
+        content
+    }
+
+    /// Writes the buffer back to the file keeping its permissions.
+    pub fn save(&mut self) -> Result<()> {
+        fs::write(&self.path, self.content())?;
+        self.modified = false;
+        Ok(())
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_idx(&self, row: usize, col: usize) -> usize {
+        self.lines[row]
+            .char_indices()
+            .nth(col)
+            .map(|(i, _)| i)
+            .unwrap_or_else(|| self.lines[row].len())
+    }
+
+    fn move_to_row(&mut self, row: usize) {
+        let row = row.min(self.lines.len() - 1);
+        self.cursor = (row, self.cursor.1.min(self.line_len(row)));
+    }
+
+    fn insert(&mut self, c: char) {
+        let (row, col) = self.cursor;
+        let idx = self.byte_idx(row, col);
+        self.lines[row].insert(idx, c);
+        self.cursor.1 += 1;
+    }
+
+    fn split_line(&mut self) {
+        let (row, col) = self.cursor;
+        let idx = self.byte_idx(row, col);
+        let rest = self.lines[row].split_off(idx);
+        self.lines.insert(row + 1, rest);
+        self.cursor = (row + 1, 0);
+    }
+
+    fn backspace(&mut self) {
+        let (row, col) = self.cursor;
+        if col > 0 {
+            let idx = self.byte_idx(row, col - 1);
+            self.lines[row].remove(idx);
+            self.cursor.1 -= 1;
+        } else if row > 0 {
+            let line = self.lines.remove(row);
+            let prev_len = self.line_len(row - 1);
+            self.lines[row - 1].push_str(&line);
+            self.cursor = (row - 1, prev_len);
+        }
+    }
+
+    fn delete(&mut self) {
+        let (row, col) = self.cursor;
+        if col < self.line_len(row) {
+            let idx = self.byte_idx(row, col);
+            self.lines[row].remove(idx);
+        } else if row + 1 < self.lines.len() {
+            let next = self.lines.remove(row + 1);
+            self.lines[row].push_str(&next);
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> EditAction {
+        let (row, col) = self.cursor;
+        match key {
+            Key::Ctrl('s') => return EditAction::Save,
+            Key::Esc => return EditAction::Discard,
+            Key::Up => self.move_to_row(row.saturating_sub(1)),
+            Key::Down => self.move_to_row(row + 1),
+            Key::PageUp => self.move_to_row(row.saturating_sub(PAGE)),
+            Key::PageDown => self.move_to_row(row + PAGE),
+            Key::Left if col > 0 => self.cursor.1 -= 1,
+            Key::Left if row > 0 => self.cursor = (row - 1, self.line_len(row - 1)),
+            Key::Right if col < self.line_len(row) => self.cursor.1 += 1,
+            Key::Right if row + 1 < self.lines.len() => self.cursor = (row + 1, 0),
+            Key::Home | Key::Ctrl('a') => self.cursor.1 = 0,
+            Key::End | Key::Ctrl('e') => self.cursor.1 = self.line_len(row),
+            Key::Char('\n') => {
+                self.split_line();
+                self.modified = true;
+            }
+            Key::Char(c) => {
+                self.insert(c);
+                self.modified = true;
+            }
+            Key::Backspace => {
+                self.backspace();
+                self.modified = true;
+            }
+            Key::Delete => {
+                self.delete();
+                self.modified = true;
+            }
+            _ => {}
+        }
+        EditAction::None
+    }
+
+    /// Adjusts the scroll offsets so that the cursor fits in `width` x `height`.
+    fn scroll_to_cursor(&mut self, width: usize, height: usize) {
+        let (row, col) = self.cursor;
+        let (top, left) = &mut self.scroll;
+        if row < *top {
+            *top = row;
+        } else if height > 0 && row >= *top + height {
+            *top = row + 1 - height;
+        }
+        if col < *left {
+            *left = col;
+        } else if width > 0 && col >= *left + width {
+            *left = col + 1 - width;
+        }
+    }
+
+    pub fn render<B: Backend>(&mut self, frame: &mut Frame<B>, rect: Rect) {
+        let (width, height) = (
+            rect.width.saturating_sub(2) as usize,
+            rect.height.saturating_sub(2) as usize,
+        );
+        self.scroll_to_cursor(width, height);
+        let (top, left) = self.scroll;
+
+        let lines: Vec<_> = self
+            .lines
+            .iter()
+            .skip(top)
+            .take(height)
+            .map(|line| Spans::from(line.chars().skip(left).take(width).collect::<String>()))
+            .collect();
+        let name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let title = format!(
+            "{}{} (Ctrl-S save, Esc discard)",
+            name,
+            if self.modified { " [+]" } else { "" }
+        );
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default().borders(Borders::ALL).title(Span::styled(
+                    title,
+                    Style::default()
+                        .fg(Color::LightCyan)
+                        .add_modifier(Modifier::BOLD),
+                )),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        frame.render_widget(paragraph, rect);
+
+        let (row, col) = self.cursor;
+        frame.set_cursor(
+            rect.x + 1 + (col - left) as u16,
+            rect.y + 1 + (row - top) as u16,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(content: &str) -> TextEditor {
+        TextEditor::with_content(PathBuf::from("test.txt"), content).unwrap()
+    }
+
+    #[test]
+    fn keeps_line_endings() {
+        for content in ["a\nb\n", "a\nb", "a\r\nb\r\n", "a\r\nb", "\n", "\r\n"] {
+            assert_eq!(editor(content).content(), content, "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn keeps_empty_files_empty() {
+        assert_eq!(editor("").content(), "");
+    }
+
+    #[test]
+    fn uses_the_line_ending_of_the_file_for_new_lines() {
+        let mut editor = editor("ab\r\n");
+        editor.cursor = (0, 1);
+        editor.split_line();
+        assert_eq!(editor.content(), "a\r\nb\r\n");
+    }
+
+    #[test]
+    fn refuses_mixed_line_endings() {
+        for content in ["a\r\nb\n", "a\rb\n", "a\r"] {
+            let res = TextEditor::with_content(PathBuf::from("test.txt"), content);
+            assert!(res.is_err(), "{:?}", content);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Decoding previewed files that aren't UTF-8. Common encodings are decoded
+//! directly, anything else is converted with `iconv`.
+use anyhow::{anyhow, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Decodes `bytes` in `encoding`, e.g. `latin-1` or `shift-jis`, or in the
+/// encoding guessed from the content if it's `auto`. Returns the text and the
+/// name of the encoding used.
+pub fn decode(bytes: &[u8], encoding: &str) -> Result<(String, String)> {
+    let name = encoding.trim().to_lowercase();
+    let name = match name.as_str() {
+        "auto" => detect(bytes).to_string(),
+        _ => name,
+    };
+    let text = match name.replace('_', "-").as_str() {
+        "utf-8" | "utf8" => String::from_utf8_lossy(strip_bom(bytes, &[0xef, 0xbb, 0xbf])).into(),
+        "latin-1" | "latin1" | "iso-8859-1" => bytes.iter().map(|&b| b as char).collect(),
+        "utf-16le" => decode_utf16(strip_bom(bytes, &[0xff, 0xfe]), u16::from_le_bytes),
+        "utf-16be" => decode_utf16(strip_bom(bytes, &[0xfe, 0xff]), u16::from_be_bytes),
+        _ => iconv(bytes, &name)?,
+    };
+    Ok((text, name))
+}
+
+/// Guesses the encoding of `bytes` from a byte order mark, UTF-8 validity and
+/// the position of zero bytes typical for UTF-16 text. Other text is assumed
+/// to be `windows-1252`.
+pub fn detect(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0xef, 0xbb, 0xbf]) {
+        return "utf-8";
+    }
+    if bytes.starts_with(&[0xff, 0xfe]) {
+        return "utf-16le";
+    }
+    if bytes.starts_with(&[0xfe, 0xff]) {
+        return "utf-16be";
+    }
+    // text cut at the end of a sample may end in the middle of a character
+    match std::str::from_utf8(bytes) {
+        Ok(_) => return "utf-8",
+        Err(e) if e.error_len().is_none() => return "utf-8",
+        Err(_) => {}
+    }
+    let zeros_at = |parity| {
+        bytes
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let pairs = bytes.len() / 2;
+    if pairs > 0 && zeros_at(1) * 2 > pairs {
+        return "utf-16le";
+    }
+    if pairs > 0 && zeros_at(0) * 2 > pairs {
+        return "utf-16be";
+    }
+    "windows-1252"
+}
+
+fn strip_bom<'a>(bytes: &'a [u8], bom: &[u8]) -> &'a [u8] {
+    bytes.strip_prefix(bom).unwrap_or(bytes)
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Converts `bytes` from `encoding` to UTF-8 with `iconv`.
+fn iconv(bytes: &[u8], encoding: &str) -> Result<String> {
+    let mut child = Command::new("iconv")
+        .args(["-c", "-f", encoding, "-t", "UTF-8"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("failed to run iconv: {}", e))?;
+
+    // feed stdin from another thread so that a full stdout pipe can't block it
+    let mut stdin = child.stdin.take();
+    let input = bytes.to_vec();
+    let writer = thread::spawn(move || {
+        if let Some(stdin) = &mut stdin {
+            let _ = stdin.write_all(&input);
+        }
+    });
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    // with `-c` iconv also fails after skipping invalid characters
+    if !output.status.success() && output.stdout.is_empty() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!(
+            "unsupported encoding `{}`: {}",
+            encoding,
+            err.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into())
+}
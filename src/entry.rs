@@ -1,24 +1,120 @@
-use anyhow::Result;
+use anyhow::{anyhow, Error, Result};
 use chrono::SecondsFormat;
-use std::ffi::OsString;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::{fs, io};
 use tui::{
     style::{Color, Modifier, Style},
     text::{Span, Spans},
     widgets::{Block, Borders, List, ListItem},
 };
+use unicode_width::UnicodeWidthChar;
 
+use crate::icons::IconMap;
+use crate::properties::format_mode;
+use crate::sort::{sort_entries, SortMode, SortOptions};
+use crate::tags;
 use crate::util;
+use crate::util::list::StatefulList;
+use crate::util::regex::Regex;
+
+/// Marks the highlighted entry of a list.
+pub const HIGHLIGHT_SYMBOL: &str = "-> ";
+
+/// Number of lines rendered per entry unless configured otherwise.
+pub const DEFAULT_ENTRY_HEIGHT: u8 = 2;
+/// Most lines an entry can take, one for each piece of information.
+pub const MAX_ENTRY_HEIGHT: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How names wider than their panel are shown.
+pub enum EntryWidthMode {
+    /// Cut off at the panel's edge.
+    #[default]
+    Truncate,
+    /// Continued on the following lines.
+    Wrap,
+    /// Cut off with a trailing `…`.
+    Ellipsis,
+}
+
+impl FromStr for EntryWidthMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "truncate" => Ok(EntryWidthMode::Truncate),
+            "wrap" => Ok(EntryWidthMode::Wrap),
+            "ellipsis" => Ok(EntryWidthMode::Ellipsis),
+            _ => Err(anyhow!("unknown entry width mode `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Decorations applied when rendering an entry as a list item.
+pub struct ItemOptions<'a> {
+    /// The part of the name matching this regex is emphasized.
+    pub highlight: Option<&'a Regex>,
+    pub selected: bool,
+    /// Lines per entry, the name, size, permissions and modification time.
+    pub height: u8,
+    /// Columns available for the name line, unlimited if `None`.
+    pub width: Option<u16>,
+    pub width_mode: EntryWidthMode,
+    /// Glyphs used instead of the emoji icons.
+    pub icons: Option<&'a IconMap>,
+    /// Color tags of entries shown as bullets before their names.
+    pub tags: Option<&'a HashMap<PathBuf, u8>>,
+    /// Workspace roots shown with a house instead of the directory icon.
+    pub workspaces: Option<&'a [PathBuf]>,
+}
+
+impl Default for ItemOptions<'_> {
+    fn default() -> Self {
+        ItemOptions {
+            highlight: None,
+            selected: false,
+            height: DEFAULT_ENTRY_HEIGHT,
+            width: None,
+            width_mode: EntryWidthMode::default(),
+            icons: None,
+            tags: None,
+            workspaces: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A recent change of an entry highlighted in watch mode.
+pub struct ChangeState {
+    pub kind: ChangeKind,
+    /// Remaining frames of the highlight animation.
+    pub ticks_left: u8,
+}
 
 #[derive(Debug)]
 pub struct DirEntry {
     inner: fs::DirEntry,
+    change: Option<ChangeState>,
 }
 
 impl From<fs::DirEntry> for DirEntry {
     fn from(entry: fs::DirEntry) -> Self {
-        DirEntry { inner: entry }
+        DirEntry {
+            inner: entry,
+            change: None,
+        }
     }
 }
 
@@ -39,78 +135,240 @@ impl DirEntry {
         self.inner.file_name()
     }
 
-    pub fn as_list_item(&self) -> ListItem {
+    pub fn change(&self) -> Option<ChangeKind> {
+        self.change.map(|state| state.kind)
+    }
+
+    pub fn set_change(&mut self, change: Option<ChangeState>) {
+        self.change = change;
+    }
+
+    pub fn as_list_item(&self, opts: &ItemOptions) -> ListItem<'_> {
         let mut lines = vec![];
 
         if let Ok(metadata) = self.inner.metadata() {
             let file_type = metadata.file_type();
 
-            let symbol = if file_type.is_dir() {
-                "📁"
-            } else if file_type.is_file() {
-                "📄"
-            } else {
-                "🔗"
+            let is_workspace = || {
+                opts.workspaces
+                    .is_some_and(|roots| roots.contains(&self.path()))
+            };
+            let symbol = match opts.icons {
+                _ if file_type.is_dir() && is_workspace() => "🏠".to_string(),
+                Some(icons) => icons.icon(&self.inner.file_name(), file_type).to_string(),
+                None if file_type.is_dir() => "📁".to_string(),
+                None if file_type.is_file() => "📄".to_string(),
+                None => "🔗".to_string(),
             };
 
-            lines.push(Spans::from(Span::styled(
-                format!(
-                    "{} {}",
-                    symbol,
-                    self.inner.file_name().to_string_lossy().to_string()
-                ),
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            )));
+            let mut name = vec![];
+            if opts.selected {
+                name.push(Span::styled(
+                    "+ ",
+                    Style::default()
+                        .fg(Color::LightGreen)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            if let Some(&tag) = opts.tags.and_then(|tags| tags.get(&self.path())) {
+                name.push(Span::styled("● ", Style::default().fg(tags::color(tag))));
+            }
+            name.push(Span::raw(format!("{} ", symbol)));
+            name.extend(highlighted_name(
+                self.inner.file_name().to_string_lossy().to_string(),
+                opts.highlight,
+                self.name_style(opts.selected),
+            ));
+            lines.extend(fit_spans(name, opts));
 
             let time = if let Ok(time) = metadata.modified() {
                 util::system_time_to_date_time(time).to_rfc3339_opts(SecondsFormat::Secs, true)
             } else {
                 String::new()
             };
+            let gray = Style::default().fg(Color::Gray);
+            let size = Span::styled(
+                util::conv_b(metadata.len()),
+                gray.add_modifier(Modifier::BOLD),
+            );
 
-            lines.push(Spans::from(vec![
-                Span::styled(time, Style::default().fg(Color::Gray)),
-                Span::raw(" "),
-                Span::styled(
-                    util::conv_b(metadata.len()),
-                    Style::default()
-                        .fg(Color::Gray)
-                        .add_modifier(Modifier::BOLD),
-                ),
-            ]));
+            // the time shares the line with the size unless it has its own
+            match opts.height {
+                0 | 1 => {}
+                2 | 3 => lines.push(Spans::from(vec![
+                    Span::styled(time.clone(), gray),
+                    Span::raw(" "),
+                    size,
+                ])),
+                _ => lines.push(Spans::from(size)),
+            }
+            if opts.height >= 3 {
+                lines.push(Spans::from(Span::styled(
+                    format_mode(metadata.permissions().mode()),
+                    gray,
+                )));
+            }
+            if opts.height >= 4 {
+                lines.push(Spans::from(Span::styled(time, gray)));
+            }
         } else {
-            lines.push(Spans::from(
-                self.inner.file_name().to_string_lossy().to_string(),
+            lines.extend(fit_spans(
+                highlighted_name(
+                    self.inner.file_name().to_string_lossy().to_string(),
+                    opts.highlight,
+                    self.name_style(opts.selected),
+                ),
+                opts,
             ));
         }
 
         ListItem::new(lines).style(Style::default().fg(Color::White).bg(Color::Black))
     }
+
+    fn name_style(&self, selected: bool) -> Style {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        match self.change() {
+            Some(ChangeKind::Created) => {
+                style.fg(Color::LightGreen).add_modifier(Modifier::REVERSED)
+            }
+            Some(ChangeKind::Modified) => style.fg(Color::Yellow).add_modifier(Modifier::REVERSED),
+            Some(ChangeKind::Deleted) => style.fg(Color::Red).add_modifier(Modifier::CROSSED_OUT),
+            None if selected => style.fg(Color::LightGreen),
+            None => style.fg(Color::White),
+        }
+    }
+}
+
+fn highlighted_name(name: String, highlight: Option<&Regex>, style: Style) -> Vec<Span<'static>> {
+    match highlight.and_then(|re| re.find(&name)) {
+        Some((start, end)) if start != end => vec![
+            Span::styled(name[..start].to_string(), style),
+            Span::styled(
+                name[start..end].to_string(),
+                style.fg(Color::Black).bg(Color::LightYellow),
+            ),
+            Span::styled(name[end..].to_string(), style),
+        ],
+        _ => vec![Span::styled(name, style)],
+    }
+}
+
+/// Lays out `spans` in `opts.width` columns according to `opts.width_mode`.
+fn fit_spans(spans: Vec<Span<'static>>, opts: &ItemOptions) -> Vec<Spans<'static>> {
+    let width = match opts.width {
+        Some(width) => width as usize,
+        None => return vec![Spans::from(spans)],
+    };
+    let total: usize = spans.iter().map(|s| s.width()).sum();
+    if total <= width {
+        return vec![Spans::from(spans)];
+    }
+
+    let ellipsis = opts.width_mode == EntryWidthMode::Ellipsis;
+    // the last line keeps room for the ellipsis
+    let limit = if ellipsis {
+        width.saturating_sub(1)
+    } else {
+        width
+    };
+    let mut lines = vec![];
+    let mut line = vec![];
+    let mut used = 0;
+    let mut last_style = Style::default();
+    'spans: for span in spans {
+        last_style = span.style;
+        let mut text = String::new();
+        for c in span.content.chars() {
+            let w = c.width().unwrap_or(0);
+            if used + w > limit {
+                if opts.width_mode != EntryWidthMode::Wrap {
+                    line.push(Span::styled(text, span.style));
+                    break 'spans;
+                }
+                line.push(Span::styled(std::mem::take(&mut text), span.style));
+                lines.push(Spans::from(std::mem::take(&mut line)));
+                used = 0;
+            }
+            text.push(c);
+            used += w;
+        }
+        line.push(Span::styled(text, span.style));
+    }
+    if ellipsis {
+        line.push(Span::styled("…", last_style));
+    }
+    lines.push(Spans::from(line));
+    lines
+}
+
+impl StatefulList<DirEntry> {
+    /// Selects the first entry named `name`. Returns `false` if there's none.
+    pub fn select_by_name(&mut self, name: &OsStr) -> bool {
+        match self.items.iter().position(|e| e.file_name() == name) {
+            Some(idx) => {
+                self.select(Some(idx));
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 pub fn get_ok_entries(path: &Path) -> Result<Vec<DirEntry>> {
     let mut entries = vec![];
-    for entry in fs::read_dir(path)? {
-        if let Ok(entry) = entry {
-            entries.push(DirEntry::from(entry));
-        }
+    for entry in fs::read_dir(path)?.flatten() {
+        entries.push(DirEntry::from(entry));
     }
 
     Ok(entries)
 }
 
+/// Entries of `path` as listed by trav, dotfiles are left out unless
+/// `show_hidden` is set.
+pub fn list_sorted(
+    path: &Path,
+    show_hidden: bool,
+    mode: SortMode,
+    opts: SortOptions,
+) -> Result<Vec<DirEntry>> {
+    let mut entries = get_ok_entries(path)?;
+    if !show_hidden {
+        entries.retain(|entry| !entry.file_name().to_string_lossy().starts_with('.'));
+    }
+    Ok(sort_entries(entries, mode, opts))
+}
+
 pub fn styled_file_entries(title: String, entries: Vec<ListItem>) -> List {
+    styled_file_entries_with_badge(title, None, entries)
+}
+
+/// List of entries titled `title` followed by `badge` in red.
+pub fn styled_file_entries_with_badge(
+    title: String,
+    badge: Option<String>,
+    entries: Vec<ListItem>,
+) -> List {
+    let mut title = vec![Span::styled(
+        title,
+        Style::default()
+            .fg(Color::Yellow)
+            .bg(Color::Black)
+            .add_modifier(Modifier::BOLD),
+    )];
+    if let Some(badge) = badge {
+        title.push(Span::styled(
+            badge,
+            Style::default()
+                .fg(Color::Red)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
     List::new(entries)
         .block(
-            Block::default().borders(Borders::ALL).title(Span::styled(
-                title,
-                Style::default()
-                    .fg(Color::Yellow)
-                    .bg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
-            )),
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Spans::from(title)),
         )
         .style(Style::default().bg(Color::Black))
         .highlight_style(
@@ -120,5 +378,105 @@ pub fn styled_file_entries(title: String, entries: Vec<ListItem>) -> List {
                 .add_modifier(Modifier::BOLD)
                 .add_modifier(Modifier::ITALIC),
         )
-        .highlight_symbol("-> ")
+        .highlight_symbol(HIGHLIGHT_SYMBOL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    /// Directory removed when dropped. The `tempfile` crate isn't a
+    /// dependency, this covers what the tests need.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let path =
+                std::env::temp_dir().join(format!("trav-entry-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// File types of `entries` by name, `"file"`, `"dir"` or `"symlink"`.
+    fn kinds(entries: &[DirEntry]) -> HashMap<String, &'static str> {
+        entries
+            .iter()
+            .map(|entry| {
+                let file_type = entry.file_type().unwrap();
+                let kind = if file_type.is_symlink() {
+                    "symlink"
+                } else if file_type.is_dir() {
+                    "dir"
+                } else {
+                    "file"
+                };
+                (entry.file_name().to_string_lossy().to_string(), kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lists_files_dirs_and_symlinks() {
+        let dir = TempDir::new("mixed");
+        fs::write(dir.path().join("file.txt"), "text").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        symlink("file.txt", dir.path().join("link")).unwrap();
+        symlink("missing", dir.path().join("broken")).unwrap();
+
+        let entries = get_ok_entries(dir.path()).unwrap();
+        let kinds = kinds(&entries);
+        assert_eq!(kinds.len(), 4);
+        assert_eq!(kinds["file.txt"], "file");
+        assert_eq!(kinds["sub"], "dir");
+        assert_eq!(kinds["link"], "symlink");
+        assert_eq!(kinds["broken"], "symlink");
+        for entry in &entries {
+            assert_eq!(entry.path().parent(), Some(dir.path()));
+        }
+    }
+
+    #[test]
+    fn lists_nothing_in_empty_dirs() {
+        let dir = TempDir::new("empty");
+        assert!(get_ok_entries(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn lists_hidden_files() {
+        let dir = TempDir::new("hidden");
+        fs::write(dir.path().join(".hidden"), "").unwrap();
+        fs::create_dir(dir.path().join(".config")).unwrap();
+
+        let kinds = kinds(&get_ok_entries(dir.path()).unwrap());
+        assert_eq!(kinds.len(), 2);
+        assert_eq!(kinds[".hidden"], "file");
+        assert_eq!(kinds[".config"], "dir");
+    }
+
+    #[test]
+    fn fails_on_files() {
+        let dir = TempDir::new("file");
+        let file = dir.path().join("file.txt");
+        fs::write(&file, "text").unwrap();
+        assert!(get_ok_entries(&file).is_err());
+    }
+
+    #[test]
+    fn fails_on_missing_paths() {
+        let dir = TempDir::new("missing");
+        assert!(get_ok_entries(&dir.path().join("missing")).is_err());
+    }
 }
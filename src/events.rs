@@ -1,4 +1,5 @@
-use std::io;
+use std::io::{self, Read};
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::sync::mpsc;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -7,23 +8,105 @@ use std::sync::{
 use std::thread;
 use std::time::Duration;
 
-use termion::event::Key;
+use termion::event::{self, Key, MouseEvent};
 use termion::input::TermRead;
 
 #[derive(Debug)]
 pub enum Event<I> {
     Input(I),
+    /// Ctrl modified key that can't be represented by termion's `Key`, like
+    /// `Ctrl-Left`.
+    CtrlInput(I),
+    Mouse(MouseEvent),
     Tick,
 }
 
+/// Decodes xterm style `ESC [ 1 ; 5 <key>` sequences of ctrl modified arrows
+/// and Home/End keys.
+fn parse_ctrl_key(seq: &[u8]) -> Option<Key> {
+    match seq {
+        [0x1B, b'[', b'1', b';', b'5', key] => match key {
+            b'A' => Some(Key::Up),
+            b'B' => Some(Key::Down),
+            b'C' => Some(Key::Right),
+            b'D' => Some(Key::Left),
+            b'H' => Some(Key::Home),
+            b'F' => Some(Key::End),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// How often a paused input thread checks whether it was resumed.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Reads from the terminal only while not paused so that input meant for
+/// another program running in the foreground isn't consumed.
+struct PausableInput {
+    /// Keeps the terminal open, reads go straight to `fd` because a buffered
+    /// reader like `Stdin` would hold back keys `poll` doesn't know about.
+    _source: Box<dyn Read + Send>,
+    fd: RawFd,
+    paused: Arc<AtomicBool>,
+}
+
+impl Read for PausableInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.paused.load(Ordering::Relaxed) {
+                thread::sleep(PAUSE_POLL_INTERVAL);
+                continue;
+            }
+            let mut pollfd = libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `pollfd` is a valid pointer to a single element
+            let ready = unsafe {
+                libc::poll(
+                    &mut pollfd,
+                    1,
+                    PAUSE_POLL_INTERVAL.as_millis() as libc::c_int,
+                )
+            };
+            if ready < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+            if ready > 0 && !self.paused.load(Ordering::Relaxed) {
+                // SAFETY: `buf` is valid for writes of `buf.len()` bytes
+                let n = unsafe { libc::read(self.fd, buf.as_mut_ptr().cast(), buf.len()) };
+                if n < 0 {
+                    let err = io::Error::last_os_error();
+                    if err.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(err);
+                }
+                return Ok(n as usize);
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 /// A small event handler that wrap termion input and tick events. Each event
-/// type is handled in its own thread and returned to a common `Receiver`
-pub struct Events {
-    rx: mpsc::Receiver<Event<Key>>,
+/// type is handled in its own thread and returned to a common `Receiver`.
+/// Keys are converted to `T` so that other events, sent with `sender`, can
+/// share the channel.
+pub struct Events<T = Key> {
+    rx: mpsc::Receiver<Event<T>>,
+    tx: mpsc::Sender<Event<T>>,
+    /// `None` when keys are only sent with `sender`.
     #[allow(dead_code)]
-    input_handle: thread::JoinHandle<()>,
+    input_handle: Option<thread::JoinHandle<()>>,
     ignore_exit_key: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
     #[allow(dead_code)]
     tick_handle: thread::JoinHandle<()>,
 }
@@ -43,39 +126,73 @@ impl Default for Config {
     }
 }
 
-impl Default for Events {
+impl<T: From<Key> + Send + 'static> Default for Events<T> {
     fn default() -> Self {
         Events::new()
     }
 }
 
-impl Events {
-    pub fn new() -> Events {
+impl<T: From<Key> + Send + 'static> Events<T> {
+    pub fn new() -> Events<T> {
         Events::with_config(Config::default())
     }
 
-    pub fn with_config(config: Config) -> Events {
-        let (tx, rx) = mpsc::channel();
-        let ignore_exit_key = Arc::new(AtomicBool::new(false));
-        let input_handle = {
-            let tx = tx.clone();
-            let ignore_exit_key = ignore_exit_key.clone();
+    pub fn with_config(config: Config) -> Events<T> {
+        let mut events = Events::without_input(config);
+        events.input_handle = Some({
+            let tx = events.tx.clone();
+            let ignore_exit_key = events.ignore_exit_key.clone();
+            let paused = events.paused.clone();
             thread::spawn(move || {
-                let stdin = io::stdin();
-                for evt in stdin.keys() {
-                    if let Ok(key) = evt {
-                        if let Err(err) = tx.send(Event::Input(key)) {
-                            eprintln!("{}", err);
-                            return;
+                // when stdin is redirected keys are read directly from the terminal
+                let (source, fd): (Box<dyn Read + Send>, _) = if termion::is_tty(&io::stdin()) {
+                    (Box::new(io::stdin()), io::stdin().as_raw_fd())
+                } else {
+                    match termion::get_tty() {
+                        Ok(tty) => {
+                            let fd = tty.as_raw_fd();
+                            (Box::new(tty), fd)
                         }
-                        if !ignore_exit_key.load(Ordering::Relaxed) && key == config.exit_key {
+                        Err(err) => {
+                            crate::error!("can't open the terminal for input: {}", err);
                             return;
                         }
                     }
+                };
+                let input = PausableInput {
+                    _source: source,
+                    fd,
+                    paused,
+                };
+                for evt in input.events().flatten() {
+                    let is_exit_key = evt == event::Event::Key(config.exit_key);
+                    let evt = match evt {
+                        event::Event::Key(key) => Event::Input(T::from(key)),
+                        event::Event::Unsupported(seq) => match parse_ctrl_key(&seq) {
+                            Some(key) => Event::CtrlInput(T::from(key)),
+                            None => continue,
+                        },
+                        event::Event::Mouse(mouse) => Event::Mouse(mouse),
+                    };
+                    if let Err(err) = tx.send(evt) {
+                        crate::error!("input thread: {}", err);
+                        return;
+                    }
+                    if !ignore_exit_key.load(Ordering::Relaxed) && is_exit_key {
+                        return;
+                    }
                 }
             })
-        };
+        });
+        events
+    }
+
+    /// Only sends ticks, keys are sent with `sender` instead of being read from
+    /// the terminal, e.g. when driving trav from tests.
+    pub fn without_input(config: Config) -> Events<T> {
+        let (tx, rx) = mpsc::channel();
         let tick_handle = {
+            let tx = tx.clone();
             thread::spawn(move || loop {
                 if tx.send(Event::Tick).is_err() {
                     break;
@@ -85,16 +202,24 @@ impl Events {
         };
         Events {
             rx,
-            ignore_exit_key,
-            input_handle,
+            tx,
+            ignore_exit_key: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            input_handle: None,
             tick_handle,
         }
     }
 
-    pub fn next(&self) -> Result<Event<Key>, mpsc::RecvError> {
+    pub fn next(&self) -> Result<Event<T>, mpsc::RecvError> {
         self.rx.recv()
     }
 
+    /// Sender of events returned by `next` along with the input and ticks,
+    /// e.g. for background work to report it's done.
+    pub fn sender(&self) -> mpsc::Sender<Event<T>> {
+        self.tx.clone()
+    }
+
     pub fn disable_exit_key(&mut self) {
         self.ignore_exit_key.store(true, Ordering::Relaxed);
     }
@@ -102,4 +227,14 @@ impl Events {
     pub fn enable_exit_key(&mut self) {
         self.ignore_exit_key.store(false, Ordering::Relaxed);
     }
+
+    /// Stops reading input until `resume` is called, e.g. while another
+    /// program runs in the terminal.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
 }
@@ -0,0 +1,120 @@
+//! Files and directories pinned with `f`, saved in
+//! `~/.config/trav/favorites.toml` most recently accessed first.
+use anyhow::{anyhow, Context, Result};
+use chrono::{Local, TimeZone};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::util;
+use crate::util::toml::{parse_line, parse_str_array, quote_array};
+
+const FAVORITES_FILE: &str = "favorites.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Favorite {
+    pub path: PathBuf,
+    /// Seconds since the epoch when it was last navigated to, `None` if never.
+    pub accessed: Option<i64>,
+}
+
+impl Favorite {
+    /// Last access formatted for display.
+    pub fn accessed_at(&self) -> String {
+        match self
+            .accessed
+            .and_then(|secs| Local.timestamp_opt(secs, 0).single())
+        {
+            Some(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+            None => "never".to_string(),
+        }
+    }
+}
+
+pub fn path(config_dir: Option<&Path>) -> Result<PathBuf> {
+    config_dir
+        .map(|dir| dir.join(FAVORITES_FILE))
+        .ok_or_else(|| anyhow!("failed to determine the config directory"))
+}
+
+/// Reads the saved favorites. A missing file is an empty list.
+pub fn load(config_dir: Option<&Path>) -> Result<Vec<Favorite>> {
+    let path = path(config_dir)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("invalid {}", path.display()))
+}
+
+/// Parses `favorites = ["<accessed> <path>", ...]` where `accessed` is `0` for
+/// favorites never navigated to.
+pub fn parse(content: &str) -> Result<Vec<Favorite>> {
+    let mut favorites = vec![];
+    for line in content.lines() {
+        match parse_line(line) {
+            Some(("favorites", value)) => {
+                favorites = parse_str_array(value)?
+                    .iter()
+                    .map(|item| parse_item(item))
+                    .collect::<Result<_>>()?;
+            }
+            Some((key, _)) => return Err(anyhow!("unknown key `{}`", key)),
+            None => {}
+        }
+    }
+    Ok(favorites)
+}
+
+fn parse_item(item: &str) -> Result<Favorite> {
+    let (accessed, path) = item
+        .split_once(' ')
+        .ok_or_else(|| anyhow!("expected `<accessed> <path>`, found `{}`", item))?;
+    let accessed: i64 = accessed
+        .parse()
+        .map_err(|_| anyhow!("invalid access time `{}`", accessed))?;
+    Ok(Favorite {
+        path: PathBuf::from(path),
+        accessed: (accessed > 0).then_some(accessed),
+    })
+}
+
+pub fn save(favorites: &[Favorite], config_dir: Option<&Path>) -> Result<()> {
+    let path = path(config_dir)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let items: Vec<_> = favorites
+        .iter()
+        .map(|f| format!("{} {}", f.accessed.unwrap_or(0), f.path.to_string_lossy()))
+        .collect();
+    let content = format!("favorites = {}\n", quote_array(&items));
+    util::write_atomic(&path, content.as_bytes())
+}
+
+/// Adds `path` after the accessed favorites or removes it if it's already one.
+/// Returns whether it was added.
+pub fn toggle(favorites: &mut Vec<Favorite>, path: &Path) -> bool {
+    if let Some(i) = favorites.iter().position(|f| f.path == path) {
+        favorites.remove(i);
+        return false;
+    }
+    favorites.push(Favorite {
+        path: path.to_path_buf(),
+        accessed: None,
+    });
+    true
+}
+
+/// Marks the favorite at `path` as accessed now moving it to the front.
+/// Returns `false` if `path` isn't a favorite.
+pub fn touch(favorites: &mut Vec<Favorite>, path: &Path) -> bool {
+    let i = match favorites.iter().position(|f| f.path == path) {
+        Some(i) => i,
+        None => return false,
+    };
+    let mut favorite = favorites.remove(i);
+    favorite.accessed = Some(Local::now().timestamp());
+    favorites.insert(0, favorite);
+    true
+}
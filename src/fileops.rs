@@ -0,0 +1,174 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardOp {
+    Copy,
+    Cut,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Entries marked for copying or moving with the next paste.
+pub struct Clipboard {
+    pub op: ClipboardOp,
+    pub paths: Vec<PathBuf>,
+}
+
+/// Copies `src` to `dst` recursing into directories. Symlinks are recreated
+/// rather than followed.
+pub fn copy_recursive(src: &Path, dst: &Path) -> Result<()> {
+    let md = fs::symlink_metadata(src)?;
+    let file_type = md.file_type();
+
+    if file_type.is_symlink() {
+        std::os::unix::fs::symlink(fs::read_link(src)?, dst)?;
+    } else if file_type.is_dir() {
+        fs::create_dir(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+        fs::set_permissions(dst, md.permissions())?;
+    } else {
+        fs::copy(src, dst)?;
+    }
+
+    Ok(())
+}
+
+/// Moves `src` to `dst` falling back to copying and removing the source when
+/// they're on different filesystems.
+pub fn move_path(src: &Path, dst: &Path) -> Result<()> {
+    match fs::rename(src, dst) {
+        Ok(()) => Ok(()),
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {
+            copy_recursive(src, dst)?;
+            remove_path(src)
+        }
+        Err(e) => Err(anyhow!("can't move {}: {}", src.display(), e)),
+    }
+}
+
+/// Removes a file, a symlink or a whole directory tree.
+pub fn remove_path(path: &Path) -> Result<()> {
+    let md = fs::symlink_metadata(path)?;
+    if md.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Removes a directory tree that contains nothing but directories, fails
+/// without removing the rest if a file turns up.
+pub fn remove_empty_dir(path: &Path) -> Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            remove_empty_dir(&entry.path())?;
+        }
+    }
+    fs::remove_dir(path).map_err(|e| anyhow!("can't remove {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// Creates a symlink at `link` pointing to `target`, which is resolved relative
+/// to the directory of the link if it isn't absolute.
+pub fn create_symlink(target: &Path, link: &Path) -> Result<()> {
+    if fs::symlink_metadata(link).is_ok() {
+        return Err(anyhow!("{} already exists", link.display()));
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, link)?;
+    #[cfg(windows)]
+    {
+        let resolved = link
+            .parent()
+            .map(|dir| dir.join(target))
+            .unwrap_or_default();
+        if resolved.is_dir() {
+            std::os::windows::fs::symlink_dir(target, link)?;
+        } else {
+            std::os::windows::fs::symlink_file(target, link)?;
+        }
+    }
+    Ok(())
+}
+
+/// Name suggested for a copy of the file `name` in the same directory,
+/// `foo_copy.txt` for `foo.txt`.
+pub fn duplicate_name(name: &str) -> String {
+    let path = Path::new(name);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => {
+            format!("{}_copy.{}", stem.to_string_lossy(), ext.to_string_lossy())
+        }
+        _ => format!("{}_copy", name),
+    }
+}
+
+/// Copies the file `src` to `dst` refusing to overwrite an existing entry.
+pub fn duplicate_file(src: &Path, dst: &Path) -> Result<()> {
+    if fs::symlink_metadata(dst).is_ok() {
+        return Err(anyhow!("{} already exists", dst.display()));
+    }
+    fs::copy(src, dst).map_err(|e| anyhow!("can't copy {}: {}", src.display(), e))?;
+    Ok(())
+}
+
+/// Pastes the entries from `clipboard` into `dir` refusing to overwrite
+/// existing entries. Returns the pasted paths.
+pub fn paste(clipboard: &Clipboard, dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut pasted = vec![];
+    for src in &clipboard.paths {
+        let name = src
+            .file_name()
+            .ok_or_else(|| anyhow!("invalid path {}", src.display()))?;
+        let dst = dir.join(name);
+        // a dangling symlink would be replaced by `rename`
+        if fs::symlink_metadata(&dst).is_ok() {
+            return Err(anyhow!("{} already exists", dst.display()));
+        }
+        if dir.starts_with(src) {
+            return Err(anyhow!("can't paste {} into itself", src.display()));
+        }
+
+        match clipboard.op {
+            ClipboardOp::Copy => copy_recursive(src, &dst)?,
+            ClipboardOp::Cut => move_path(src, &dst)?,
+        }
+        pasted.push(dst);
+    }
+
+    Ok(pasted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn paste_keeps_dangling_symlinks() {
+        let dir = env::temp_dir().join(format!("trav-fileops-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("dst")).unwrap();
+        fs::write(dir.join("a"), "a").unwrap();
+        std::os::unix::fs::symlink("missing", dir.join("dst").join("a")).unwrap();
+
+        let clipboard = Clipboard {
+            op: ClipboardOp::Cut,
+            paths: vec![dir.join("a")],
+        };
+        let res = paste(&clipboard, &dir.join("dst"));
+        let link = fs::read_link(dir.join("dst").join("a"));
+        let src_kept = dir.join("a").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(res.is_err());
+        assert_eq!(link.unwrap(), Path::new("missing"));
+        assert!(src_kept);
+    }
+}
@@ -0,0 +1,339 @@
+use anyhow::{anyhow, Error, Result};
+use chrono::{offset::Utc, DateTime};
+use std::ops::Range;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fmt, fs};
+
+use crate::entry::DirEntry;
+use crate::tags;
+use crate::util;
+use crate::util::regex::Regex;
+
+#[derive(Debug, Default)]
+/// Filters applied to the entries of the current directory. An entry is shown
+/// only if it matches all active filters.
+pub struct Filters {
+    /// Extensions without the leading dot.
+    pub extensions: Option<Vec<String>>,
+    pub size: Option<SizeFilter>,
+    pub date: Option<DateFilter>,
+    /// Matched against file names.
+    pub regex: Option<Regex>,
+    pub perm: Option<PermFilter>,
+    /// Only entries with this color tag, applied by the app which knows the
+    /// tags.
+    pub tag: Option<u8>,
+    /// Match extensions and the regex respecting case, toggled with `Ctrl-I`
+    /// while searching.
+    pub case_sensitive: bool,
+}
+
+impl Filters {
+    pub fn is_active(&self) -> bool {
+        self.extensions.is_some()
+            || self.size.is_some()
+            || self.date.is_some()
+            || self.regex.is_some()
+            || self.perm.is_some()
+            || self.tag.is_some()
+    }
+
+    pub fn matches(&self, entry: &DirEntry) -> bool {
+        if let Some(extensions) = &self.extensions {
+            if !matches_extension(&entry.path(), extensions, self.case_sensitive) {
+                return false;
+            }
+        }
+        if let Some(size) = &self.size {
+            match entry.metadata() {
+                Ok(md) if size.matches(md.len()) => {}
+                _ => return false,
+            }
+        }
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(&entry.file_name().to_string_lossy()) {
+                return false;
+            }
+        }
+        if let Some(date) = &self.date {
+            match entry.metadata().and_then(|md| md.modified()) {
+                Ok(time) if date.matches(util::system_time_to_date_time(time)) => {}
+                _ => return false,
+            }
+        }
+        if let Some(perm) = &self.perm {
+            match entry.metadata() {
+                Ok(md) if perm.matches(&md) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    pub fn apply(&self, entries: Vec<DirEntry>) -> Vec<DirEntry> {
+        if !self.is_active() {
+            return entries;
+        }
+        entries.into_iter().filter(|e| self.matches(e)).collect()
+    }
+
+    /// Compiles `pattern` respecting the case setting.
+    pub fn regex(&self, pattern: &str) -> Result<Regex> {
+        Regex::with_case(pattern, !self.case_sensitive)
+    }
+
+    /// Switches between case sensitive and insensitive matching, the regex is
+    /// recompiled accordingly.
+    pub fn toggle_case(&mut self) -> Result<()> {
+        self.case_sensitive = !self.case_sensitive;
+        if let Some(regex) = &self.regex {
+            self.regex = Some(self.regex(regex.as_str())?);
+        }
+        Ok(())
+    }
+
+    /// Indicator of the case setting shown in the search prompts, `[I]` if
+    /// case matters and `[i]` if it's ignored.
+    pub fn case_indicator(&self) -> &'static str {
+        if self.case_sensitive {
+            "[I]"
+        } else {
+            "[i]"
+        }
+    }
+
+    /// Forgets the case setting once nothing is searched for anymore.
+    pub fn end_search(&mut self) {
+        if self.extensions.is_none() && self.regex.is_none() {
+            self.case_sensitive = false;
+        }
+    }
+
+    /// Short description of active filters appended to the panel title.
+    pub fn describe(&self) -> String {
+        let mut desc = String::new();
+        if let Some(extensions) = &self.extensions {
+            desc.push_str(&format!(" [ext: {}]", extensions.join(",")));
+        }
+        if let Some(size) = &self.size {
+            desc.push_str(&format!(" [size: {}]", size));
+        }
+        if let Some(date) = &self.date {
+            desc.push_str(&format!(" [date: {}]", date.desc));
+        }
+        if let Some(regex) = &self.regex {
+            desc.push_str(&format!(" [regex: {}]", regex));
+        }
+        if let Some(tag) = self.tag {
+            desc.push_str(&format!(" [tagged: {}]", tags::name(tag)));
+        }
+        desc
+    }
+}
+
+/// Parses a comma separated list of extensions like `rs,.toml, MD`. Returns
+/// `None` if the list is empty.
+pub fn parse_extensions(s: &str) -> Option<Vec<String>> {
+    let extensions: Vec<_> = s
+        .split(',')
+        .map(|ext| ext.trim().trim_start_matches('.').to_string())
+        .filter(|ext| !ext.is_empty())
+        .collect();
+
+    if extensions.is_empty() {
+        None
+    } else {
+        Some(extensions)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Entries with security relevant permission bits set, selected with `:perm`.
+pub enum PermFilter {
+    WorldWritable,
+    Suid,
+    Executable,
+}
+
+impl PermFilter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PermFilter::WorldWritable => "world-writable",
+            PermFilter::Suid => "suid",
+            PermFilter::Executable => "executable",
+        }
+    }
+
+    /// Bits of which an entry has to have at least one set to match.
+    pub fn bits(&self) -> u32 {
+        match self {
+            PermFilter::WorldWritable => 0o002,
+            PermFilter::Suid => 0o4000,
+            PermFilter::Executable => 0o111,
+        }
+    }
+
+    /// Whether the entry with metadata `md` matches. Symlinks never do as their
+    /// permissions aren't used and executable matches only regular files.
+    pub fn matches(&self, md: &fs::Metadata) -> bool {
+        let file_type = md.file_type();
+        if file_type.is_symlink() || (*self == PermFilter::Executable && !file_type.is_file()) {
+            return false;
+        }
+        md.permissions().mode() & self.bits() != 0
+    }
+}
+
+impl FromStr for PermFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "world-writable" => Ok(PermFilter::WorldWritable),
+            "suid" => Ok(PermFilter::Suid),
+            "executable" => Ok(PermFilter::Executable),
+            _ => Err(anyhow!(
+                "invalid permission filter `{}`, expected world-writable, suid or executable",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PermFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn matches_extension(path: &Path, extensions: &[String], case_sensitive: bool) -> bool {
+    let ext = match path.extension() {
+        Some(ext) => ext.to_string_lossy(),
+        None => return false,
+    };
+    extensions.iter().any(|e| {
+        if case_sensitive {
+            *e == ext
+        } else {
+            e.to_lowercase() == ext.to_lowercase()
+        }
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Matches entries modified within `range`.
+pub struct DateFilter {
+    range: Range<DateTime<Utc>>,
+    /// The range as entered by the user.
+    desc: String,
+}
+
+impl DateFilter {
+    /// The range as entered by the user, accepted by `parse_date_filter`.
+    pub fn spec(&self) -> &str {
+        &self.desc
+    }
+
+    pub fn matches(&self, time: DateTime<Utc>) -> bool {
+        self.range.contains(&time)
+    }
+}
+
+pub fn parse_date_filter(s: &str) -> Result<DateFilter> {
+    Ok(DateFilter {
+        range: util::parse_date_range(s, util::today())?,
+        desc: s.trim().to_string(),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+}
+
+impl SizeOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SizeOp::Gt => ">",
+            SizeOp::Ge => ">=",
+            SizeOp::Lt => "<",
+            SizeOp::Le => "<=",
+            SizeOp::Eq => "=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A set of size bounds that all have to be satisfied, like `>10MB <100MB`.
+pub struct SizeFilter {
+    bounds: Vec<(SizeOp, u64)>,
+}
+
+impl SizeFilter {
+    /// Exact representation of the bounds accepted by `parse_size_filter`.
+    pub fn spec(&self) -> String {
+        let bounds: Vec<_> = self
+            .bounds
+            .iter()
+            .map(|(op, bound)| format!("{}{}", op.as_str(), bound))
+            .collect();
+        bounds.join(" ")
+    }
+
+    pub fn matches(&self, size: u64) -> bool {
+        self.bounds.iter().all(|(op, bound)| match op {
+            SizeOp::Gt => size > *bound,
+            SizeOp::Ge => size >= *bound,
+            SizeOp::Lt => size < *bound,
+            SizeOp::Le => size <= *bound,
+            SizeOp::Eq => size == *bound,
+        })
+    }
+}
+
+impl fmt::Display for SizeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds: Vec<_> = self
+            .bounds
+            .iter()
+            .map(|(op, bound)| format!("{}{}", op.as_str(), util::conv_b(*bound)))
+            .collect();
+        f.write_str(&bounds.join(" "))
+    }
+}
+
+/// Parses a whitespace separated list of size bounds. Each bound is an operator
+/// (`>`, `<`, `>=`, `<=`, `=`) followed by a size with an optional `K`, `M`, `G`
+/// or `T` suffix, e.g. `>=1.5M <2G`.
+pub fn parse_size_filter(s: &str) -> Result<SizeFilter> {
+    let mut bounds = vec![];
+    for bound in s.split_whitespace() {
+        let (op, size) = if let Some(size) = bound.strip_prefix(">=") {
+            (SizeOp::Ge, size)
+        } else if let Some(size) = bound.strip_prefix("<=") {
+            (SizeOp::Le, size)
+        } else if let Some(size) = bound.strip_prefix('>') {
+            (SizeOp::Gt, size)
+        } else if let Some(size) = bound.strip_prefix('<') {
+            (SizeOp::Lt, size)
+        } else if let Some(size) = bound.strip_prefix('=') {
+            (SizeOp::Eq, size)
+        } else {
+            return Err(anyhow!("missing operator in `{}`", bound));
+        };
+        bounds.push((op, util::parse_size(size)?));
+    }
+
+    if bounds.is_empty() {
+        return Err(anyhow!("empty size filter"));
+    }
+
+    Ok(SizeFilter { bounds })
+}
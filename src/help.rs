@@ -0,0 +1,74 @@
+//! Key bindings of the main view shown with `?`.
+
+#[rustfmt::skip]
+/// Keys and what they do. Notes in brackets explain bindings that differ from
+/// the usual ones because the key is taken or terminals can't send it.
+pub const KEYS: &[(&str, &str)] = &[
+    ("Up/Down", "move the highlight"),
+    ("Left, Ctrl-Left", "go to the parent directory"),
+    ("Right/Enter, Ctrl-Right", "enter the directory or open the file"),
+    ("Alt-Enter", "pick the program the file is opened with"),
+    ("g/Home, G/End", "first and last entry"),
+    ("PageUp/PageDown", "move the highlight by a page"),
+    ("J/K", "scroll the preview"),
+    ("Esc", "restore the preview or clear the filters"),
+    ("q", "quit"),
+    (":", "command prompt"),
+    ("?", "this list"),
+    ("s, r, d", "cycle the sort mode, reverse it, directories first"),
+    ("e", "extension filter"),
+    ("Ctrl-R", "regex filter prompt"),
+    ("Ctrl-I", "toggle case sensitive filtering in the prompts [arrives as Tab]"),
+    ("Space", "select the entry and move down"),
+    ("Ctrl-A", "select all visible entries"),
+    ("Tab", "invert the selection [Ctrl-I arrives as Tab]"),
+    ("c, x, v", "copy, cut and paste the selected or highlighted entries"),
+    ("Delete", "delete the selected or highlighted entries"),
+    ("R", "rename"),
+    ("n", "new file or directory"),
+    ("Alt-D", "duplicate the file under a new name [Ctrl-D compares files]"),
+    ("L", "create a symlink [Ctrl-L reloads the directory]"),
+    ("Ctrl-T", "archive the selected or highlighted entries"),
+    ("Ctrl-P, M", "edit permissions"),
+    ("F", "fix dangerous permissions of the filtered entries"),
+    ("E", "export paths to a file"),
+    ("i", "entry properties"),
+    ("1-5", "toggle a color tag"),
+    ("f", "toggle a favorite"),
+    ("Ctrl-F", "favorites"),
+    ("W", "toggle a workspace root [Ctrl-M arrives as Enter]"),
+    ("Alt-1..Alt-9", "open a workspace root"),
+    ("Alt-E", "recently modified files [Ctrl-R is the regex prompt]"),
+    ("H", "recently visited directories [Ctrl-R is the regex prompt]"),
+    ("Ctrl-G", "jump to a directory"),
+    ("Ctrl-L", "reload the directory"),
+    ("Ctrl-C", "copy the path to the clipboard"),
+    ("Alt-C", "copy the directory path [for Ctrl-Shift-C]"),
+    ("Alt-R", "copy the path relative to the startup directory [for Ctrl-Shift-R]"),
+    ("Ctrl-Alt-C", "copy the directory tree"),
+    ("Ctrl-Alt-T", "show the directory tree"),
+    ("y", "copy the command output shown in the preview"),
+    ("p", "open the file in $PAGER"),
+    ("P", "cycle the preview layout [p opens the pager]"),
+    ("w, #", "wrap preview lines, show line numbers"),
+    ("Ctrl-E", "edit a small text file in the preview"),
+    ("Ctrl-O", "open the directory in the file manager"),
+    ("O", "open the selected files"),
+    ("Ctrl-N", "start trav in a new window [for Ctrl-Shift-N]"),
+    ("S", "split view"),
+    ("Ctrl-D", "compare the copied file with the highlighted one"),
+    ("D", "compare two files in the external diff tool"),
+    ("Ctrl-U", "disk usage map"),
+    ("Ctrl-X", "extension statistics [Ctrl-S saves the session]"),
+    ("Ctrl-S", "save the session"),
+    ("Ctrl-W", "resize the panels"),
+    ("Ctrl-Z", "suspend"),
+];
+
+/// The key bindings as aligned lines of text.
+pub fn text() -> String {
+    let width = KEYS.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0);
+    KEYS.iter()
+        .map(|(keys, action)| format!("{:width$}  {}\n", keys, action, width = width))
+        .collect()
+}
@@ -0,0 +1,91 @@
+//! History of commands entered in the command prompt.
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::util;
+
+const HISTORY_FILE: &str = "history";
+/// Number of commands kept, older ones are dropped first.
+pub const MAX_COMMAND_HISTORY: usize = 100;
+
+pub fn path(config_dir: Option<&Path>) -> Result<PathBuf> {
+    config_dir
+        .map(|dir| dir.join(HISTORY_FILE))
+        .ok_or_else(|| anyhow!("failed to determine the config directory"))
+}
+
+/// Reads the saved commands, oldest first. A missing file is an empty history.
+pub fn load(config_dir: Option<&Path>) -> Vec<String> {
+    let content = path(config_dir)
+        .and_then(|path| Ok(fs::read_to_string(path)?))
+        .unwrap_or_default();
+    let mut history: Vec<_> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect();
+    truncate(&mut history);
+    history
+}
+
+pub fn save(history: &[String], config_dir: Option<&Path>) -> Result<()> {
+    let path = path(config_dir)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content = history.join("\n");
+    content.push('\n');
+    util::write_atomic(&path, content.as_bytes())
+}
+
+/// Appends `cmd` unless it repeats the last command.
+pub fn push(history: &mut Vec<String>, cmd: &str) {
+    if cmd.trim().is_empty() || history.last().map(String::as_str) == Some(cmd) {
+        return;
+    }
+    history.push(cmd.to_string());
+    truncate(history);
+}
+
+fn truncate(history: &mut Vec<String>) {
+    if history.len() > MAX_COMMAND_HISTORY {
+        history.drain(..history.len() - MAX_COMMAND_HISTORY);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Position in the history while it's browsed in the prompt.
+pub struct HistoryCursor {
+    idx: usize,
+    /// Input typed before browsing started, restored past the newest command.
+    draft: String,
+}
+
+impl HistoryCursor {
+    /// Starts browsing `history` from its end.
+    pub fn new(history: &[String], draft: &str) -> HistoryCursor {
+        HistoryCursor {
+            idx: history.len(),
+            draft: draft.to_string(),
+        }
+    }
+
+    /// Moves to the previous, older command.
+    pub fn previous<'a>(&mut self, history: &'a [String]) -> Option<&'a str> {
+        if self.idx == 0 {
+            return None;
+        }
+        self.idx -= 1;
+        history.get(self.idx).map(String::as_str)
+    }
+
+    /// Moves to the next, newer command or the draft after the newest.
+    pub fn next<'a>(&'a mut self, history: &'a [String]) -> Option<&'a str> {
+        if self.idx >= history.len() {
+            return None;
+        }
+        self.idx += 1;
+        Some(history.get(self.idx).unwrap_or(&self.draft))
+    }
+}
@@ -0,0 +1,70 @@
+//! Nerd Fonts glyphs shown in front of entry names if `nerd_fonts` is set.
+use std::ffi::OsStr;
+use std::fs::FileType;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy)]
+/// Icons of entries by their type and, for files, lowercase extension.
+pub struct IconMap {
+    pub dir: char,
+    pub file: char,
+    /// Anything else, e.g. a broken symlink or a socket.
+    pub other: char,
+    extensions: &'static [(&'static str, char)],
+}
+
+/// Glyphs from the Nerd Fonts patched fonts.
+pub const NERD_FONTS: IconMap = IconMap {
+    dir: '\u{E5FF}',
+    file: '\u{F15B}',
+    other: '\u{F481}',
+    extensions: &[
+        ("7z", '\u{F410}'),
+        ("c", '\u{E61E}'),
+        ("cpp", '\u{E61D}'),
+        ("css", '\u{E749}'),
+        ("gif", '\u{F1C5}'),
+        ("go", '\u{E626}'),
+        ("gz", '\u{F410}'),
+        ("h", '\u{F0FD}'),
+        ("html", '\u{E736}'),
+        ("jpeg", '\u{F1C5}'),
+        ("jpg", '\u{F1C5}'),
+        ("js", '\u{E74E}'),
+        ("json", '\u{E60B}'),
+        ("lock", '\u{F023}'),
+        ("md", '\u{E609}'),
+        ("pdf", '\u{F1C1}'),
+        ("png", '\u{F1C5}'),
+        ("py", '\u{E606}'),
+        ("rs", '\u{E7A8}'),
+        ("sh", '\u{F489}'),
+        ("tar", '\u{F410}'),
+        ("toml", '\u{F1F6}'),
+        ("txt", '\u{F15C}'),
+        ("zip", '\u{F410}'),
+    ],
+};
+
+impl IconMap {
+    /// Icon of the entry named `name`, `file_type` should follow symlinks.
+    pub fn icon(&self, name: &OsStr, file_type: FileType) -> char {
+        if file_type.is_dir() {
+            self.dir
+        } else if file_type.is_file() {
+            Path::new(name)
+                .extension()
+                .and_then(|ext| self.extension_icon(&ext.to_string_lossy().to_lowercase()))
+                .unwrap_or(self.file)
+        } else {
+            self.other
+        }
+    }
+
+    fn extension_icon(&self, ext: &str) -> Option<char> {
+        self.extensions
+            .binary_search_by(|(e, _)| (*e).cmp(ext))
+            .ok()
+            .map(|i| self.extensions[i].1)
+    }
+}
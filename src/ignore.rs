@@ -0,0 +1,92 @@
+//! Patterns of paths skipped by searches of a subtree read from `.travignore`.
+use std::fs;
+use std::path::Path;
+
+/// Name of the file listing ignored paths in the searched directory.
+pub const IGNORE_FILE: &str = ".travignore";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    glob: String,
+    /// Matched against the whole relative path rather than the file name.
+    anchored: bool,
+    dir_only: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Gitignore like patterns, one per line with `#` starting a comment. `*` and
+/// `?` don't match `/`, `**` does. A pattern containing a `/` is matched
+/// against the path relative to the root, otherwise against the file name.
+/// A trailing `/` only matches directories.
+pub struct IgnoreRules {
+    patterns: Vec<Pattern>,
+}
+
+impl IgnoreRules {
+    /// Reads the patterns from `.travignore` in `root` if there is one.
+    pub fn load(root: &Path) -> IgnoreRules {
+        fs::read_to_string(root.join(IGNORE_FILE))
+            .map(|content| IgnoreRules::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(content: &str) -> IgnoreRules {
+        let patterns = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let dir_only = line.ends_with('/');
+                let line = line.trim_end_matches('/');
+                Pattern {
+                    anchored: line.contains('/'),
+                    glob: line.trim_start_matches('/').to_string(),
+                    dir_only,
+                }
+            })
+            .filter(|p| !p.glob.is_empty())
+            .collect();
+        IgnoreRules { patterns }
+    }
+
+    /// Checks whether `rel_path`, relative to the directory the rules were
+    /// loaded from, is ignored.
+    pub fn is_ignored(&self, rel_path: &Path, is_dir: bool) -> bool {
+        let path = rel_path.to_string_lossy();
+        let name = rel_path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        self.patterns.iter().any(|p| {
+            (is_dir || !p.dir_only)
+                && glob_match(
+                    p.glob.as_bytes(),
+                    if p.anchored { &path } else { &name }.as_bytes(),
+                )
+        })
+    }
+}
+
+/// Matches `text` against `glob` where `*` and `?` don't match `/` and `**`
+/// does.
+pub fn glob_match(glob: &[u8], text: &[u8]) -> bool {
+    match glob {
+        [] => text.is_empty(),
+        // `**/` matches whole leading directories only
+        [b'*', b'*', b'/', rest @ ..] => (0..=text.len())
+            .filter(|&i| i == 0 || text[i - 1] == b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => match text {
+            [c, text @ ..] if *c != b'/' => glob_match(rest, text),
+            _ => false,
+        },
+        [c, rest @ ..] => match text {
+            [t, text @ ..] if t == c => glob_match(rest, text),
+            _ => false,
+        },
+    }
+}
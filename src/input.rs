@@ -0,0 +1,123 @@
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::util;
+
+#[derive(Debug, Default)]
+/// A single line text input with a cursor.
+pub struct InputWidget {
+    buffer: String,
+    /// Cursor position counted in characters.
+    cursor: usize,
+    /// Expand a leading `~` or `~user` once it's followed by `/`.
+    expand_tilde: bool,
+}
+
+impl InputWidget {
+    pub fn new() -> InputWidget {
+        InputWidget::default()
+    }
+
+    pub fn with_text<S: Into<String>>(text: S) -> InputWidget {
+        let buffer = text.into();
+        let cursor = buffer.chars().count();
+        InputWidget {
+            buffer,
+            cursor,
+            expand_tilde: false,
+        }
+    }
+
+    /// Makes the input replace `~/` and `~user/` typed at its start with the
+    /// home directory, `\~` is left alone.
+    pub fn expanding_tilde(mut self) -> InputWidget {
+        self.expand_tilde = true;
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.cursor = 0;
+    }
+
+    fn byte_idx(&self, cursor: usize) -> usize {
+        self.buffer
+            .char_indices()
+            .nth(cursor)
+            .map(|(i, _)| i)
+            .unwrap_or_else(|| self.buffer.len())
+    }
+
+    /// Expands `~user` if it spans the buffer up to the `/` at byte `slash`.
+    fn expand_home(&mut self, slash: usize) {
+        let user = match self.buffer[..slash].strip_prefix('~') {
+            Some(user) if !user.contains('/') => user,
+            _ => return,
+        };
+        let home = util::home_dir(Some(user).filter(|user| !user.is_empty()));
+        if let Some(home) = home {
+            let home = home.trim_end_matches('/');
+            self.cursor = self.cursor + home.chars().count() - self.buffer[..slash].chars().count();
+            self.buffer.replace_range(..slash, home);
+        }
+    }
+
+    /// Updates the input with `key`. Returns `true` if the key was consumed.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        match key {
+            Key::Char(c) if c != '\n' && c != '\t' => {
+                let idx = self.byte_idx(self.cursor);
+                self.buffer.insert(idx, c);
+                self.cursor += 1;
+                if c == '/' && self.expand_tilde {
+                    self.expand_home(idx);
+                }
+            }
+            Key::Backspace => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    let idx = self.byte_idx(self.cursor);
+                    self.buffer.remove(idx);
+                }
+            }
+            Key::Delete => {
+                if self.cursor < self.buffer.chars().count() {
+                    let idx = self.byte_idx(self.cursor);
+                    self.buffer.remove(idx);
+                }
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.buffer.chars().count()),
+            Key::Home | Key::Ctrl('a') => self.cursor = 0,
+            Key::End | Key::Ctrl('e') => self.cursor = self.buffer.chars().count(),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn render<B: Backend, S: AsRef<str>>(&self, title: S, frame: &mut Frame<B>, rect: Rect) {
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            title.as_ref().to_string(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+        let paragraph = Paragraph::new(self.buffer.as_str())
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+
+        frame.render_widget(paragraph, rect);
+        frame.set_cursor(rect.x + 1 + self.cursor as u16, rect.y + 1);
+    }
+}
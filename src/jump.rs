@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::util;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Candidates offered in the jump prompt cycled through with `Tab`.
+pub struct Completion {
+    pub candidates: Vec<String>,
+    idx: Option<usize>,
+}
+
+impl Completion {
+    /// Completes `query` with bookmark names and visited directories, most
+    /// recently visited first.
+    pub fn new(
+        query: &str,
+        history: &[PathBuf],
+        bookmarks: &BTreeMap<String, PathBuf>,
+    ) -> Completion {
+        let expanded = expand_tilde(query);
+        let mut candidates: Vec<String> = bookmarks
+            .keys()
+            .filter(|name| name.starts_with(query))
+            .cloned()
+            .collect();
+        for path in history.iter().rev() {
+            let path = path.to_string_lossy().to_string();
+            if (path.starts_with(&expanded) || path.contains(query)) && !candidates.contains(&path)
+            {
+                candidates.push(path);
+            }
+        }
+
+        Completion {
+            candidates,
+            idx: None,
+        }
+    }
+
+    /// Advances to the next candidate wrapping around at the end.
+    pub fn cycle(&mut self) -> Option<&str> {
+        if self.candidates.is_empty() {
+            return None;
+        }
+        let idx = self
+            .idx
+            .map(|i| (i + 1) % self.candidates.len())
+            .unwrap_or(0);
+        self.idx = Some(idx);
+        Some(&self.candidates[idx])
+    }
+
+    /// Position of the current candidate as `(index, total)` counted from 1.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        self.idx.map(|i| (i + 1, self.candidates.len()))
+    }
+}
+
+/// Replaces a leading `~` with `$HOME` and `~user` with the home directory of
+/// `user`. An escaped `\~` stands for a literal `~`.
+pub fn expand_tilde(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix("\\~") {
+        return format!("~{}", rest);
+    }
+    let rest = match path.strip_prefix('~') {
+        Some(rest) => rest,
+        None => return path.to_string(),
+    };
+    let (user, rest) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let user = Some(user).filter(|user| !user.is_empty());
+    match util::home_dir(user) {
+        Some(home) => format!("{}{}", home, rest),
+        None => path.to_string(),
+    }
+}
+
+/// Resolves the text entered in the jump prompt to an absolute path. Bookmark
+/// names take precedence over relative paths.
+pub fn resolve(input: &str, cwd: &Path, bookmarks: &BTreeMap<String, PathBuf>) -> PathBuf {
+    let input = input.trim();
+    if let Some(path) = bookmarks.get(input) {
+        return PathBuf::from(expand_tilde(&path.to_string_lossy()));
+    }
+    cwd.join(expand_tilde(input))
+}
@@ -1,7 +1,46 @@
 pub mod app;
+pub mod archive;
+pub mod children;
+pub mod chmod;
+pub mod cli;
+pub mod command;
+pub mod config;
+pub mod count;
+pub mod diff;
+pub mod du;
+pub mod duplicates;
+pub mod editor;
+pub mod encoding;
 pub mod entry;
 pub mod events;
+pub mod favorites;
+pub mod fileops;
+pub mod filter;
+pub mod help;
+pub mod history;
+pub mod icons;
+pub mod ignore;
+pub mod input;
+pub mod jump;
+pub mod log;
+pub mod output;
+pub mod permfix;
+pub mod popup;
+pub mod properties;
+pub mod recent;
+pub mod scan;
+pub mod scrollbar;
+pub mod session;
+pub mod sort;
+pub mod split;
+pub mod stats;
+pub mod tags;
+pub mod template;
+pub mod tree;
 pub mod util;
+pub mod visited;
+pub mod watch;
+pub mod workspaces;
 
 use std::io::Stdout;
 use termion::{input::MouseTerminal, raw::RawTerminal, screen::AlternateScreen};
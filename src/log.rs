@@ -0,0 +1,118 @@
+//! Logging to a file enabled with `--log-file`, messages are dropped otherwise
+//! so that nothing is written over the TUI.
+use anyhow::{anyhow, Error, Result};
+use chrono::{SecondsFormat, Utc};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+/// Verbosity of a message, a logger set to a level writes it and all the
+/// levels before it.
+pub enum Level {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(anyhow!("unknown log level `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Logger {
+    file: File,
+    level: Level,
+}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+/// Appends messages of at most `level` to the file at `path`.
+pub fn init(path: &Path, level: Level) -> Result<()> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow!("can't open log file {}: {}", path.display(), e))?;
+    *LOGGER.lock().unwrap_or_else(|e| e.into_inner()) = Some(Logger { file, level });
+    Ok(())
+}
+
+/// Writes a message, used through the `error!`, `warn!`, `info!`, `debug!`
+/// and `trace!` macros.
+pub fn write(level: Level, args: fmt::Arguments) {
+    let mut logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(logger) = logger.as_mut().filter(|logger| level <= logger.level) {
+        let _ = writeln!(
+            logger.file,
+            "{} {:5} {}",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            level.as_str().to_uppercase(),
+            args
+        );
+    }
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Error, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Warn, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Info, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Debug, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Trace, format_args!($($arg)*))
+    };
+}
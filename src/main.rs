@@ -1,20 +1,140 @@
-use anyhow::Result;
-use std::path::Path;
-use trav::{app::TravApp, util::get_terminal};
+use anyhow::{anyhow, Result};
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{env, io, process};
+use trav::{
+    app::{AppOptions, TravApp},
+    cli,
+    config::Config,
+    entry::list_sorted,
+    info, log, output,
+    session::Session,
+    util, warn,
+};
+
+/// How long to wait for a path piped to stdin, e.g. `echo ~/projects | trav`.
+const STDIN_PATH_TIMEOUT: Duration = Duration::from_millis(100);
+
+fn print_listing(args: &cli::Args) -> Result<()> {
+    let path = match &args.path {
+        Some(path) => env::current_dir()?.join(path),
+        None => env::current_dir()?,
+    };
+    let config = Config::load()?;
+    let entries = list_sorted(
+        &path,
+        config.show_hidden,
+        config.sort_mode,
+        config.sort_options(),
+    )?;
+
+    let stdout = io::stdout();
+    match output::write_listing(&mut stdout.lock(), &entries, args.list_format) {
+        // the reader went away, e.g. `trav --list | head`
+        Err(e)
+            if e.downcast_ref::<io::Error>().map(io::Error::kind)
+                == Some(io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        res => res,
+    }
+}
+
+/// Runs trav again with the same arguments in a `tmux display-popup` and
+/// exits with its status. Picking files from a popup is handled by
+/// `contrib/trav-tmux.sh`.
+fn run_in_tmux_popup() -> Result<()> {
+    if env::var_os("TMUX").is_none_or(|tmux| tmux.is_empty()) {
+        return Err(anyhow!("--tmux-popup requires a running tmux session"));
+    }
+    let status = process::Command::new("tmux")
+        .arg("display-popup")
+        .arg("-E")
+        .arg("-d")
+        .arg(env::current_dir()?)
+        .arg(env::current_exe()?)
+        .args(env::args().skip(1).filter(|arg| arg != "--tmux-popup"))
+        .status()
+        .map_err(|e| anyhow!("failed to run tmux: {}", e))?;
+    process::exit(status.code().unwrap_or(1));
+}
 
 fn main() -> Result<()> {
-    let mut terminal = get_terminal()?;
-    let mut app = TravApp::new::<&Path>(None)?;
+    let args = cli::Args::from_env()?;
+    if args.help {
+        print!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if args.version {
+        println!("{}", cli::version());
+        return Ok(());
+    }
+    if let Some(log_file) = &args.log_file {
+        log::init(log_file, args.log_level)?;
+    }
+    if args.list {
+        return print_listing(&args);
+    }
+    if args.tmux_popup {
+        return run_in_tmux_popup();
+    }
+
+    let path = args
+        .path
+        .clone()
+        .or_else(|| util::read_stdin_line(STDIN_PATH_TIMEOUT).map(PathBuf::from));
+
+    let picker_output = if args.select_output {
+        util::redirect_stdout_to_tty()?
+    } else {
+        None
+    };
+
+    let mut config = Config::load()?;
+    if let Some(diff_tool) = args.diff_tool {
+        config.diff_tool = Some(diff_tool);
+    }
+
+    let state_dir = config.state_dir.clone();
+    let mut app = TravApp::with_config(path, config)?;
+    info!("started in {}", app.current_dir().display());
+    app.set_options(AppOptions {
+        select_output: args.select_output,
+        readonly: args.readonly,
+        force_root: args.force_root,
+        max_depth: args.max_depth,
+        watch: args.watch,
+    });
+    if util::is_root() {
+        warn!("running as root");
+    }
+
+    if args.restore_session {
+        if let Err(e) =
+            Session::load(state_dir.as_deref()).and_then(|session| app.restore_session(session))
+        {
+            app.set_err(format!("failed to restore session: {}", e));
+        }
+    }
 
-    loop {
-        terminal.draw(|mut f| {
-            app.draw_frame(&mut f);
-        })?;
+    if let Some(name) = &args.select {
+        if !app.select_by_name(OsStr::new(name))? {
+            app.set_err(format!("no entry named `{}`", name));
+        }
+    }
 
-        app.handle_event()?;
+    app.run()?;
 
-        if app.exit {
-            break;
+    if args.select_output {
+        let mut out: Box<dyn Write> = match picker_output {
+            Some(file) => Box::new(file),
+            None => Box::new(io::stdout()),
+        };
+        for path in app.output() {
+            writeln!(out, "{}", path.display())?;
         }
     }
 
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use chrono::SecondsFormat;
+use std::io::Write;
+use std::os::unix::fs::MetadataExt;
+
+use crate::entry::DirEntry;
+use crate::properties::{format_mode, group_name, user_name};
+use crate::util;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Output format of `--list`.
+pub enum ListFormat {
+    /// Newline separated absolute paths.
+    #[default]
+    Lines,
+    /// Null separated absolute paths.
+    Null,
+    /// A pretty printed JSON array of objects describing each entry.
+    Json,
+    /// Like `Json` but on a single line.
+    CompactJson,
+}
+
+/// Quotes and escapes `s` as a JSON string.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Name of the entry type as used in machine readable output.
+pub fn entry_type(entry: &DirEntry) -> &'static str {
+    match entry.file_type() {
+        Ok(ft) if ft.is_dir() => "dir",
+        Ok(ft) if ft.is_symlink() => "symlink",
+        Ok(ft) if ft.is_file() => "file",
+        _ => "other",
+    }
+}
+
+/// Fields of the JSON object describing `entry` with values already encoded.
+fn json_fields(entry: &DirEntry) -> Vec<(&'static str, String)> {
+    let md = entry.metadata().ok();
+    let null = || "null".to_string();
+    let size = md.as_ref().map(|md| md.len().to_string());
+    let modified = md.as_ref().and_then(|md| md.modified().ok()).map(|t| {
+        json_string(&util::system_time_to_date_time(t).to_rfc3339_opts(SecondsFormat::Secs, true))
+    });
+
+    vec![
+        ("name", json_string(&entry.file_name().to_string_lossy())),
+        ("path", json_string(&entry.path().to_string_lossy())),
+        ("type", json_string(entry_type(entry))),
+        ("size", size.unwrap_or_else(null)),
+        ("modified", modified.unwrap_or_else(null)),
+        (
+            "permissions",
+            md.as_ref()
+                .map(|md| json_string(&format_mode(md.mode())))
+                .unwrap_or_else(null),
+        ),
+        (
+            "owner",
+            md.as_ref()
+                .map(|md| json_string(&user_name(md.uid())))
+                .unwrap_or_else(null),
+        ),
+        (
+            "group",
+            md.as_ref()
+                .map(|md| json_string(&group_name(md.gid())))
+                .unwrap_or_else(null),
+        ),
+    ]
+}
+
+fn json_entry(entry: &DirEntry, pretty: bool) -> String {
+    let fields: Vec<_> = json_fields(entry)
+        .into_iter()
+        .map(|(key, value)| format!("{}: {}", json_string(key), value))
+        .collect();
+    if pretty {
+        format!("  {{\n    {}\n  }}", fields.join(",\n    "))
+    } else {
+        format!("{{{}}}", fields.join(", "))
+    }
+}
+
+pub fn write_listing<W: Write>(
+    out: &mut W,
+    entries: &[DirEntry],
+    format: ListFormat,
+) -> Result<()> {
+    match format {
+        ListFormat::Lines | ListFormat::Null => {
+            let sep = if format == ListFormat::Null {
+                b'\0'
+            } else {
+                b'\n'
+            };
+            for entry in entries {
+                out.write_all(entry.path().to_string_lossy().as_bytes())?;
+                out.write_all(&[sep])?;
+            }
+        }
+        ListFormat::Json | ListFormat::CompactJson => {
+            let pretty = format == ListFormat::Json;
+            let items: Vec<_> = entries.iter().map(|e| json_entry(e, pretty)).collect();
+            if pretty && !items.is_empty() {
+                writeln!(out, "[\n{}\n]", items.join(",\n"))?;
+            } else {
+                writeln!(out, "[{}]", items.join(", "))?;
+            }
+        }
+    }
+    out.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Removing dangerous permission bits from several entries at once.
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use crate::util::list::StatefulList;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Permissions of an entry before and after the fix, without the file type bits.
+pub struct ModeChange {
+    pub path: PathBuf,
+    pub old: u32,
+    pub new: u32,
+}
+
+#[derive(Debug)]
+/// Pending removal of `bits` from the permissions of the listed entries.
+pub struct PermFix {
+    pub bits: u32,
+    pub changes: StatefulList<ModeChange>,
+}
+
+impl PermFix {
+    /// Plans clearing `bits` on `paths`, entries that don't have any of them set
+    /// or are symlinks are skipped.
+    pub fn new(paths: &[PathBuf], bits: u32) -> Result<PermFix> {
+        let mut changes = vec![];
+        for path in paths {
+            let md = fs::symlink_metadata(path)?;
+            if md.file_type().is_symlink() {
+                continue;
+            }
+            let old = md.permissions().mode() & 0o7777;
+            if old & bits != 0 {
+                changes.push(ModeChange {
+                    path: path.clone(),
+                    old,
+                    new: old & !bits,
+                });
+            }
+        }
+        if changes.is_empty() {
+            return Err(anyhow!("no permissions to fix"));
+        }
+        let mut changes = StatefulList::with_items(changes);
+        changes.select(Some(0));
+        Ok(PermFix { bits, changes })
+    }
+
+    /// Applies all changes stopping at the first failure.
+    pub fn apply(&self) -> Result<()> {
+        for change in &self.changes.items {
+            fs::set_permissions(&change.path, fs::Permissions::from_mode(change.new))
+                .map_err(|e| anyhow!("{}: {}", change.path.display(), e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Clear, List, ListItem},
+    Frame,
+};
+
+use crate::util::list::StatefulList;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupKind {
+    /// Picks a command to open the highlighted file with.
+    Openers,
+    /// Actions for the highlighted entry opened with a right click.
+    ContextMenu,
+    /// Asks whether to delete the targeted entries.
+    ConfirmDelete,
+    /// Asks again before deleting when running as root.
+    ConfirmDeleteAsRoot,
+    /// Asks whether to open many files at once.
+    ConfirmOpen,
+    /// Read only details about the highlighted entry.
+    Properties,
+    /// Pinned paths to navigate to.
+    Favorites,
+    /// Picks the template filling a new file.
+    Template,
+}
+
+#[derive(Debug)]
+/// A small list of choices rendered over the main view.
+pub struct Popup {
+    pub kind: PopupKind,
+    pub title: String,
+    pub items: StatefulList<String>,
+    /// Top left corner of the popup, centered in the frame if not set.
+    pub anchor: Option<(u16, u16)>,
+}
+
+impl Popup {
+    pub fn new<S: Into<String>>(kind: PopupKind, title: S, items: Vec<String>) -> Popup {
+        let mut items = StatefulList::with_items(items);
+        items.select(Some(0));
+        Popup {
+            kind,
+            title: title.into(),
+            items,
+            anchor: None,
+        }
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.items.current().map(String::as_str)
+    }
+
+    /// Area of the popup within `frame` that doesn't overflow its edges.
+    fn area(&self, frame: Rect) -> Rect {
+        let content_width = self
+            .items
+            .items
+            .iter()
+            .map(|item| item.chars().count())
+            .chain(std::iter::once(self.title.chars().count()))
+            .max()
+            .unwrap_or_default() as u16;
+        let width = (content_width + 4).min(frame.width);
+        let height = (self.items.items.len() as u16 + 2).min(frame.height);
+
+        let (x, y) = match self.anchor {
+            Some((x, y)) => (x, y),
+            None => (
+                frame.x + (frame.width - width) / 2,
+                frame.y + (frame.height - height) / 2,
+            ),
+        };
+
+        Rect {
+            x: x.min(frame.x + frame.width - width),
+            y: y.min(frame.y + frame.height - height),
+            width,
+            height,
+        }
+    }
+
+    pub fn render<B: Backend>(&mut self, frame: &mut Frame<B>) {
+        let area = self.area(frame.size());
+        let items: Vec<_> = self
+            .items
+            .items
+            .iter()
+            .map(|item| ListItem::new(item.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default().borders(Borders::ALL).title(Span::styled(
+                    self.title.as_str(),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .bg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            );
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.items.state);
+    }
+}
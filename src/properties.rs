@@ -0,0 +1,308 @@
+use anyhow::Result;
+use chrono::{offset::Local, TimeZone};
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use crate::util;
+
+/// Number of bytes inspected when guessing whether a file is text.
+const SNIFF_LEN: usize = 512;
+
+/// All available metadata of the entry at `path` as `name: value` lines.
+pub fn properties(path: &Path) -> Result<Vec<String>> {
+    let md = fs::symlink_metadata(path)?;
+    let mut lines = vec![format!("path: {}", path.display())];
+
+    if md.file_type().is_symlink() {
+        let target = fs::read_link(path)?;
+        lines.push(format!("target: {}", target.display()));
+    }
+    lines.push(format!("type: {}", file_type(&md)));
+    lines.push(format!("mime type: {}", mime_type(path, &md)));
+    lines.push(format!("size: {} ({} B)", util::conv_b(md.len()), md.len()));
+    lines.push(format!(
+        "permissions: {} ({:o})",
+        format_mode(md.permissions().mode()),
+        md.permissions().mode() & 0o7777
+    ));
+    lines.push(format!("owner: {} ({})", user_name(md.uid()), md.uid()));
+    lines.push(format!("group: {} ({})", group_name(md.gid()), md.gid()));
+    lines.push(format!("inode: {}", md.ino()));
+    lines.push(format!("device: {}", md.dev()));
+    lines.push(format!("links: {}", md.nlink()));
+    lines.push(format!("block size: {}", md.blksize()));
+    lines.push(format!("blocks: {}", md.blocks()));
+    lines.push(format!(
+        "accessed: {}",
+        format_time(md.atime(), md.atime_nsec())
+    ));
+    lines.push(format!(
+        "modified: {}",
+        format_time(md.mtime(), md.mtime_nsec())
+    ));
+    lines.push(format!(
+        "changed: {}",
+        format_time(md.ctime(), md.ctime_nsec())
+    ));
+    lines.push(format!(
+        "born: {}",
+        md.created()
+            .map(|t| util::system_time_to_date_time(t).with_timezone(&Local))
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|_| "unknown".to_string())
+    ));
+
+    Ok(lines)
+}
+
+/// Size, permissions, modification time and owner of the entry at `path`.
+pub fn summary(path: &Path) -> Result<Vec<String>> {
+    let md = fs::symlink_metadata(path)?;
+    Ok(vec![
+        format!("size: {}", util::conv_b(md.len())),
+        format!("permissions: {}", format_mode(md.permissions().mode())),
+        format!("modified: {}", format_time(md.mtime(), md.mtime_nsec())),
+        format!("owner: {}:{}", user_name(md.uid()), group_name(md.gid())),
+    ])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Why following a chain of symlinks stopped.
+pub enum ChainEnd {
+    /// The last target isn't a symlink.
+    Resolved,
+    /// The last target doesn't exist.
+    Broken,
+    /// The last target is a link already seen in the chain.
+    Cycle,
+    /// More links than the maximum depth were followed.
+    TooDeep,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkChain {
+    /// Targets read from each link in order, as stored in the links.
+    pub hops: Vec<PathBuf>,
+    pub end: ChainEnd,
+}
+
+/// Follows the symlink at `path` and the links it points to, at most
+/// `max_depth` of them.
+pub fn symlink_chain(path: &Path, max_depth: usize) -> io::Result<SymlinkChain> {
+    let mut seen = HashSet::new();
+    seen.insert(canonical_link(path));
+    let mut current = path.to_path_buf();
+    let mut hops = vec![];
+    let end = loop {
+        if hops.len() >= max_depth {
+            break ChainEnd::TooDeep;
+        }
+        let target = fs::read_link(&current)?;
+        // relative targets are resolved from the directory of the link
+        let next = match current.parent() {
+            Some(dir) => dir.join(&target),
+            None => target.clone(),
+        };
+        hops.push(target);
+        match fs::symlink_metadata(&next) {
+            Err(_) => break ChainEnd::Broken,
+            Ok(md) if md.file_type().is_symlink() => {
+                if !seen.insert(canonical_link(&next)) {
+                    break ChainEnd::Cycle;
+                }
+                current = next;
+            }
+            Ok(_) => break ChainEnd::Resolved,
+        }
+    };
+    Ok(SymlinkChain { hops, end })
+}
+
+/// Canonical path of the link at `path` itself rather than of its target.
+fn canonical_link(path: &Path) -> PathBuf {
+    match (path.parent().map(fs::canonicalize), path.file_name()) {
+        (Some(Ok(dir)), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn format_time(secs: i64, nsecs: i64) -> String {
+    Local
+        .timestamp(secs, nsecs as u32)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn file_type(md: &fs::Metadata) -> &'static str {
+    let file_type = md.file_type();
+    if file_type.is_symlink() {
+        "symlink"
+    } else if file_type.is_dir() {
+        "directory"
+    } else if file_type.is_file() {
+        "regular file"
+    } else if file_type.is_block_device() {
+        "block device"
+    } else if file_type.is_char_device() {
+        "character device"
+    } else if file_type.is_fifo() {
+        "fifo"
+    } else if file_type.is_socket() {
+        "socket"
+    } else {
+        "unknown"
+    }
+}
+
+/// Formats permission bits like `ls -l` does, e.g. `drwxr-xr-x`.
+pub fn format_mode(mode: u32) -> String {
+    let kind = match mode & libc::S_IFMT {
+        libc::S_IFDIR => 'd',
+        libc::S_IFLNK => 'l',
+        libc::S_IFBLK => 'b',
+        libc::S_IFCHR => 'c',
+        libc::S_IFIFO => 'p',
+        libc::S_IFSOCK => 's',
+        _ => '-',
+    };
+    let mut out = String::with_capacity(10);
+    out.push(kind);
+
+    // (read, write, execute, special bit, special char)
+    let triplets = [
+        (0o400, 0o200, 0o100, libc::S_ISUID, 's'),
+        (0o040, 0o020, 0o010, libc::S_ISGID, 's'),
+        (0o004, 0o002, 0o001, libc::S_ISVTX, 't'),
+    ];
+    for (r, w, x, special, c) in triplets.iter() {
+        out.push(if mode & r != 0 { 'r' } else { '-' });
+        out.push(if mode & w != 0 { 'w' } else { '-' });
+        out.push(match (mode & x != 0, mode & special != 0) {
+            (true, true) => *c,
+            (false, true) => c.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+
+    out
+}
+
+/// Name of the user with `uid` or the id itself if it's unknown.
+pub fn user_name(uid: u32) -> String {
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut res = std::ptr::null_mut();
+    // SAFETY: all pointers are valid for the duration of the call and the
+    // returned name points into `buf`.
+    unsafe {
+        libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut res);
+        if res.is_null() {
+            return uid.to_string();
+        }
+        CStr::from_ptr(pwd.pw_name).to_string_lossy().to_string()
+    }
+}
+
+/// Name of the group with `gid` or the id itself if it's unknown.
+pub fn group_name(gid: u32) -> String {
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut grp: libc::group = unsafe { std::mem::zeroed() };
+    let mut res = std::ptr::null_mut();
+    // SAFETY: all pointers are valid for the duration of the call and the
+    // returned name points into `buf`.
+    unsafe {
+        libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut res);
+        if res.is_null() {
+            return gid.to_string();
+        }
+        CStr::from_ptr(grp.gr_name).to_string_lossy().to_string()
+    }
+}
+
+/// Guesses the MIME type from the extension falling back to inspecting the
+/// beginning of the file.
+pub fn mime_type(path: &Path, md: &fs::Metadata) -> &'static str {
+    if md.file_type().is_symlink() {
+        return "inode/symlink";
+    } else if md.is_dir() {
+        return "inode/directory";
+    } else if !md.is_file() {
+        return "inode/special";
+    } else if md.len() == 0 {
+        return "inode/x-empty";
+    }
+
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let by_ext = match ext.as_str() {
+        "txt" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "rs" => "text/rust",
+        "toml" => "application/toml",
+        "json" => "application/json",
+        "yaml" | "yml" => "application/yaml",
+        "xml" => "application/xml",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "csv" => "text/csv",
+        "sh" => "application/x-sh",
+        "py" => "text/x-python",
+        "c" | "h" => "text/x-c",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "mkv" => "video/x-matroska",
+        _ => "",
+    };
+    if !by_ext.is_empty() {
+        return by_ext;
+    }
+
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    match fs::File::open(path).and_then(|f| f.take(SNIFF_LEN as u64).read_to_end(&mut head)) {
+        Ok(_) if is_text(&head) => "text/plain",
+        Ok(_) => "application/octet-stream",
+        Err(_) => "unknown",
+    }
+}
+
+/// Whether files of the `mime` type are readable in a pager.
+pub fn is_text_mime(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || matches!(
+            mime,
+            "application/json"
+                | "application/toml"
+                | "application/yaml"
+                | "application/xml"
+                | "application/x-sh"
+                | "inode/x-empty"
+        )
+}
+
+fn is_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        // the sniffed prefix may end in the middle of a multi byte character
+        Err(e) => e.error_len().is_none(),
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Maximum number of files listed in the recent files view.
+pub const MAX_RECENT_FILES: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+}
+
+/// Walks `root` breadth first up to `max_depth` levels deep and returns the most
+/// recently modified files, newest first. Symlinked directories aren't
+/// followed and unreadable directories are skipped.
+pub fn recent_files(root: &Path, max_depth: usize) -> Vec<RecentFile> {
+    let mut files = vec![];
+    let mut queue = VecDeque::new();
+    queue.push_back((root.to_path_buf(), 0));
+
+    while let Some((dir, depth)) = queue.pop_front() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(_) => continue,
+            };
+            if file_type.is_dir() {
+                if depth + 1 < max_depth {
+                    queue.push_back((entry.path(), depth + 1));
+                }
+            } else if let Ok(modified) = entry.metadata().and_then(|md| md.modified()) {
+                files.push(RecentFile {
+                    path: entry.path(),
+                    modified,
+                });
+            }
+        }
+    }
+
+    files.sort_by_key(|file| std::cmp::Reverse(file.modified));
+    files.truncate(MAX_RECENT_FILES);
+    files
+}
@@ -0,0 +1,259 @@
+//! Background searches of the current subtree listing matching paths.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::ignore::{glob_match, IgnoreRules};
+use crate::util::list::StatefulList;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// What a scan looks for.
+pub enum ScanKind {
+    /// Directories without any files below them.
+    EmptyDirs,
+    /// Symlinks pointing at paths that don't exist.
+    BrokenLinks,
+    /// Entries whose name matches a glob, or whose relative path does if the
+    /// glob contains a `/`.
+    Name(String),
+}
+
+impl ScanKind {
+    pub fn describe(&self) -> String {
+        match self {
+            ScanKind::EmptyDirs => "empty directories".to_string(),
+            ScanKind::BrokenLinks => "broken symlinks".to_string(),
+            ScanKind::Name(glob) => format!("entries matching `{}`", glob),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Message {
+    /// Number of directories scanned so far.
+    Scanned(usize),
+    Found(PathBuf),
+}
+
+/// Reports the topmost directories below `dir` that contain no files and
+/// returns whether `dir` itself contains none. Unreadable directories count
+/// as not empty.
+fn find_empty_dirs(
+    dir: &Path,
+    scanned: &mut usize,
+    tx: &mpsc::Sender<Message>,
+) -> Result<bool, mpsc::SendError<Message>> {
+    *scanned += 1;
+    tx.send(Message::Scanned(*scanned))?;
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(false),
+    };
+    let mut is_empty = true;
+    let mut empty_children = vec![];
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => {
+                is_empty = false;
+                continue;
+            }
+        };
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => {
+                if find_empty_dirs(&entry.path(), scanned, tx)? {
+                    empty_children.push(entry.path());
+                } else {
+                    is_empty = false;
+                }
+            }
+            _ => is_empty = false,
+        }
+    }
+
+    if !is_empty {
+        empty_children.sort();
+        for child in empty_children {
+            tx.send(Message::Found(child))?;
+        }
+    }
+    Ok(is_empty)
+}
+
+/// Reports symlinks below `root` whose target doesn't exist skipping paths
+/// ignored by `rules`. Symlinks to directories aren't followed.
+fn find_broken_links(
+    root: &Path,
+    rules: &IgnoreRules,
+    tx: &mpsc::Sender<Message>,
+) -> Result<(), mpsc::SendError<Message>> {
+    let mut scanned = 0;
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        scanned += 1;
+        tx.send(Message::Scanned(scanned))?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_type = match entry.file_type() {
+                Ok(t) => t,
+                Err(_) => continue,
+            };
+            let rel_path = path.strip_prefix(root).unwrap_or(&path);
+            if rules.is_ignored(rel_path, file_type.is_dir()) {
+                continue;
+            }
+            if file_type.is_dir() {
+                dirs.push(path);
+            } else if file_type.is_symlink() && fs::metadata(&path).is_err() {
+                tx.send(Message::Found(path))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reports entries below `root` matching `glob` skipping paths ignored by
+/// `rules`. Symlinks to directories aren't followed.
+fn find_by_name(
+    root: &Path,
+    glob: &str,
+    rules: &IgnoreRules,
+    tx: &mpsc::Sender<Message>,
+) -> Result<(), mpsc::SendError<Message>> {
+    let anchored = glob.contains('/');
+    let glob = glob.trim_start_matches('/');
+    let mut scanned = 0;
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        scanned += 1;
+        tx.send(Message::Scanned(scanned))?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            let rel_path = path.strip_prefix(root).unwrap_or(&path);
+            if rules.is_ignored(rel_path, is_dir) {
+                continue;
+            }
+            let name = entry.file_name();
+            let text = if anchored {
+                rel_path.to_string_lossy()
+            } else {
+                name.to_string_lossy()
+            };
+            if glob_match(glob.as_bytes(), text.as_bytes()) {
+                tx.send(Message::Found(path.clone()))?;
+            }
+            if is_dir {
+                dirs.push(path);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+/// Paths found by a search of `root` running in the background.
+pub struct PathScan {
+    pub kind: ScanKind,
+    pub root: PathBuf,
+    pub found: StatefulList<PathBuf>,
+    /// Number of directories scanned so far.
+    pub scanned: usize,
+    rx: Option<mpsc::Receiver<Message>>,
+}
+
+impl PathScan {
+    /// Starts searching `root` in a background thread.
+    pub fn start(kind: ScanKind, root: PathBuf) -> PathScan {
+        let (tx, rx) = mpsc::channel();
+        let dir = root.clone();
+        let scan_kind = kind.clone();
+        thread::spawn(move || {
+            let mut scanned = 0;
+            match scan_kind {
+                ScanKind::EmptyDirs => {
+                    // the root can't be removed, its empty children are
+                    // listed even when it's empty as a whole
+                    if let Ok(true) = find_empty_dirs(&dir, &mut scanned, &tx) {
+                        let mut children: Vec<_> = fs::read_dir(&dir)
+                            .into_iter()
+                            .flatten()
+                            .flatten()
+                            .map(|e| e.path())
+                            .collect();
+                        children.sort();
+                        for child in children {
+                            let _ = tx.send(Message::Found(child));
+                        }
+                    }
+                }
+                ScanKind::BrokenLinks => {
+                    let rules = IgnoreRules::load(&dir);
+                    let _ = find_broken_links(&dir, &rules, &tx);
+                }
+                ScanKind::Name(glob) => {
+                    let rules = IgnoreRules::load(&dir);
+                    let _ = find_by_name(&dir, &glob, &rules, &tx);
+                }
+            }
+        });
+
+        PathScan {
+            kind,
+            root,
+            found: StatefulList::new(),
+            scanned: 0,
+            rx: Some(rx),
+        }
+    }
+
+    /// Collects paths found since the last call.
+    pub fn update(&mut self) {
+        let rx = match &self.rx {
+            Some(rx) => rx,
+            None => return,
+        };
+        let selected = self.found.current().cloned();
+        loop {
+            match rx.try_recv() {
+                Ok(Message::Scanned(n)) => self.scanned = n,
+                Ok(Message::Found(path)) => self.found.items.push(path),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.rx = None;
+                    break;
+                }
+            }
+        }
+        self.found.items.sort();
+        // keep the selection on the same path as results come in
+        let idx = match selected {
+            Some(selected) => self.found.items.iter().position(|p| *p == selected),
+            None => Some(0).filter(|_| !self.found.items.is_empty()),
+        };
+        self.found.select(idx);
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// Forgets a removed path.
+    pub fn remove(&mut self, path: &Path) {
+        self.found.items.retain(|p| p != path);
+        let last = self.found.items.len().checked_sub(1);
+        let selected = self.found.state.selected();
+        self.found
+            .select(selected.and_then(|idx| last.map(|l| idx.min(l))));
+    }
+}
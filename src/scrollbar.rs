@@ -0,0 +1,40 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::Spans,
+    widgets::Paragraph,
+    Frame,
+};
+
+const THUMB: &str = "█";
+
+/// Draws a scrollbar thumb over the right border of the bordered `rect` of a
+/// list with `total` items of which `visible` fit at once and the item at
+/// `position` is selected. Nothing is drawn if all items fit.
+pub fn render_scrollbar<B: Backend>(
+    position: usize,
+    total: usize,
+    visible: usize,
+    frame: &mut Frame<B>,
+    rect: Rect,
+) {
+    let track = rect.height.saturating_sub(2) as usize;
+    if total <= visible || track == 0 || rect.width < 2 {
+        return;
+    }
+    let thumb = (track * visible / total).max(1);
+    let offset = (track - thumb) * position.min(total - 1) / (total - 1);
+
+    let lines: Vec<_> = (0..thumb).map(|_| Spans::from(THUMB)).collect();
+    let area = Rect {
+        x: rect.x + rect.width - 1,
+        y: rect.y + 1 + offset as u16,
+        width: 1,
+        height: thumb as u16,
+    };
+    frame.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(Color::Gray)),
+        area,
+    );
+}
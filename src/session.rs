@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sort::SortMode;
+use crate::util;
+use crate::util::toml::{parse_bool, parse_line, parse_str, parse_str_array, quote, quote_array};
+
+const SESSION_FILE: &str = "session.toml";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// State of a trav session that can be saved with `Ctrl-S` and restored on
+/// startup with `--restore-session`. Filters are kept in the textual form
+/// accepted by their parsers.
+pub struct Session {
+    pub cwd_path: PathBuf,
+    /// Name of the highlighted entry of `cwd_path`.
+    pub highlighted: Option<String>,
+    pub selection: Vec<PathBuf>,
+    pub sort_mode: SortMode,
+    pub sort_reversed: bool,
+    pub dirs_first: bool,
+    pub extensions: Option<Vec<String>>,
+    pub size_filter: Option<String>,
+    pub date_filter: Option<String>,
+    pub regex_filter: Option<String>,
+    pub perm_filter: Option<String>,
+    pub tag_filter: Option<String>,
+    pub case_sensitive: bool,
+}
+
+impl Session {
+    pub fn path(config_dir: Option<&Path>) -> Result<PathBuf> {
+        config_dir
+            .map(|dir| dir.join(SESSION_FILE))
+            .ok_or_else(|| anyhow!("failed to determine the config directory"))
+    }
+
+    pub fn load(config_dir: Option<&Path>) -> Result<Session> {
+        let path = Session::path(config_dir)?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read session {}", path.display()))?;
+        Session::parse(&content).with_context(|| format!("invalid session {}", path.display()))
+    }
+
+    pub fn save(&self, config_dir: Option<&Path>) -> Result<()> {
+        let path = Session::path(config_dir)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        util::write_atomic(&path, self.to_toml().as_bytes())
+    }
+
+    pub fn parse(content: &str) -> Result<Session> {
+        let mut session = Session::default();
+
+        for (n, line) in content.lines().enumerate() {
+            let (key, value) = match parse_line(line) {
+                Some(kv) => kv,
+                None => continue,
+            };
+
+            let res = match key {
+                "cwd_path" => parse_str(value).map(|v| session.cwd_path = v.into()),
+                "highlighted" => parse_str(value).map(|v| session.highlighted = Some(v)),
+                "selection" => parse_str_array(value)
+                    .map(|v| session.selection = v.into_iter().map(PathBuf::from).collect()),
+                "sort_mode" => parse_str(value)
+                    .and_then(|v| v.parse())
+                    .map(|v| session.sort_mode = v),
+                "sort_reversed" => parse_bool(value).map(|v| session.sort_reversed = v),
+                "dirs_first" => parse_bool(value).map(|v| session.dirs_first = v),
+                "extensions" => parse_str_array(value).map(|v| session.extensions = Some(v)),
+                "size_filter" => parse_str(value).map(|v| session.size_filter = Some(v)),
+                "date_filter" => parse_str(value).map(|v| session.date_filter = Some(v)),
+                "regex_filter" => parse_str(value).map(|v| session.regex_filter = Some(v)),
+                "perm_filter" => parse_str(value).map(|v| session.perm_filter = Some(v)),
+                "tag_filter" => parse_str(value).map(|v| session.tag_filter = Some(v)),
+                "case_sensitive" => parse_bool(value).map(|v| session.case_sensitive = v),
+                _ => Err(anyhow!("unknown key `{}`", key)),
+            };
+            res.with_context(|| format!("line {}", n + 1))?;
+        }
+
+        if session.cwd_path.as_os_str().is_empty() {
+            return Err(anyhow!("missing `cwd_path`"));
+        }
+
+        Ok(session)
+    }
+
+    pub fn to_toml(&self) -> String {
+        let selection: Vec<_> = self
+            .selection
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+
+        let mut out = String::new();
+        out.push_str(&format!(
+            "cwd_path = {}\n",
+            quote(&self.cwd_path.to_string_lossy())
+        ));
+        if let Some(name) = &self.highlighted {
+            out.push_str(&format!("highlighted = {}\n", quote(name)));
+        }
+        out.push_str(&format!("selection = {}\n", quote_array(&selection)));
+        out.push_str(&format!("sort_mode = {}\n", quote(self.sort_mode.as_str())));
+        out.push_str(&format!("sort_reversed = {}\n", self.sort_reversed));
+        out.push_str(&format!("dirs_first = {}\n", self.dirs_first));
+        if let Some(extensions) = &self.extensions {
+            out.push_str(&format!("extensions = {}\n", quote_array(extensions)));
+        }
+        if let Some(size) = &self.size_filter {
+            out.push_str(&format!("size_filter = {}\n", quote(size)));
+        }
+        if let Some(date) = &self.date_filter {
+            out.push_str(&format!("date_filter = {}\n", quote(date)));
+        }
+        if let Some(regex) = &self.regex_filter {
+            out.push_str(&format!("regex_filter = {}\n", quote(regex)));
+        }
+        if let Some(perm) = &self.perm_filter {
+            out.push_str(&format!("perm_filter = {}\n", quote(perm)));
+        }
+        if let Some(tag) = &self.tag_filter {
+            out.push_str(&format!("tag_filter = {}\n", quote(tag)));
+        }
+        if self.case_sensitive {
+            out.push_str("case_sensitive = true\n");
+        }
+        out
+    }
+}
@@ -0,0 +1,162 @@
+use anyhow::{anyhow, Error, Result};
+use std::cmp::Ordering;
+use std::ffi::{CString, OsStr, OsString};
+use std::fmt;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Once;
+use std::time::SystemTime;
+
+use crate::entry::DirEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    #[default]
+    Name,
+    /// By name in the order of the locale's collation rules.
+    NameLocale,
+    Size,
+    Modified,
+    /// By lowercase extension and then by name.
+    Extension,
+}
+
+impl SortMode {
+    /// Returns the mode that follows `self` when cycling through sort modes.
+    pub fn cycle(self) -> SortMode {
+        match self {
+            SortMode::Name => SortMode::NameLocale,
+            SortMode::NameLocale => SortMode::Size,
+            SortMode::Size => SortMode::Modified,
+            SortMode::Modified => SortMode::Extension,
+            SortMode::Extension => SortMode::Name,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortMode::Name => "name",
+            SortMode::NameLocale => "name-locale",
+            SortMode::Size => "size",
+            SortMode::Modified => "modified",
+            SortMode::Extension => "extension",
+        }
+    }
+}
+
+impl fmt::Display for SortMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "name" => Ok(SortMode::Name),
+            "name-locale" => Ok(SortMode::NameLocale),
+            "size" => Ok(SortMode::Size),
+            "modified" => Ok(SortMode::Modified),
+            "extension" => Ok(SortMode::Extension),
+            _ => Err(anyhow!("unknown sort mode `{}`", s)),
+        }
+    }
+}
+
+fn size(entry: &DirEntry) -> u64 {
+    entry.metadata().map(|md| md.len()).unwrap_or(0)
+}
+
+fn modified(entry: &DirEntry) -> SystemTime {
+    entry
+        .metadata()
+        .and_then(|md| md.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Key grouping entries by extension, entries without one go first if
+/// `no_ext_first` is set and last otherwise.
+fn extension(entry: &DirEntry, no_ext_first: bool) -> (bool, String, OsString) {
+    let name = entry.file_name();
+    match Path::new(&name).extension() {
+        Some(ext) => (
+            no_ext_first,
+            ext.to_string_lossy().to_lowercase(),
+            name.clone(),
+        ),
+        None => (!no_ext_first, String::new(), name),
+    }
+}
+
+static LOCALE_INIT: Once = Once::new();
+
+/// Compares `a` and `b` with `strcoll` using the collation of the locale set
+/// in the environment, `$LC_ALL`, `$LC_COLLATE` or `$LANG`. With no usable
+/// locale this is the same as comparing bytes.
+fn collate(a: &OsStr, b: &OsStr) -> Ordering {
+    LOCALE_INIT.call_once(|| {
+        // SAFETY: called once before any `strcoll`, the empty string selects
+        // the locale from the environment
+        unsafe {
+            libc::setlocale(libc::LC_COLLATE, b"\0".as_ptr().cast());
+        }
+    });
+
+    match (CString::new(a.as_bytes()), CString::new(b.as_bytes())) {
+        (Ok(ca), Ok(cb)) => {
+            // SAFETY: both pointers are valid null terminated strings
+            let res = unsafe { libc::strcoll(ca.as_ptr(), cb.as_ptr()) };
+            res.cmp(&0).then_with(|| a.cmp(b))
+        }
+        _ => a.cmp(b),
+    }
+}
+
+fn sort_partition(entries: &mut [DirEntry], mode: SortMode, opts: SortOptions) {
+    match mode {
+        SortMode::Name => entries.sort_by_cached_key(DirEntry::file_name),
+        SortMode::NameLocale => entries.sort_by(|a, b| collate(&a.file_name(), &b.file_name())),
+        SortMode::Size => entries.sort_by_cached_key(size),
+        SortMode::Modified => entries.sort_by_cached_key(modified),
+        SortMode::Extension => {
+            entries.sort_by_cached_key(|entry| extension(entry, opts.no_ext_first))
+        }
+    }
+    if opts.reversed {
+        entries.reverse();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SortOptions {
+    /// Sort in descending order.
+    pub reversed: bool,
+    /// Sort directories separately and place them before all other entries.
+    pub dirs_first: bool,
+    /// List entries without an extension before the others when sorting by
+    /// extension.
+    pub no_ext_first: bool,
+}
+
+/// Sorts `entries` according to `mode` and `opts`.
+pub fn sort_entries(
+    mut entries: Vec<DirEntry>,
+    mode: SortMode,
+    opts: SortOptions,
+) -> Vec<DirEntry> {
+    if !opts.dirs_first {
+        sort_partition(&mut entries, mode, opts);
+        return entries;
+    }
+
+    let (mut dirs, mut others): (Vec<_>, Vec<_>) =
+        entries.into_iter().partition(|e| e.path().is_dir());
+    sort_partition(&mut dirs, mode, opts);
+    sort_partition(&mut others, mode, opts);
+    dirs.append(&mut others);
+
+    dirs
+}
@@ -0,0 +1,84 @@
+//! Two directory panes stacked vertically, entries are copied and moved from
+//! the focused pane into the other one.
+use std::path::PathBuf;
+
+use crate::entry::DirEntry;
+use crate::util::list::StatefulList;
+
+/// Share of the height taken by the top pane when the view opens.
+pub const DEFAULT_SPLIT_RATIO: u16 = 50;
+const MIN_SPLIT_RATIO: u16 = 20;
+const MAX_SPLIT_RATIO: u16 = 80;
+const SPLIT_RATIO_STEP: u16 = 10;
+
+#[derive(Debug)]
+pub struct Pane {
+    pub path: PathBuf,
+    pub entries: StatefulList<DirEntry>,
+}
+
+impl Pane {
+    /// Lists `entries` of `path` highlighting the first one.
+    pub fn new(path: PathBuf, entries: Vec<DirEntry>) -> Pane {
+        let mut entries = StatefulList::with_items(entries);
+        if !entries.items.is_empty() {
+            entries.select(Some(0));
+        }
+        Pane { path, entries }
+    }
+
+    /// Path of the highlighted entry.
+    pub fn current(&self) -> Option<PathBuf> {
+        self.entries.current().map(DirEntry::path)
+    }
+}
+
+#[derive(Debug)]
+pub struct SplitView {
+    /// The top and bottom pane.
+    pub panes: [Pane; 2],
+    /// Index of the pane receiving keys.
+    pub focus: usize,
+    /// Percentage of the height taken by the top pane.
+    pub ratio: u16,
+}
+
+impl SplitView {
+    pub fn new(top: Pane, bottom: Pane) -> SplitView {
+        SplitView {
+            panes: [top, bottom],
+            focus: 0,
+            ratio: DEFAULT_SPLIT_RATIO,
+        }
+    }
+
+    pub fn focused(&self) -> &Pane {
+        &self.panes[self.focus]
+    }
+
+    pub fn focused_mut(&mut self) -> &mut Pane {
+        &mut self.panes[self.focus]
+    }
+
+    /// The pane entries are copied or moved into.
+    pub fn unfocused(&self) -> &Pane {
+        &self.panes[1 - self.focus]
+    }
+
+    pub fn switch_focus(&mut self) {
+        self.focus = 1 - self.focus;
+    }
+
+    /// Gives the top pane more room.
+    pub fn grow(&mut self) {
+        self.ratio = (self.ratio + SPLIT_RATIO_STEP).min(MAX_SPLIT_RATIO);
+    }
+
+    /// Gives the bottom pane more room.
+    pub fn shrink(&mut self) {
+        self.ratio = self
+            .ratio
+            .saturating_sub(SPLIT_RATIO_STEP)
+            .max(MIN_SPLIT_RATIO);
+    }
+}
@@ -0,0 +1,137 @@
+//! Per extension file statistics of a directory tree gathered in the background.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+/// Number of extensions shown, the most common ones.
+pub const TOP_EXTENSIONS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtStat {
+    /// Lowercase extension, `None` for files without one.
+    pub extension: Option<String>,
+    pub count: u64,
+    /// Total size of the files in bytes.
+    pub size: u64,
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+#[derive(Debug)]
+/// Files under `root` grouped by extension, updated as the scan proceeds.
+pub struct ExtStats {
+    pub root: PathBuf,
+    totals: HashMap<Option<String>, (u64, u64)>,
+    /// The most common extensions, by count and then by name.
+    pub top: Vec<ExtStat>,
+    pub files: u64,
+    pub selected: usize,
+    rx: Option<mpsc::Receiver<(Option<String>, u64)>>,
+}
+
+impl ExtStats {
+    /// Starts scanning `root` in a background thread. Symlinks aren't followed.
+    pub fn scan(root: PathBuf) -> ExtStats {
+        let (tx, rx) = mpsc::channel();
+        let dir = root.clone();
+        thread::spawn(move || {
+            let mut dirs = vec![dir];
+            while let Some(dir) = dirs.pop() {
+                let entries = match fs::read_dir(&dir) {
+                    Ok(entries) => entries,
+                    Err(_) => continue,
+                };
+                for entry in entries.flatten() {
+                    match entry.file_type() {
+                        Ok(t) if t.is_dir() => dirs.push(entry.path()),
+                        Ok(t) if t.is_file() => {
+                            let size = entry.metadata().map(|md| md.len()).unwrap_or(0);
+                            // the view was closed
+                            if tx.send((extension(&entry.path()), size)).is_err() {
+                                return;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        });
+
+        ExtStats {
+            root,
+            totals: HashMap::new(),
+            top: vec![],
+            files: 0,
+            selected: 0,
+            rx: Some(rx),
+        }
+    }
+
+    /// Counts files scanned since the last call.
+    pub fn update(&mut self) {
+        let rx = match &self.rx {
+            Some(rx) => rx,
+            None => return,
+        };
+        let selected = self.current().map(|stat| stat.extension.clone());
+        loop {
+            match rx.try_recv() {
+                Ok((extension, size)) => {
+                    let (count, total) = self.totals.entry(extension).or_default();
+                    *count += 1;
+                    *total += size;
+                    self.files += 1;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.rx = None;
+                    break;
+                }
+            }
+        }
+
+        let mut top: Vec<_> = self
+            .totals
+            .iter()
+            .map(|(extension, &(count, size))| ExtStat {
+                extension: extension.clone(),
+                count,
+                size,
+            })
+            .collect();
+        top.sort_by(|a, b| b.count.cmp(&a.count).then(a.extension.cmp(&b.extension)));
+        top.truncate(TOP_EXTENSIONS);
+        self.top = top;
+        // keep the selection on the same extension as the order changes
+        if let Some(selected) = selected {
+            self.selected = self
+                .top
+                .iter()
+                .position(|stat| stat.extension == selected)
+                .unwrap_or(0);
+        }
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn current(&self) -> Option<&ExtStat> {
+        self.top.get(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.top.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
@@ -0,0 +1,99 @@
+//! Color tags assigned to entries with `1`–`5`, saved in
+//! `~/.config/trav/tags.toml` as one array of paths per color.
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tui::style::Color;
+
+use crate::util;
+use crate::util::toml::{parse_line, parse_str_array, quote_array};
+
+const TAGS_FILE: &str = "tags.toml";
+
+/// Names and colors of the tags numbered from 1.
+static TAGS: [(&str, Color); 5] = [
+    ("red", Color::Red),
+    ("orange", Color::Indexed(208)),
+    ("yellow", Color::Yellow),
+    ("green", Color::Green),
+    ("blue", Color::Blue),
+];
+
+fn get(tag: u8) -> Option<&'static (&'static str, Color)> {
+    (tag as usize).checked_sub(1).and_then(|i| TAGS.get(i))
+}
+
+/// Name of the tag numbered `tag`.
+pub fn name(tag: u8) -> &'static str {
+    get(tag).map_or("unknown", |(name, _)| name)
+}
+
+pub fn color(tag: u8) -> Color {
+    get(tag).map_or(Color::Reset, |(_, color)| *color)
+}
+
+/// Number of the tag named `s` or given by its number.
+pub fn parse_tag(s: &str) -> Result<u8> {
+    if let Some(i) = TAGS.iter().position(|(name, _)| *name == s) {
+        return Ok(i as u8 + 1);
+    }
+    match s.parse() {
+        Ok(tag @ 1..=5) => Ok(tag),
+        _ => Err(anyhow!(
+            "invalid tag `{}`, expected red, orange, yellow, green or blue",
+            s
+        )),
+    }
+}
+
+pub fn path(config_dir: Option<&Path>) -> Result<PathBuf> {
+    config_dir
+        .map(|dir| dir.join(TAGS_FILE))
+        .ok_or_else(|| anyhow!("failed to determine the config directory"))
+}
+
+/// Reads the saved tags. A missing file has no tags.
+pub fn load(config_dir: Option<&Path>) -> Result<HashMap<PathBuf, u8>> {
+    let path = path(config_dir)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("invalid {}", path.display()))
+}
+
+pub fn parse(content: &str) -> Result<HashMap<PathBuf, u8>> {
+    let mut tags = HashMap::new();
+    for line in content.lines() {
+        if let Some((key, value)) = parse_line(line) {
+            let tag = parse_tag(key)?;
+            for path in parse_str_array(value)? {
+                tags.insert(PathBuf::from(path), tag);
+            }
+        }
+    }
+    Ok(tags)
+}
+
+pub fn save(tags: &HashMap<PathBuf, u8>, config_dir: Option<&Path>) -> Result<()> {
+    let path = path(config_dir)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content = String::new();
+    for (i, (name, _)) in TAGS.iter().enumerate() {
+        let mut paths: Vec<_> = tags
+            .iter()
+            .filter(|(_, &tag)| tag as usize == i + 1)
+            .map(|(path, _)| path.to_string_lossy())
+            .collect();
+        if paths.is_empty() {
+            continue;
+        }
+        paths.sort();
+        content.push_str(&format!("{} = {}\n", name, quote_array(&paths)));
+    }
+    util::write_atomic(&path, content.as_bytes())
+}
@@ -0,0 +1,75 @@
+//! Boilerplate for new files read from `~/.config/trav/templates/`. A template
+//! applies to new files with the same extension, e.g. `template.rs` to
+//! `main.rs`.
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const TEMPLATES_DIR: &str = "templates";
+
+/// Lowercase extension of `path`.
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+/// Lists the template files sorted by name. A missing directory has no
+/// templates.
+pub fn load(config_dir: Option<&Path>) -> Vec<PathBuf> {
+    let dir = match config_dir {
+        Some(dir) => dir.join(TEMPLATES_DIR),
+        None => return vec![],
+    };
+    let mut templates: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => vec![],
+    };
+    templates.sort();
+    templates
+}
+
+/// Templates applying to a new file named `name`.
+pub fn matching<'a>(templates: &'a [PathBuf], name: &str) -> Vec<&'a PathBuf> {
+    let ext = match extension(Path::new(name)) {
+        Some(ext) => ext,
+        None => return vec![],
+    };
+    templates
+        .iter()
+        .filter(|template| extension(template).as_deref() == Some(ext.as_str()))
+        .collect()
+}
+
+/// Replaces `{name}` with the new file's name without the extension and
+/// `{date}` with `date` formatted as `YYYY-MM-DD`.
+pub fn expand(content: &str, path: &Path, date: NaiveDate) -> String {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    content
+        .replace("{name}", &name)
+        .replace("{date}", &date.format("%Y-%m-%d").to_string())
+}
+
+/// Creates the file at `path` filled from `template` if given. Existing files
+/// aren't overwritten.
+pub fn create_file(path: &Path, template: Option<&Path>, date: NaiveDate) -> Result<()> {
+    let content = match template {
+        Some(template) => expand(&fs::read_to_string(template)?, path, date),
+        None => String::new(),
+    };
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
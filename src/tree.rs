@@ -0,0 +1,122 @@
+//! Text representation of a directory tree in the style of the `tree` command.
+use std::fs;
+use std::path::Path;
+
+use crate::ignore::IgnoreRules;
+use crate::util;
+
+/// Depth of generated trees if not configured.
+pub const DEFAULT_TREE_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirTree {
+    /// One line per entry starting with the root directory.
+    pub lines: Vec<String>,
+    pub dirs: usize,
+    pub files: usize,
+    /// Total size of the listed files.
+    pub size: u64,
+}
+
+impl DirTree {
+    /// Walks `root` up to `max_depth` levels deep skipping paths ignored by
+    /// `rules` and, unless `show_hidden` is set, hidden entries. Symlinks aren't
+    /// followed.
+    pub fn generate(
+        root: &Path,
+        max_depth: usize,
+        rules: &IgnoreRules,
+        show_hidden: bool,
+    ) -> DirTree {
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| root.to_string_lossy().to_string());
+        let mut tree = DirTree {
+            lines: vec![format!("{}/", name.trim_end_matches('/'))],
+            ..DirTree::default()
+        };
+        let walker = Walker {
+            root,
+            max_depth,
+            rules,
+            show_hidden,
+        };
+        walker.walk(root, "", 1, &mut tree);
+        tree
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    /// Numbers of directories and files and their total size, e.g.
+    /// `2 directories, 5 files, 1.20KB`.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} {}, {} {}, {}",
+            self.dirs,
+            if self.dirs == 1 {
+                "directory"
+            } else {
+                "directories"
+            },
+            self.files,
+            if self.files == 1 { "file" } else { "files" },
+            util::conv_b(self.size)
+        )
+    }
+}
+
+struct Walker<'a> {
+    root: &'a Path,
+    max_depth: usize,
+    rules: &'a IgnoreRules,
+    show_hidden: bool,
+}
+
+impl Walker<'_> {
+    fn walk(&self, dir: &Path, prefix: &str, depth: usize, tree: &mut DirTree) {
+        if depth > self.max_depth {
+            return;
+        }
+        let mut entries: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let file_type = entry.file_type().ok()?;
+                    let path = entry.path();
+                    let rel = path.strip_prefix(self.root).unwrap_or(&path);
+                    if (!self.show_hidden && name.starts_with('.'))
+                        || self.rules.is_ignored(rel, file_type.is_dir())
+                    {
+                        return None;
+                    }
+                    Some((name, entry))
+                })
+                .collect(),
+            Err(_) => return,
+        };
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let count = entries.len();
+        for (i, (name, entry)) in entries.into_iter().enumerate() {
+            let last = i + 1 == count;
+            let branch = if last { "└── " } else { "├── " };
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if is_dir {
+                tree.dirs += 1;
+                tree.lines.push(format!("{}{}{}/", prefix, branch, name));
+                let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                self.walk(&entry.path(), &prefix, depth + 1, tree);
+            } else {
+                tree.files += 1;
+                tree.size += entry.metadata().map(|md| md.len()).unwrap_or(0);
+                tree.lines.push(format!("{}{}{}", prefix, branch, name));
+            }
+        }
+    }
+}
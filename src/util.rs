@@ -1,9 +1,22 @@
+pub mod clipboard;
 pub mod list;
+pub mod lru;
+pub mod regex;
+pub mod sha256;
+pub mod toml;
 
-use anyhow::Result;
-use chrono::{offset::Utc, DateTime, TimeZone};
-use std::io;
-use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::{anyhow, Result};
+use chrono::{offset::Local, offset::Utc, DateTime, Datelike, NaiveDate, TimeZone};
+use std::ffi::{CStr, CString};
+use std::io::BufRead;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fs, mem, process, ptr};
 use termion::{input::MouseTerminal, raw::IntoRawMode, screen::AlternateScreen};
 use tui::{backend::TermionBackend, Terminal};
 
@@ -22,6 +35,70 @@ pub fn get_terminal() -> Result<Term> {
     Ok(Terminal::new(backend)?)
 }
 
+/// Shortens `path` to at most `max_len` characters keeping its rightmost
+/// components, `/home/user/very/deep/path` becomes `…/deep/path`.
+pub fn truncate_path(path: &str, max_len: u16) -> String {
+    let max_len = max_len as usize;
+    let len = path.chars().count();
+    if len <= max_len {
+        return path.to_string();
+    }
+    if max_len == 0 {
+        return String::new();
+    }
+
+    let tail: String = path.chars().skip(len - (max_len - 1)).collect();
+    // cut at a separator so that the first component isn't partial
+    let tail = match tail.find('/') {
+        Some(idx) if idx > 0 => &tail[idx..],
+        _ => tail.as_str(),
+    };
+    format!("…{}", tail)
+}
+
+/// Replaces tabs in `line` with spaces up to the next multiple of `width`
+/// columns.
+pub fn expand_tabs(line: &str, width: usize) -> String {
+    if !line.contains('\t') {
+        return line.to_string();
+    }
+    let mut out = String::with_capacity(line.len());
+    let mut col = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let n = if width == 0 { 0 } else { width - col % width };
+            out.extend(std::iter::repeat_n(' ', n));
+            col += n;
+        } else {
+            out.push(c);
+            col += 1;
+        }
+    }
+    out
+}
+
+/// Path leading from the directory `base` to `path`, both absolute, going up
+/// with `..` where they diverge.
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<_> = path.components().collect();
+    let base: Vec<_> = base.components().collect();
+    let common = path
+        .iter()
+        .zip(base.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut rel = PathBuf::new();
+    for _ in common..base.len() {
+        rel.push("..");
+    }
+    rel.extend(&path[common..]);
+    if rel.as_os_str().is_empty() {
+        rel.push(".");
+    }
+    rel
+}
+
 pub fn system_time_to_date_time(t: SystemTime) -> DateTime<Utc> {
     let (sec, nsec) = match t.duration_since(UNIX_EPOCH) {
         Ok(dur) => (dur.as_secs() as i64, dur.subsec_nanos()),
@@ -42,11 +119,11 @@ pub fn system_time_to_date_time(t: SystemTime) -> DateTime<Utc> {
 fn conv_metric(value: f64, unit: &str) -> String {
     let (val, u) = if value < KILO {
         (value, "")
-    } else if KILO <= value && value < MEGA {
+    } else if (KILO..MEGA).contains(&value) {
         (value / KILO, "K")
-    } else if MEGA <= value && value < GIGA {
+    } else if (MEGA..GIGA).contains(&value) {
         (value / MEGA, "M")
-    } else if GIGA <= value && value < TERA {
+    } else if (GIGA..TERA).contains(&value) {
         (value / GIGA, "G")
     } else {
         (value / TERA, "T")
@@ -62,3 +139,463 @@ pub fn conv_fb(bytes: f64) -> String {
 pub fn conv_b(bytes: u64) -> String {
     conv_fb(bytes as f64)
 }
+
+/// Parses a human readable size like `10`, `1.5K`, `20MB` or `3g` into bytes.
+pub fn parse_size(s: &str) -> Result<u64> {
+    let upper = s.trim().to_uppercase();
+    let upper = upper.strip_suffix('B').unwrap_or(&upper);
+    let (num, mult) = match upper.chars().last() {
+        Some('K') => (&upper[..upper.len() - 1], KILO),
+        Some('M') => (&upper[..upper.len() - 1], MEGA),
+        Some('G') => (&upper[..upper.len() - 1], GIGA),
+        Some('T') => (&upper[..upper.len() - 1], TERA),
+        _ => (upper, 1.),
+    };
+    let num: f64 = num.parse().map_err(|_| anyhow!("invalid size `{}`", s))?;
+    // `inf`, `nan` and overflowing exponents parse fine as floats
+    let bytes = num * mult;
+    if !bytes.is_finite() || bytes < 0. {
+        return Err(anyhow!("invalid size `{}`", s));
+    }
+    if bytes >= u64::MAX as f64 {
+        return Err(anyhow!("size `{}` is too large", s));
+    }
+
+    Ok(bytes as u64)
+}
+
+/// Applies the mode `spec` to `base`, the `st_mode` of a file including its
+/// type bits. `spec` is either octal like `644` or symbolic like `u+x,go-w` in
+/// the syntax of `chmod`. Like in `chmod`, `x` applies to files and
+/// directories alike while `X` adds execute permission only to directories
+/// and files already executable by someone. Returns the new permission bits.
+pub fn parse_symbolic_mode(base: u32, spec: &str) -> Result<u32> {
+    const S_IFMT: u32 = 0o170000;
+    const S_IFDIR: u32 = 0o040000;
+
+    let spec = spec.trim();
+    if spec.is_empty() {
+        return Err(anyhow!("empty mode"));
+    }
+    if spec.len() <= 4 && spec.chars().all(|c| c.is_digit(8)) {
+        return Ok(u32::from_str_radix(spec, 8)?);
+    }
+
+    let is_dir = base & S_IFMT == S_IFDIR;
+    let mut mode = base & 0o7777;
+    for clause in spec.split(',') {
+        let invalid = || anyhow!("invalid mode `{}`", clause);
+        let mut chars = clause.chars().peekable();
+        let mut who = 0;
+        while let Some(&c) = chars.peek() {
+            who |= match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                'a' => 0o7777,
+                _ => break,
+            };
+            chars.next();
+        }
+        if who == 0 {
+            who = 0o7777;
+        }
+        if chars.peek().is_none() {
+            return Err(invalid());
+        }
+
+        // executable bits as of the start of the clause decide `X`
+        let executable = is_dir || mode & 0o111 != 0;
+        while let Some(op) = chars.next() {
+            if !matches!(op, '+' | '-' | '=') {
+                return Err(invalid());
+            }
+            let mut bits = 0;
+            while let Some(&c) = chars.peek() {
+                bits |= match c {
+                    'r' => 0o444,
+                    'w' => 0o222,
+                    'x' => 0o111,
+                    'X' if executable => 0o111,
+                    'X' => 0,
+                    's' => 0o6000,
+                    't' => 0o1000,
+                    // copies the permissions of the given class
+                    'u' => (mode >> 6 & 0o7) * 0o111,
+                    'g' => (mode >> 3 & 0o7) * 0o111,
+                    'o' => (mode & 0o7) * 0o111,
+                    '+' | '-' | '=' => break,
+                    _ => return Err(invalid()),
+                };
+                chars.next();
+            }
+            bits &= who;
+            match op {
+                '+' => mode |= bits,
+                '-' => mode &= !bits,
+                _ => mode = mode & !who | bits,
+            }
+        }
+    }
+
+    Ok(mode)
+}
+
+fn local_midnight(date: NaiveDate) -> Result<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&date.and_hms(0, 0, 0))
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok_or_else(|| anyhow!("invalid local date {}", date))
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|e| anyhow!("invalid date `{}`, expected YYYY-MM-DD: {}", s, e))
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd(date.year(), date.month(), 1)
+}
+
+/// Parses a date range relative to `today`. Accepts the keywords `today`,
+/// `yesterday`, `this_week`, `last_week`, `this_month`, `last_month`, a single
+/// `YYYY-MM-DD` date or an inclusive `YYYY-MM-DD..YYYY-MM-DD` range. Days are
+/// interpreted in the local timezone.
+pub fn parse_date_range(s: &str, today: NaiveDate) -> Result<Range<DateTime<Utc>>> {
+    let day = chrono::Duration::days(1);
+    let week_start = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+    let month_start = first_of_month(today);
+
+    let (start, end) = match s.trim() {
+        "today" => (today, today + day),
+        "yesterday" => (today - day, today),
+        "this_week" => (week_start, today + day),
+        "last_week" => (week_start - chrono::Duration::weeks(1), week_start),
+        "this_month" => (month_start, today + day),
+        "last_month" => (first_of_month(month_start - day), month_start),
+        range => {
+            let mut it = range.splitn(2, "..");
+            let start = parse_date(it.next().unwrap_or_default())?;
+            let end = match it.next() {
+                Some(end) => parse_date(end)?,
+                None => start,
+            };
+            if end < start {
+                return Err(anyhow!("date range `{}` ends before it starts", range));
+            }
+            (start, end + day)
+        }
+    };
+
+    Ok(local_midnight(start)?..local_midnight(end)?)
+}
+
+pub fn today() -> NaiveDate {
+    Local::today().naive_local()
+}
+
+/// Writes `data` to a temporary file next to `path` and renames it over `path`
+/// so that readers never observe a partially written file.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid file path {}", path.display()))?;
+    let tmp = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        process::id()
+    ));
+
+    let res = fs::File::create(&tmp)
+        .and_then(|mut f| f.write_all(data).and_then(|_| f.sync_all()))
+        .and_then(|_| fs::rename(&tmp, path));
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    Ok(res?)
+}
+
+/// Runs `cmd` with `sh -c` feeding it `input` on stdin. Returns its stdout and
+/// stderr.
+pub fn pipe_through(cmd: &str, input: Vec<u8>) -> Result<(String, String)> {
+    let mut child = process::Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::piped())
+        .spawn()?;
+
+    // written from another thread so that a command producing output before
+    // reading all of its input can't deadlock
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("failed to open stdin"))?;
+    let writer = thread::spawn(move || {
+        // the command may exit without reading everything, e.g. `head`
+        let _ = stdin.write_all(&input);
+    });
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+
+    Ok((
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    ))
+}
+
+/// Reads a single line from stdin if it's not a terminal. Gives up if nothing
+/// arrives within `timeout`.
+pub fn read_stdin_line(timeout: Duration) -> Option<String> {
+    if termion::is_tty(&io::stdin()) {
+        return None;
+    }
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line).is_ok() {
+            let _ = tx.send(line);
+        }
+    });
+
+    rx.recv_timeout(timeout)
+        .ok()
+        .map(|line| line.trim_end_matches(&['\r', '\n'][..]).to_string())
+        .filter(|line| !line.is_empty())
+}
+
+/// Stops the process with `SIGSTOP` until it's continued, e.g. with `fg`.
+pub fn stop_process() -> Result<()> {
+    // SAFETY: sending a signal to the current process has no memory effects
+    if unsafe { libc::kill(libc::getpid(), libc::SIGSTOP) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Whether the process runs with the user id of root.
+pub fn is_root() -> bool {
+    // SAFETY: getuid always succeeds and has no side effects
+    unsafe { libc::getuid() == 0 }
+}
+
+/// Value in bytes of a memory field like `VmRSS` in `/proc/self/status`,
+/// `None` where there's no procfs.
+pub fn process_memory(field: &str) -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status.lines().find_map(|line| {
+        let value = line.strip_prefix(field)?.strip_prefix(':')?;
+        let kb: u64 = value.trim().trim_end_matches("kB").trim().parse().ok()?;
+        Some(kb * 1024)
+    })
+}
+
+/// Number of file descriptors open in this process, `None` where there's no
+/// procfs.
+pub fn open_fds() -> Option<usize> {
+    // reading the directory takes a descriptor itself
+    fs::read_dir("/proc/self/fd")
+        .ok()
+        .map(|fds| fds.count().saturating_sub(1))
+}
+
+/// `path` for display with the `home` directory at its start replaced by `~`.
+pub fn abbreviate_home(path: &Path, home: Option<&Path>) -> String {
+    let home = match home {
+        // the root would turn every path into `~/...`
+        Some(home) if home.parent().is_some() => home,
+        _ => return path.to_string_lossy().to_string(),
+    };
+    match path.strip_prefix(home) {
+        Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Ok(rest) => format!("~/{}", rest.to_string_lossy()),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Home directory of `user` looked up in the user database or of the current
+/// user from `$HOME` if `None`.
+pub fn home_dir(user: Option<&str>) -> Option<String> {
+    let user = match user {
+        Some(user) => user,
+        None => return env::var("HOME").ok(),
+    };
+    let name = CString::new(user).ok()?;
+    let mut buf = vec![0 as libc::c_char; 1024];
+    loop {
+        // SAFETY: an all zero `passwd` is a valid value for the out parameter
+        let mut pwd: libc::passwd = unsafe { mem::zeroed() };
+        let mut result = ptr::null_mut();
+        // SAFETY: all pointers are valid for the duration of the call and `buf`
+        // is as long as passed
+        let rc = unsafe {
+            libc::getpwnam_r(
+                name.as_ptr(),
+                &mut pwd,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+        if rc == libc::ERANGE && buf.len() < 1 << 20 {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 || result.is_null() || pwd.pw_dir.is_null() {
+            return None;
+        }
+        // SAFETY: `pw_dir` points to a null terminated string stored in `buf`
+        let dir = unsafe { CStr::from_ptr(pwd.pw_dir) };
+        return Some(dir.to_string_lossy().to_string());
+    }
+}
+
+/// If stdout isn't a terminal, e.g. when it's captured by `$(trav ...)`, points
+/// it at the controlling terminal so the TUI can be drawn. Returns a handle to
+/// the original stdout in that case.
+pub fn redirect_stdout_to_tty() -> Result<Option<fs::File>> {
+    if termion::is_tty(&io::stdout()) {
+        return Ok(None);
+    }
+
+    let tty = termion::get_tty()?;
+    // SAFETY: both file descriptors are valid for the duration of the calls and
+    // the duplicated descriptor is owned by the returned `File`.
+    unsafe {
+        let orig = libc::dup(libc::STDOUT_FILENO);
+        if orig < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        let orig = fs::File::from_raw_fd(orig);
+        if libc::dup2(tty.as_raw_fd(), libc::STDOUT_FILENO) < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(Some(orig))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_time(t: SystemTime) -> String {
+        system_time_to_date_time(t).to_rfc3339()
+    }
+
+    #[test]
+    fn system_time_after_epoch() {
+        let t = UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+        assert_eq!(date_time(t), "2024-01-01T00:00:00+00:00");
+        let t = t + Duration::from_millis(250);
+        assert_eq!(date_time(t), "2024-01-01T00:00:00.250+00:00");
+    }
+
+    #[test]
+    fn system_time_at_epoch() {
+        assert_eq!(date_time(UNIX_EPOCH), "1970-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn system_time_before_epoch() {
+        // whole seconds take the `nsec == 0` branch
+        let t = UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(date_time(t), "1969-12-31T23:59:59+00:00");
+        let t = UNIX_EPOCH - Duration::from_millis(1_500);
+        assert_eq!(date_time(t), "1969-12-31T23:59:58.500+00:00");
+        let t = UNIX_EPOCH - Duration::from_nanos(1);
+        assert_eq!(date_time(t), "1969-12-31T23:59:59.999999999+00:00");
+    }
+
+    #[test]
+    fn parse_size_units() {
+        assert_eq!(parse_size("10").unwrap(), 10);
+        assert_eq!(parse_size("1.5K").unwrap(), 1_500);
+        assert_eq!(parse_size("20MB").unwrap(), 20_000_000);
+        assert_eq!(parse_size(" 3g ").unwrap(), 3_000_000_000);
+    }
+
+    #[test]
+    fn parse_size_rejects_non_finite_and_negative() {
+        for s in [
+            "inf", "-inf", "nan", "NaN", "1e400", "1e400K", "-1", "-0.5K", "1e20", "x",
+        ] {
+            assert!(parse_size(s).is_err(), "{}", s);
+        }
+    }
+
+    const FILE: u32 = 0o100000;
+    const DIR: u32 = 0o040000;
+
+    #[test]
+    fn parse_symbolic_mode_octal() {
+        assert_eq!(parse_symbolic_mode(FILE | 0o600, "644").unwrap(), 0o644);
+        assert_eq!(parse_symbolic_mode(DIR | 0o700, "1777").unwrap(), 0o1777);
+    }
+
+    #[test]
+    fn parse_symbolic_mode_clauses() {
+        assert_eq!(parse_symbolic_mode(FILE | 0o644, "u+x").unwrap(), 0o744);
+        assert_eq!(parse_symbolic_mode(FILE | 0o666, "go-w").unwrap(), 0o644);
+        assert_eq!(parse_symbolic_mode(FILE | 0o755, "a=r").unwrap(), 0o444);
+        assert_eq!(parse_symbolic_mode(FILE | 0o600, "u-w,g+r").unwrap(), 0o440);
+        assert_eq!(parse_symbolic_mode(FILE | 0o640, "o=g").unwrap(), 0o644);
+        // `x` doesn't depend on the type
+        assert_eq!(parse_symbolic_mode(FILE | 0o644, "+x").unwrap(), 0o755);
+        assert_eq!(parse_symbolic_mode(DIR | 0o644, "+x").unwrap(), 0o755);
+    }
+
+    #[test]
+    fn parse_symbolic_mode_capital_x() {
+        assert_eq!(parse_symbolic_mode(FILE | 0o644, "+X").unwrap(), 0o644);
+        assert_eq!(parse_symbolic_mode(FILE | 0o744, "+X").unwrap(), 0o755);
+        assert_eq!(parse_symbolic_mode(DIR | 0o644, "+X").unwrap(), 0o755);
+        assert_eq!(parse_symbolic_mode(DIR | 0o600, "go+rX").unwrap(), 0o655);
+    }
+
+    #[test]
+    fn parse_symbolic_mode_rejects_invalid_specs() {
+        for spec in ["", " ", "u", "u+q", "z+x", "u+x,", "9", "+x y", "u!x"] {
+            assert!(parse_symbolic_mode(FILE | 0o644, spec).is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn conv_b_uses_decimal_units() {
+        assert_eq!(conv_b(0), "0.00B");
+        assert_eq!(conv_b(1_000), "1.00KB");
+        assert_eq!(conv_b(1_024), "1.02KB");
+        assert_eq!(conv_b(1_000_000), "1.00MB");
+        assert_eq!(conv_b(1_000_000_000), "1.00GB");
+        assert_eq!(conv_b(1_000_000_000_000), "1.00TB");
+        assert_eq!(conv_b(1_000_000_000_000_000), "1000.00TB");
+    }
+
+    #[test]
+    fn conv_b_below_thresholds() {
+        assert_eq!(conv_b(999), "999.00B");
+        assert_eq!(conv_b(999_994), "999.99KB");
+        // rounded up without switching to the next unit
+        assert_eq!(conv_b(999_999), "1000.00KB");
+        assert_eq!(conv_b(999_999_999), "1000.00MB");
+        assert_eq!(conv_b(999_999_999_999), "1000.00GB");
+    }
+
+    #[test]
+    fn conv_b_max() {
+        assert_eq!(conv_b(u64::MAX), "18446744.07TB");
+    }
+
+    #[test]
+    fn conv_fb_fractions_and_negatives() {
+        assert_eq!(conv_fb(0.5), "0.50B");
+        assert_eq!(conv_fb(1_500.), "1.50KB");
+        assert_eq!(conv_fb(-0.), "-0.00B");
+        assert_eq!(conv_fb(-1.), "-1.00B");
+        // negatives are never scaled
+        assert_eq!(conv_fb(-2_000_000.), "-2000000.00B");
+    }
+}
@@ -0,0 +1,63 @@
+//! Access to the system clipboard through the usual command line tools with a
+//! fallback to the OSC 52 escape sequence understood by most terminals.
+use anyhow::Result;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Clipboard tools tried in order.
+const TOOLS: &[&[&str]] = &[
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["pbcopy"],
+];
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Puts `text` in the system clipboard.
+pub fn copy(text: &str) -> Result<()> {
+    for tool in TOOLS {
+        if copy_with(tool, text).unwrap_or(false) {
+            return Ok(());
+        }
+    }
+
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Pipes `text` to the clipboard tool, returns `false` if it failed.
+fn copy_with(tool: &[&str], text: &str) -> io::Result<bool> {
+    let mut child = Command::new(tool[0])
+        .args(&tool[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    Ok(child.wait()?.success())
+}
+
+fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
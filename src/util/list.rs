@@ -1,6 +1,9 @@
 use tui::widgets::ListState;
 
 #[derive(Debug, Default)]
+/// Items with the state of the list widget rendering them. The widget scrolls
+/// to keep the selected item in view when rendered, `ListState` doesn't expose
+/// the offset to position it otherwise.
 pub struct StatefulList<T> {
     pub state: ListState,
     pub items: Vec<T>,
@@ -21,7 +24,11 @@ impl<T> StatefulList<T> {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<usize> {
+        if self.items.is_empty() {
+            return None;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= self.items.len() - 1 {
@@ -38,6 +45,9 @@ impl<T> StatefulList<T> {
     }
 
     pub fn previous(&mut self) -> Option<usize> {
+        if self.items.is_empty() {
+            return None;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -53,12 +63,40 @@ impl<T> StatefulList<T> {
         self.current_idx()
     }
 
+    /// Moves the selection `n` items down stopping at the last one.
+    pub fn page_down(&mut self, n: usize) -> Option<usize> {
+        let idx = self.state.selected().map(|i| i + n).unwrap_or(0);
+        self.select(Some(idx));
+        self.current_idx()
+    }
+
+    /// Moves the selection `n` items up stopping at the first one.
+    pub fn page_up(&mut self, n: usize) -> Option<usize> {
+        let idx = self
+            .state
+            .selected()
+            .map(|i| i.saturating_sub(n))
+            .unwrap_or(0);
+        self.select(Some(idx));
+        self.current_idx()
+    }
+
+    pub fn select_first(&mut self) -> Option<usize> {
+        self.select(Some(0));
+        self.current_idx()
+    }
+
+    pub fn select_last(&mut self) -> Option<usize> {
+        self.select(Some(self.items.len().saturating_sub(1)));
+        self.current_idx()
+    }
+
     pub fn unselect(&mut self) {
         self.state.select(None);
     }
 
     pub fn current(&self) -> Option<&T> {
-        Some(&self.items[self.state.selected()?])
+        self.items.get(self.state.selected()?)
     }
 
     pub fn current_idx(&self) -> Option<usize> {
@@ -66,6 +104,10 @@ impl<T> StatefulList<T> {
     }
 
     pub fn select(&mut self, idx: Option<usize>) {
+        if self.items.is_empty() {
+            self.state.select(None);
+            return;
+        }
         let idx = match idx {
             Some(i) => {
                 if i >= self.items.len() - 1 {
@@ -79,3 +121,84 @@ impl<T> StatefulList<T> {
         self.state.select(Some(idx))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(n: usize) -> StatefulList<usize> {
+        StatefulList::with_items((0..n).collect())
+    }
+
+    #[test]
+    fn next_wraps_to_the_first_item() {
+        let mut list = list(3);
+        list.select(Some(2));
+        assert_eq!(list.next(), Some(0));
+    }
+
+    #[test]
+    fn next_without_selection_selects_the_first_item() {
+        let mut list = list(3);
+        assert_eq!(list.next(), Some(0));
+        assert_eq!(list.next(), Some(1));
+    }
+
+    #[test]
+    fn previous_wraps_to_the_last_item() {
+        let mut list = list(3);
+        list.select(Some(0));
+        assert_eq!(list.previous(), Some(2));
+        assert_eq!(list.previous(), Some(1));
+    }
+
+    #[test]
+    fn empty_lists_select_nothing() {
+        let mut list = list(0);
+        assert_eq!(list.next(), None);
+        assert_eq!(list.previous(), None);
+        assert_eq!(list.page_down(5), None);
+        assert_eq!(list.page_up(5), None);
+        assert_eq!(list.select_first(), None);
+        assert_eq!(list.select_last(), None);
+        list.select(Some(3));
+        assert_eq!(list.current(), None);
+    }
+
+    #[test]
+    fn current_is_the_selected_item() {
+        let mut list = list(3);
+        assert_eq!(list.current(), None);
+        list.select(Some(1));
+        assert_eq!(list.current(), Some(&1));
+        list.unselect();
+        assert_eq!(list.current(), None);
+        assert_eq!(list.current_idx(), None);
+    }
+
+    #[test]
+    fn select_clamps_to_the_last_item() {
+        let mut list = list(3);
+        list.select(Some(10));
+        assert_eq!(list.current_idx(), Some(2));
+        list.select(None);
+        assert_eq!(list.current_idx(), Some(0));
+    }
+
+    #[test]
+    fn pages_stop_at_the_ends() {
+        let mut list = list(10);
+        list.select(Some(5));
+        assert_eq!(list.page_down(3), Some(8));
+        assert_eq!(list.page_down(3), Some(9));
+        assert_eq!(list.page_up(4), Some(5));
+        assert_eq!(list.page_up(10), Some(0));
+    }
+
+    #[test]
+    fn selects_first_and_last() {
+        let mut list = list(4);
+        assert_eq!(list.select_last(), Some(3));
+        assert_eq!(list.select_first(), Some(0));
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug)]
+/// A map holding at most `capacity` entries. When full, inserting a new key
+/// evicts the least recently used one.
+pub struct LruMap<K, V> {
+    map: HashMap<K, (V, u64)>,
+    capacity: usize,
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruMap<K, V> {
+    pub fn new(capacity: usize) -> LruMap<K, V> {
+        LruMap {
+            map: HashMap::new(),
+            capacity,
+            tick: 0,
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let tick = self.next_tick();
+        self.map.get_mut(key).map(|(v, used)| {
+            *used = tick;
+            &*v
+        })
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        let tick = self.next_tick();
+        if !self.map.contains_key(&key) && self.map.len() >= self.capacity {
+            let lru = self
+                .map
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(k, _)| k.clone());
+            if let Some(lru) = lru {
+                self.map.remove(&lru);
+            }
+        }
+        self.map.insert(key, (value, tick));
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
@@ -0,0 +1,659 @@
+//! A small regular expression engine supporting the commonly used subset of
+//! the syntax: literals, `.`, `^`, `$`, character classes (`[a-z]`, `[^0-9]`,
+//! `\d`, `\w`, `\s` and their negations), groups `(...)`/`(?:...)`,
+//! alternation `|` and the `*`, `+`, `?`, `{n}`, `{n,}`, `{n,m}` quantifiers
+//! with their lazy `?` variants.
+//!
+//! Patterns are compiled to an NFA simulated a character at a time (a Pike VM)
+//! so matching takes time linear in the text for any pattern, while matches
+//! are the same a backtracking engine would find.
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+/// Instruction of the compiled program, `Split` prefers its first target.
+#[derive(Debug, Clone, PartialEq)]
+enum Inst {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+const SPACE: &[(char, char)] = &[(' ', ' '), ('\t', '\r')];
+/// Largest compiled program, counted repetitions are expanded into copies.
+const MAX_INSTS: usize = 10_000;
+
+#[derive(Clone)]
+pub struct Regex {
+    pattern: String,
+    prog: Vec<Inst>,
+    case_insensitive: bool,
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Regex").field(&self.pattern).finish()
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl PartialEq for Regex {
+    fn eq(&self, other: &Regex) -> bool {
+        self.pattern == other.pattern && self.case_insensitive == other.case_insensitive
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex> {
+        Regex::with_case(pattern, false)
+    }
+
+    pub fn with_case(pattern: &str, case_insensitive: bool) -> Result<Regex> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let root = parser.parse_alt()?;
+        if parser.pos < parser.chars.len() {
+            return Err(anyhow!("unmatched `)` at position {}", parser.pos));
+        }
+
+        let mut compiler = Compiler { prog: vec![] };
+        compiler.compile(&root)?;
+        compiler.push(Inst::Match)?;
+
+        Ok(Regex {
+            pattern: pattern.to_string(),
+            prog: compiler.prog,
+            case_insensitive,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// Returns the byte range of the leftmost match in `text`.
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        let chars: Vec<char> = text.chars().collect();
+        let offsets: Vec<usize> = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .collect();
+
+        // threads are (instruction, start of the match) ordered by priority
+        let mut threads = Threads::new(self.prog.len());
+        let mut next = Threads::new(self.prog.len());
+        let mut found = None;
+
+        for pos in 0..=chars.len() {
+            if found.is_none() {
+                // a match starting here is only preferred over none at all
+                self.add_thread(&mut threads, 0, pos, pos, &chars);
+            }
+            if threads.list.is_empty() {
+                break;
+            }
+            for &(pc, start) in &threads.list {
+                let matches = match &self.prog[pc] {
+                    Inst::Char(c) => pos < chars.len() && self.char_eq(chars[pos], *c),
+                    Inst::Any => pos < chars.len() && chars[pos] != '\n',
+                    Inst::Class { ranges, negated } => {
+                        pos < chars.len() && self.class_contains(ranges, chars[pos]) != *negated
+                    }
+                    Inst::Match => {
+                        // threads after this one have lower priority
+                        found = Some((start, pos));
+                        break;
+                    }
+                    _ => false,
+                };
+                if matches {
+                    self.add_thread(&mut next, pc + 1, start, pos + 1, &chars);
+                }
+            }
+            std::mem::swap(&mut threads, &mut next);
+            next.clear();
+        }
+
+        found.map(|(start, end)| (offsets[start], offsets[end]))
+    }
+
+    /// Adds the thread at `pc` following jumps and assertions at `pos` in
+    /// priority order.
+    fn add_thread(
+        &self,
+        threads: &mut Threads,
+        pc: usize,
+        start: usize,
+        pos: usize,
+        chars: &[char],
+    ) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if !threads.visit(pc) {
+                continue;
+            }
+            match self.prog[pc] {
+                Inst::Jmp(to) => stack.push(to),
+                Inst::Split(first, second) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                Inst::Start => {
+                    if pos == 0 {
+                        stack.push(pc + 1);
+                    }
+                }
+                Inst::End => {
+                    if pos == chars.len() {
+                        stack.push(pc + 1);
+                    }
+                }
+                _ => threads.list.push((pc, start)),
+            }
+        }
+    }
+
+    fn char_eq(&self, a: char, b: char) -> bool {
+        a == b || (self.case_insensitive && a.to_lowercase().eq(b.to_lowercase()))
+    }
+
+    fn class_contains(&self, ranges: &[(char, char)], c: char) -> bool {
+        let in_ranges = |c: char| ranges.iter().any(|(lo, hi)| *lo <= c && c <= *hi);
+        in_ranges(c)
+            || (self.case_insensitive
+                && (c.to_lowercase().any(in_ranges) || c.to_uppercase().any(in_ranges)))
+    }
+}
+
+/// Threads alive at one position, each instruction is run at most once.
+struct Threads {
+    list: Vec<(usize, usize)>,
+    visited: Vec<bool>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Threads {
+        Threads {
+            list: vec![],
+            visited: vec![false; len],
+        }
+    }
+
+    /// Marks `pc` as visited, returns `false` if it already was.
+    fn visit(&mut self, pc: usize) -> bool {
+        !std::mem::replace(&mut self.visited[pc], true)
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        self.visited.iter_mut().for_each(|v| *v = false);
+    }
+}
+
+struct Compiler {
+    prog: Vec<Inst>,
+}
+
+impl Compiler {
+    fn push(&mut self, inst: Inst) -> Result<usize> {
+        if self.prog.len() >= MAX_INSTS {
+            return Err(anyhow!("pattern is too large"));
+        }
+        self.prog.push(inst);
+        Ok(self.prog.len() - 1)
+    }
+
+    fn compile(&mut self, node: &Node) -> Result<()> {
+        match node {
+            Node::Char(c) => {
+                self.push(Inst::Char(*c))?;
+            }
+            Node::Any => {
+                self.push(Inst::Any)?;
+            }
+            Node::Class { ranges, negated } => {
+                self.push(Inst::Class {
+                    ranges: ranges.clone(),
+                    negated: *negated,
+                })?;
+            }
+            Node::Start => {
+                self.push(Inst::Start)?;
+            }
+            Node::End => {
+                self.push(Inst::End)?;
+            }
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.compile(node)?;
+                }
+            }
+            Node::Alt(nodes) => {
+                let mut jumps = vec![];
+                for (i, node) in nodes.iter().enumerate() {
+                    if i + 1 == nodes.len() {
+                        self.compile(node)?;
+                        break;
+                    }
+                    let split = self.push(Inst::Split(0, 0))?;
+                    self.compile(node)?;
+                    jumps.push(self.push(Inst::Jmp(0))?);
+                    self.prog[split] = Inst::Split(split + 1, self.prog.len());
+                }
+                let end = self.prog.len();
+                for jump in jumps {
+                    self.prog[jump] = Inst::Jmp(end);
+                }
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.compile(node)?;
+                }
+                let mut splits = vec![];
+                match max {
+                    None => {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.compile(node)?;
+                        self.push(Inst::Jmp(split))?;
+                        splits.push(split);
+                    }
+                    Some(max) => {
+                        for _ in *min..*max {
+                            splits.push(self.push(Inst::Split(0, 0))?);
+                            self.compile(node)?;
+                        }
+                    }
+                }
+                let end = self.prog.len();
+                for split in splits {
+                    let (more, done) = (split + 1, end);
+                    self.prog[split] = if *greedy {
+                        Inst::Split(more, done)
+                    } else {
+                        Inst::Split(done, more)
+                    };
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_alt(&mut self) -> Result<Node> {
+        let mut alts = vec![self.parse_concat()?];
+        while self.eat('|') {
+            alts.push(self.parse_concat()?);
+        }
+
+        Ok(if alts.len() == 1 {
+            alts.remove(0)
+        } else {
+            Node::Alt(alts)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node> {
+        let mut nodes = vec![];
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            nodes.push(self.parse_repeat()?);
+        }
+
+        Ok(Node::Concat(nodes))
+    }
+
+    fn parse_repeat(&mut self) -> Result<Node> {
+        let atom = self.parse_atom()?;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => return self.parse_counted(atom),
+            _ => return Ok(atom),
+        };
+        self.pos += 1;
+
+        Ok(self.repeat(atom, min, max))
+    }
+
+    fn repeat(&mut self, atom: Node, min: usize, max: Option<usize>) -> Node {
+        let greedy = !self.eat('?');
+        Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<usize> {
+        let start = self.pos;
+        while self.peek().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn parse_counted(&mut self, atom: Node) -> Result<Node> {
+        let start = self.pos;
+        self.pos += 1;
+        let min = match self.parse_number() {
+            Some(min) => min,
+            None => {
+                // not a quantifier, treat `{` literally
+                self.pos = start + 1;
+                return Ok(Node::Concat(vec![atom, Node::Char('{')]));
+            }
+        };
+        let max = if self.eat(',') {
+            self.parse_number()
+        } else {
+            Some(min)
+        };
+        if !self.eat('}') {
+            return Err(anyhow!("unterminated counted repetition at {}", start));
+        }
+        if max.map(|max| max < min).unwrap_or(false) {
+            return Err(anyhow!("invalid counted repetition at {}", start));
+        }
+
+        Ok(self.repeat(atom, min, max))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node> {
+        let pos = self.pos;
+        match self.bump() {
+            Some('(') => {
+                if self.eat('?') && !self.eat(':') {
+                    return Err(anyhow!("unsupported group flag at {}", pos));
+                }
+                let node = self.parse_alt()?;
+                if !self.eat(')') {
+                    return Err(anyhow!("unclosed group at {}", pos));
+                }
+                Ok(node)
+            }
+            Some('[') => self.parse_class(),
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Start),
+            Some('$') => Ok(Node::End),
+            Some('\\') => self.parse_escape(),
+            Some(c @ '*') | Some(c @ '+') | Some(c @ '?') => {
+                Err(anyhow!("quantifier `{}` without a target at {}", c, pos))
+            }
+            Some(c) => Ok(Node::Char(c)),
+            None => Err(anyhow!("unexpected end of pattern")),
+        }
+    }
+
+    /// Character escaped by the backslash at `pos`, only `\n`, `\t` and
+    /// punctuation stand for a single character.
+    fn escaped_char(c: char, pos: usize) -> Result<char> {
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            c if !c.is_alphanumeric() => Ok(c),
+            c => Err(anyhow!("unsupported escape `\\{}` at {}", c, pos)),
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<Node> {
+        let pos = self.pos - 1;
+        let class = |ranges: &[(char, char)], negated| Node::Class {
+            ranges: ranges.to_vec(),
+            negated,
+        };
+        match self.bump() {
+            Some('d') => Ok(class(DIGIT, false)),
+            Some('D') => Ok(class(DIGIT, true)),
+            Some('w') => Ok(class(WORD, false)),
+            Some('W') => Ok(class(WORD, true)),
+            Some('s') => Ok(class(SPACE, false)),
+            Some('S') => Ok(class(SPACE, true)),
+            Some(c) => Ok(Node::Char(Parser::escaped_char(c, pos)?)),
+            None => Err(anyhow!("trailing backslash")),
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<Node> {
+        let start = self.pos - 1;
+        let negated = self.eat('^');
+        let mut ranges = vec![];
+        let mut first = true;
+
+        loop {
+            let c = match self.bump() {
+                Some(']') if !first => break,
+                Some('\\') => match self.bump() {
+                    Some('d') => {
+                        ranges.extend_from_slice(DIGIT);
+                        continue;
+                    }
+                    Some('w') => {
+                        ranges.extend_from_slice(WORD);
+                        continue;
+                    }
+                    Some('s') => {
+                        ranges.extend_from_slice(SPACE);
+                        continue;
+                    }
+                    Some(c) => Parser::escaped_char(c, self.pos - 2)?,
+                    None => return Err(anyhow!("unclosed character class at {}", start)),
+                },
+                Some(c) => c,
+                None => return Err(anyhow!("unclosed character class at {}", start)),
+            };
+            first = false;
+
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1) != Some(&']') {
+                self.pos += 1;
+                let hi = match self.bump() {
+                    Some('\\') => match self.bump() {
+                        Some(hi) => Some(Parser::escaped_char(hi, self.pos - 2)?),
+                        None => None,
+                    },
+                    hi => hi,
+                }
+                .ok_or_else(|| anyhow!("unclosed character class at {}", start))?;
+                if hi < c {
+                    return Err(anyhow!("invalid class range `{}-{}`", c, hi));
+                }
+                ranges.push((c, hi));
+            } else {
+                ranges.push((c, c));
+            }
+        }
+
+        Ok(Node::Class { ranges, negated })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Option<(usize, usize)> {
+        Regex::new(pattern).unwrap().find(text)
+    }
+
+    #[test]
+    fn literals_and_any() {
+        assert_eq!(find("abc", "xxabcxx"), Some((2, 5)));
+        assert_eq!(find("a.c", "abc"), Some((0, 3)));
+        assert_eq!(find("a.c", "a\nc"), None);
+        assert_eq!(find("", "abc"), Some((0, 0)));
+        assert_eq!(find("abd", "abc"), None);
+    }
+
+    #[test]
+    fn anchors() {
+        assert_eq!(find("^ab", "abab"), Some((0, 2)));
+        assert_eq!(find("ab$", "abab"), Some((2, 4)));
+        assert_eq!(find("^b", "ab"), None);
+        assert_eq!(find("^$", ""), Some((0, 0)));
+        assert!(Regex::new(r"\.rs$").unwrap().is_match("main.rs"));
+        assert!(!Regex::new(r"\.rs$").unwrap().is_match("main.rs.bak"));
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(find("[a-c]+", "xxbcaz"), Some((2, 5)));
+        assert_eq!(find("[^0-9]+", "12ab3"), Some((2, 4)));
+        assert_eq!(find(r"\d+", "v1.23"), Some((1, 2)));
+        assert_eq!(find(r"\w+", "  foo_1 "), Some((2, 7)));
+        assert_eq!(find(r"\s", "a\tb"), Some((1, 2)));
+        assert_eq!(find(r"[\d.]+", "v1.23"), Some((1, 5)));
+        assert_eq!(find("[]a]", "]"), Some((0, 1)));
+        assert_eq!(find("[a-]", "-"), Some((0, 1)));
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(find(r"a\tb\n", "a\tb\n"), Some((0, 4)));
+        assert_eq!(find(r"\(\*\)", "f(*)"), Some((1, 4)));
+        assert_eq!(find(r"[\]\-]+", "a-]b"), Some((1, 3)));
+        assert_eq!(find(r"\D\W\S", "a b"), Some((0, 3)));
+        let err = Regex::new(r"\bfoo").unwrap_err().to_string();
+        assert!(err.starts_with("unsupported escape"), "{}", err);
+    }
+
+    #[test]
+    fn alternation_prefers_the_first_branch() {
+        assert_eq!(find("a|ab", "ab"), Some((0, 1)));
+        assert_eq!(find("ab|a", "ab"), Some((0, 2)));
+        assert_eq!(find("(foo|bar)baz", "barbaz"), Some((0, 6)));
+        assert_eq!(find("(?:x|y)z", "yz"), Some((0, 2)));
+    }
+
+    #[test]
+    fn greedy_and_lazy_quantifiers() {
+        assert_eq!(find("a*", "aaa"), Some((0, 3)));
+        assert_eq!(find("a*?", "aaa"), Some((0, 0)));
+        assert_eq!(find("a+?", "aaa"), Some((0, 1)));
+        assert_eq!(find("<.*>", "<a><b>"), Some((0, 6)));
+        assert_eq!(find("<.*?>", "<a><b>"), Some((0, 3)));
+        assert_eq!(find("colou?r", "color"), Some((0, 5)));
+        assert_eq!(find("(a*)*b", "aab"), Some((0, 3)));
+    }
+
+    #[test]
+    fn counted_repetitions() {
+        assert_eq!(find("a{2}", "aaa"), Some((0, 2)));
+        assert_eq!(find("a{2,}", "aaaa"), Some((0, 4)));
+        assert_eq!(find("a{1,2}", "aaa"), Some((0, 2)));
+        assert_eq!(find("a{1,2}?", "aaa"), Some((0, 1)));
+        assert_eq!(find("a{3}", "aa"), None);
+        // not a quantifier
+        assert_eq!(find("a{x", "a{x"), Some((0, 3)));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let re = Regex::with_case("readme", true).unwrap();
+        assert_eq!(re.find("README.md"), Some((0, 6)));
+        let re = Regex::with_case("[a-z]+", true).unwrap();
+        assert_eq!(re.find("ABC"), Some((0, 3)));
+        assert!(!Regex::new("readme").unwrap().is_match("README"));
+    }
+
+    #[test]
+    fn byte_offsets_of_multibyte_text() {
+        assert_eq!(find("ó.d", "źółdź"), Some((2, 7)));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        for pattern in [
+            "(a", "a)", "[a", "*a", "a{2", "a{3,1}", "[z-a]", "(?i)a", "\\", "\\bfoo", "\\B",
+            "\\A", "\\z", "\\p{L}", "[\\D]", "[\\b]", "[a-\\z]",
+        ] {
+            assert!(Regex::new(pattern).is_err(), "{}", pattern);
+        }
+        assert!(Regex::new("(a{100}){100}{100}").is_err());
+    }
+
+    #[test]
+    fn pathological_patterns_match_in_linear_time() {
+        let text = "a".repeat(10_000);
+        assert_eq!(find("(a|aa)*c", &text), None);
+        assert_eq!(find("(a*)*b", &text), None);
+        assert_eq!(find("(a|aa)*$", &text), Some((0, 10_000)));
+    }
+}
@@ -0,0 +1,109 @@
+//! Helpers for reading and writing the flat `key = value` TOML files used for
+//! configuration and persisted state. Only strings, booleans, integers and
+//! arrays of strings are supported.
+use anyhow::{anyhow, Result};
+
+/// Splits a `key = value` line returning `None` for blank lines and comments.
+pub fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let mut it = line.splitn(2, '=');
+    let key = it.next()?.trim();
+    let value = it.next().unwrap_or_default().trim();
+    Some((key, value))
+}
+
+pub fn parse_bool(value: &str) -> Result<bool> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("expected `true` or `false`, found `{}`", value))
+}
+
+pub fn parse_int<T: std::str::FromStr>(value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("expected an integer, found `{}`", value))
+}
+
+/// Parses a quoted string at the start of `value` returning it along with the
+/// remaining input.
+fn parse_str_prefix(value: &str) -> Result<(String, &str)> {
+    let err = || anyhow!("expected a quoted string, found `{}`", value);
+    let mut chars = value.char_indices();
+    if chars.next().map(|(_, c)| c) != Some('"') {
+        return Err(err());
+    }
+
+    let mut out = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((out, &value[i + 1..])),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some(c @ '"') | Some(c @ '\\') => out.push(c),
+                _ => return Err(anyhow!("invalid escape sequence in `{}`", value)),
+            },
+            c => out.push(c),
+        }
+    }
+
+    Err(err())
+}
+
+pub fn parse_str(value: &str) -> Result<String> {
+    let (s, rest) = parse_str_prefix(value)?;
+    if !rest.trim().is_empty() {
+        return Err(anyhow!("unexpected `{}` after string", rest.trim()));
+    }
+    Ok(s)
+}
+
+pub fn parse_str_array(value: &str) -> Result<Vec<String>> {
+    let err = || anyhow!("expected an array of strings, found `{}`", value);
+    let mut rest = value.strip_prefix('[').ok_or_else(err)?.trim_start();
+    let mut items = vec![];
+
+    loop {
+        if let Some(after) = rest.strip_prefix(']') {
+            if !after.trim().is_empty() {
+                return Err(err());
+            }
+            return Ok(items);
+        }
+        let (item, after) = parse_str_prefix(rest)?;
+        items.push(item);
+        rest = after.trim_start();
+        if let Some(after) = rest.strip_prefix(',') {
+            rest = after.trim_start();
+        } else if !rest.starts_with(']') {
+            return Err(err());
+        }
+    }
+}
+
+/// Quotes and escapes `s` as a TOML basic string.
+pub fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub fn quote_array<S: AsRef<str>>(items: &[S]) -> String {
+    let items: Vec<_> = items.iter().map(|s| quote(s.as_ref())).collect();
+    format!("[{}]", items.join(", "))
+}
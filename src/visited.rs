@@ -0,0 +1,65 @@
+//! Directories visited across sessions, saved in `~/.config/trav/recent.toml`.
+use anyhow::{anyhow, Context, Result};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::util;
+use crate::util::toml::{parse_line, parse_str_array, quote_array};
+
+const RECENT_FILE: &str = "recent.toml";
+/// Number of remembered directories, the least recent ones are dropped first.
+pub const MAX_RECENTLY_VISITED: usize = 50;
+
+pub fn path(config_dir: Option<&Path>) -> Result<PathBuf> {
+    config_dir
+        .map(|dir| dir.join(RECENT_FILE))
+        .ok_or_else(|| anyhow!("failed to determine the config directory"))
+}
+
+/// Reads the saved directories, most recent first. A missing file is an empty
+/// list.
+pub fn load(config_dir: Option<&Path>) -> Result<VecDeque<PathBuf>> {
+    let path = path(config_dir)?;
+    if !path.exists() {
+        return Ok(VecDeque::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("invalid {}", path.display()))
+}
+
+pub fn parse(content: &str) -> Result<VecDeque<PathBuf>> {
+    let mut dirs = VecDeque::new();
+    for line in content.lines() {
+        match parse_line(line) {
+            Some(("dirs", value)) => {
+                dirs = parse_str_array(value)?
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .collect();
+            }
+            Some((key, _)) => return Err(anyhow!("unknown key `{}`", key)),
+            None => {}
+        }
+    }
+    dirs.truncate(MAX_RECENTLY_VISITED);
+    Ok(dirs)
+}
+
+pub fn save(dirs: &VecDeque<PathBuf>, config_dir: Option<&Path>) -> Result<()> {
+    let path = path(config_dir)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let dirs: Vec<_> = dirs.iter().map(|dir| dir.to_string_lossy()).collect();
+    let content = format!("dirs = {}\n", quote_array(&dirs));
+    util::write_atomic(&path, content.as_bytes())
+}
+
+/// Moves `dir` to the front of `dirs`.
+pub fn record(dirs: &mut VecDeque<PathBuf>, dir: &Path) {
+    dirs.retain(|d| d != dir);
+    dirs.push_front(dir.to_path_buf());
+    dirs.truncate(MAX_RECENTLY_VISITED);
+}
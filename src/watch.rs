@@ -0,0 +1,134 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::entry::{ChangeKind, ChangeState, DirEntry};
+
+/// Number of ticks a change stays highlighted.
+pub const CHANGE_TICKS: u8 = 8;
+
+/// Modification time and size used to detect modified entries.
+type Stamp = (Option<SystemTime>, u64);
+
+#[derive(Debug)]
+/// State of a directory at one point in time.
+struct Snapshot {
+    dir: PathBuf,
+    stamps: HashMap<PathBuf, Stamp>,
+}
+
+impl Snapshot {
+    fn take(dir: &Path) -> Result<Snapshot> {
+        let mut stamps = HashMap::new();
+        for entry in fs::read_dir(dir)?.flatten() {
+            let stamp = match entry.metadata() {
+                Ok(md) => (md.modified().ok(), md.len()),
+                Err(_) => (None, 0),
+            };
+            stamps.insert(entry.path(), stamp);
+        }
+
+        Ok(Snapshot {
+            dir: dir.to_path_buf(),
+            stamps,
+        })
+    }
+
+    fn diff(&self, newer: &Snapshot) -> Vec<(PathBuf, ChangeKind)> {
+        let mut changes = vec![];
+        for (path, stamp) in &newer.stamps {
+            match self.stamps.get(path) {
+                None => changes.push((path.clone(), ChangeKind::Created)),
+                Some(old) if old != stamp => changes.push((path.clone(), ChangeKind::Modified)),
+                Some(_) => {}
+            }
+        }
+        for path in self.stamps.keys() {
+            if !newer.stamps.contains_key(path) {
+                changes.push((path.clone(), ChangeKind::Deleted));
+            }
+        }
+
+        changes
+    }
+}
+
+#[derive(Debug, Default)]
+/// Polls the current directory for changes and tracks how long each change
+/// stays highlighted.
+pub struct Watch {
+    snapshot: Option<Snapshot>,
+    changes: HashMap<PathBuf, ChangeState>,
+}
+
+impl Watch {
+    pub fn new() -> Watch {
+        Watch::default()
+    }
+
+    /// Compares `dir` with its last snapshot recording the changes. Returns
+    /// `true` if the listing of `dir` is out of date.
+    pub fn poll(&mut self, dir: &Path) -> Result<bool> {
+        let snapshot = Snapshot::take(dir)?;
+        let stale = match &self.snapshot {
+            Some(old) if old.dir == dir => {
+                let changes = old.diff(&snapshot);
+                let stale = !changes.is_empty();
+                for (path, kind) in changes {
+                    self.changes.insert(
+                        path,
+                        ChangeState {
+                            kind,
+                            ticks_left: CHANGE_TICKS,
+                        },
+                    );
+                }
+                stale
+            }
+            // changes made before entering a directory aren't interesting
+            _ => {
+                self.changes.clear();
+                false
+            }
+        };
+        self.snapshot = Some(snapshot);
+
+        Ok(stale)
+    }
+
+    /// Advances all change animations by one frame.
+    pub fn tick(&mut self) {
+        for state in self.changes.values_mut() {
+            state.ticks_left = state.ticks_left.saturating_sub(1);
+        }
+        self.changes.retain(|_, state| state.ticks_left > 0);
+    }
+
+    /// Marks freshly loaded `entries` with their change state. Deleted entries
+    /// are taken over from the `previous` listing until their animation ends.
+    pub fn decorate(&self, entries: &mut Vec<DirEntry>, previous: Vec<DirEntry>) {
+        let deleted = previous.into_iter().filter(|e| {
+            self.changes.get(&e.path()).map(|state| state.kind) == Some(ChangeKind::Deleted)
+        });
+        entries.extend(deleted);
+        self.update(entries);
+    }
+
+    /// Refreshes change states of `entries` dropping deleted entries whose
+    /// animation has ended.
+    pub fn update(&self, entries: &mut Vec<DirEntry>) {
+        entries.retain(|e| {
+            e.change() != Some(ChangeKind::Deleted)
+                || self.changes.get(&e.path()).map(|state| state.kind) == Some(ChangeKind::Deleted)
+        });
+        for entry in entries.iter_mut() {
+            entry.set_change(self.changes.get(&entry.path()).copied());
+        }
+    }
+
+    pub fn is_animating(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
@@ -0,0 +1,74 @@
+//! Directories marked as workspace roots with `W` and jumped to with
+//! `Alt-1`–`Alt-9`, saved in `~/.config/trav/workspaces.toml`.
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::util;
+use crate::util::toml::{parse_line, parse_str_array, quote_array};
+
+const WORKSPACES_FILE: &str = "workspaces.toml";
+/// Number of roots reachable with `Alt-1`–`Alt-9`.
+pub const MAX_WORKSPACES: usize = 9;
+
+pub fn path(config_dir: Option<&Path>) -> Result<PathBuf> {
+    config_dir
+        .map(|dir| dir.join(WORKSPACES_FILE))
+        .ok_or_else(|| anyhow!("failed to determine the config directory"))
+}
+
+/// Reads the saved workspace roots. A missing file is an empty list.
+pub fn load(config_dir: Option<&Path>) -> Result<Vec<PathBuf>> {
+    let path = path(config_dir)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse(&content).with_context(|| format!("invalid {}", path.display()))
+}
+
+pub fn parse(content: &str) -> Result<Vec<PathBuf>> {
+    let mut roots = vec![];
+    for line in content.lines() {
+        match parse_line(line) {
+            Some(("roots", value)) => {
+                roots = parse_str_array(value)?
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .collect();
+            }
+            Some((key, _)) => return Err(anyhow!("unknown key `{}`", key)),
+            None => {}
+        }
+    }
+    roots.truncate(MAX_WORKSPACES);
+    Ok(roots)
+}
+
+pub fn save(roots: &[PathBuf], config_dir: Option<&Path>) -> Result<()> {
+    let path = path(config_dir)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let roots: Vec<_> = roots.iter().map(|root| root.to_string_lossy()).collect();
+    let content = format!("roots = {}\n", quote_array(&roots));
+    util::write_atomic(&path, content.as_bytes())
+}
+
+/// Appends `dir` to the roots or removes it if it's already one. Returns
+/// whether it was added.
+pub fn toggle(roots: &mut Vec<PathBuf>, dir: &Path) -> Result<bool> {
+    if let Some(i) = roots.iter().position(|root| root == dir) {
+        roots.remove(i);
+        return Ok(false);
+    }
+    if roots.len() >= MAX_WORKSPACES {
+        return Err(anyhow!(
+            "at most {} workspaces can be marked, unmark one with `W` first",
+            MAX_WORKSPACES
+        ));
+    }
+    roots.push(dir.to_path_buf());
+    Ok(true)
+}
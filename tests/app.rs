@@ -0,0 +1,149 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use termion::event::Key;
+use trav::app::AppOptions;
+
+use common::{fixture, open, press, type_until};
+
+const FILES: &[&str] = &["a.txt", "b.txt", "sub/c.txt"];
+
+#[test]
+fn opens_the_base_dir() {
+    let fixture = fixture("base-dir", FILES);
+    let dir = &fixture.0;
+    let app = open(dir);
+
+    assert_eq!(app.current_dir(), dir.as_path());
+    let selected = app.selected_path().unwrap();
+    assert_eq!(selected.parent(), Some(dir.as_path()));
+    assert!(app.selection().is_empty());
+    assert!(app.output().is_empty());
+}
+
+#[test]
+fn canonicalizes_the_base_dir() {
+    let fixture = fixture("canonical-base-dir", FILES);
+    let dir = &fixture.0;
+    let app = open(&dir.join("sub").join(".."));
+
+    assert_eq!(app.current_dir(), dir.as_path());
+}
+
+#[test]
+fn set_dir_resolves_relative_paths() {
+    let fixture = fixture("relative", FILES);
+    let dir = &fixture.0;
+    let mut app = open(dir);
+
+    app.set_dir(Path::new("sub")).unwrap();
+    assert_eq!(app.current_dir(), dir.join("sub"));
+    assert_eq!(app.selected_path(), Some(dir.join("sub").join("c.txt")));
+
+    app.set_dir(Path::new("..")).unwrap();
+    assert_eq!(app.current_dir(), dir.as_path());
+}
+
+#[test]
+fn set_dir_accepts_absolute_paths() {
+    let fixture = fixture("absolute", FILES);
+    let dir = &fixture.0;
+    let mut app = open(&dir.join("sub"));
+
+    app.set_dir(dir).unwrap();
+    assert_eq!(app.current_dir(), dir.as_path());
+}
+
+#[test]
+fn set_dir_rejects_files() {
+    let fixture = fixture("files", FILES);
+    let dir = &fixture.0;
+    let mut app = open(dir);
+
+    assert!(app.set_dir(Path::new("a.txt")).is_err());
+    assert!(app.set_dir(Path::new("missing")).is_err());
+    assert_eq!(app.current_dir(), dir.as_path());
+}
+
+#[test]
+fn set_dir_honors_max_depth() {
+    let fixture = fixture("max-depth", FILES);
+    let dir = &fixture.0;
+    let mut app = open(dir);
+    app.set_options(AppOptions {
+        max_depth: Some(0),
+        ..AppOptions::default()
+    });
+
+    assert!(app.set_dir(Path::new("sub")).is_err());
+    assert_eq!(app.current_dir(), dir.as_path());
+}
+
+#[test]
+fn handles_injected_keys() {
+    let fixture = fixture("keys", FILES);
+    let dir = &fixture.0;
+    let mut app = open(dir);
+    let before = app.selected_path();
+
+    press(&mut app, Key::Down);
+    assert_ne!(app.selected_path(), before);
+    assert_eq!(app.current_dir(), dir.as_path());
+}
+
+#[test]
+fn max_depth_keeps_the_start_dir_as_root() {
+    let fixture = fixture("max-depth-root", FILES);
+    let dir = &fixture.0;
+    let mut app = open(&dir.join("sub"));
+    app.set_options(AppOptions {
+        max_depth: Some(1),
+        ..AppOptions::default()
+    });
+
+    assert!(app.set_dir(Path::new("..")).is_err());
+    assert_eq!(app.current_dir(), dir.join("sub"));
+}
+
+/// Keys typing `text` into a prompt and confirming it.
+fn typed(text: &str) -> Vec<Key> {
+    text.chars().chain(Some('\n')).map(Key::Char).collect()
+}
+
+#[test]
+fn readonly_refuses_to_export() {
+    let fixture = fixture("export-readonly", FILES);
+    let dir = &fixture.0;
+    let mut app = open(dir);
+    app.set_options(AppOptions {
+        readonly: true,
+        ..AppOptions::default()
+    });
+
+    let mut keys = vec![Key::Char('E')];
+    keys.extend(typed("a.txt"));
+    type_until(&mut app, &keys, |app| app.error().is_some());
+    assert_eq!(app.error(), Some("read-only mode"));
+    assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "a.txt");
+}
+
+#[test]
+fn export_keeps_existing_files() {
+    let fixture = fixture("export-existing", FILES);
+    let dir = &fixture.0;
+    let mut app = open(dir);
+
+    let mut keys = vec![Key::Char('E')];
+    keys.extend(typed("a.txt"));
+    type_until(&mut app, &keys, |app| app.error().is_some());
+    assert!(app.error().unwrap().ends_with("already exists"));
+    assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "a.txt");
+
+    let mut keys = vec![Key::Char('E')];
+    keys.extend(typed("paths"));
+    type_until(&mut app, &keys, |_| dir.join("paths").exists());
+    let paths = fs::read_to_string(dir.join("paths")).unwrap();
+    assert_eq!(paths.lines().count(), 3);
+}
@@ -0,0 +1,99 @@
+// each test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use termion::event::Key;
+use trav::app::{AppEvent, TravApp};
+use trav::config::Config;
+use trav::events::{self, Event, Events};
+
+/// Events handled while waiting for a key to take effect, ticks may arrive
+/// before it.
+const MAX_EVENTS_PER_KEY: usize = 10;
+
+/// Directory removed when the test ends.
+pub struct Fixture(pub PathBuf);
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Creates a fresh directory for `name` with the `files`, given relative to
+/// it, filled with their own name. Paths ending with `/` are directories,
+/// parents are created as needed.
+pub fn fixture(name: &str, files: &[&str]) -> Fixture {
+    let dir = env::temp_dir().join(format!("trav-tests-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for file in files {
+        let path = dir.join(file);
+        if file.ends_with('/') {
+            fs::create_dir_all(path).unwrap();
+        } else {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            fs::write(path, name).unwrap();
+        }
+    }
+    Fixture(fs::canonicalize(dir).unwrap())
+}
+
+/// Default config that keeps the user's favorites, tags and history out of
+/// the tests and shows paths in full.
+pub fn config() -> Config {
+    Config {
+        state_dir: None,
+        home_dir: None,
+        ..Config::default()
+    }
+}
+
+pub fn open(dir: &Path) -> TravApp {
+    open_with(dir, config())
+}
+
+/// Opens `dir` with keys sent only by `press` instead of read from the
+/// terminal.
+pub fn open_with(dir: &Path, config: Config) -> TravApp {
+    let events = Events::without_input(events::Config::default());
+    TravApp::with_events(Some(dir), config, events).unwrap()
+}
+
+/// Sends `keys` to `app` and handles events until `done` returns `true`.
+pub fn type_until(app: &mut TravApp, keys: &[Key], done: impl Fn(&TravApp) -> bool) {
+    let sender = app.event_sender();
+    for key in keys {
+        sender.send(Event::Input(AppEvent::Key(*key))).unwrap();
+    }
+    for _ in 0..keys.len() + MAX_EVENTS_PER_KEY {
+        app.handle_event().unwrap();
+        if done(app) {
+            return;
+        }
+    }
+    panic!("{:?} didn't take effect", keys);
+}
+
+/// Sends `key` to `app` and handles events until the current directory or
+/// the highlighted entry changes.
+pub fn press(app: &mut TravApp, key: Key) {
+    let before = (app.current_dir().to_path_buf(), app.selected_index());
+    app.event_sender()
+        .send(Event::Input(AppEvent::Key(key)))
+        .unwrap();
+    for _ in 0..MAX_EVENTS_PER_KEY {
+        app.handle_event().unwrap();
+        if (app.current_dir().to_path_buf(), app.selected_index()) != before {
+            return;
+        }
+    }
+    panic!(
+        "{:?} didn't move the highlight or change the directory",
+        key
+    );
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use termion::event::Key;
+
+use common::{fixture, open, press};
+
+const FILES: &[&str] = &["a/b/c.txt", "d.txt"];
+
+#[test]
+fn walks_into_and_out_of_directories() {
+    let fixture = fixture("navigation", FILES);
+    let root = &fixture.0;
+    let mut app = open(root);
+    assert_eq!(app.current_dir(), root.as_path());
+    assert_eq!(app.selected_index(), Some(1));
+    assert_eq!(app.selected_path(), Some(root.join("d.txt")));
+    assert_eq!(app.preview(), Some("d.txt\n"));
+
+    press(&mut app, Key::Up);
+    assert_eq!(app.current_dir(), root.as_path());
+    assert_eq!(app.selected_index(), Some(0));
+    assert_eq!(app.selected_path(), Some(root.join("a")));
+
+    press(&mut app, Key::Right);
+    assert_eq!(app.current_dir(), root.join("a"));
+    assert_eq!(app.selected_index(), Some(0));
+    assert_eq!(app.selected_path(), Some(root.join("a/b")));
+
+    press(&mut app, Key::Right);
+    assert_eq!(app.current_dir(), root.join("a/b"));
+    assert_eq!(app.selected_index(), Some(0));
+    assert_eq!(app.selected_path(), Some(root.join("a/b/c.txt")));
+    assert_eq!(app.preview(), Some("c.txt\n"));
+
+    press(&mut app, Key::Left);
+    assert_eq!(app.current_dir(), root.join("a"));
+    assert_eq!(app.selected_index(), Some(0));
+    assert_eq!(app.selected_path(), Some(root.join("a/b")));
+
+    press(&mut app, Key::Left);
+    assert_eq!(app.current_dir(), root.as_path());
+    assert_eq!(app.selected_index(), Some(0));
+    assert_eq!(app.selected_path(), Some(root.join("a")));
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use trav::app::TravApp;
+use trav::config::Config;
+
+use common::{config, fixture, open_with};
+
+/// Long enough for the titles to be truncated before the temporary directory.
+const PARENT: &str = "a-parent-directory-name-wider-than-its-panel";
+/// Set to rewrite the snapshots instead of comparing with them.
+const UPDATE_VAR: &str = "TRAV_UPDATE_SNAPSHOTS";
+/// Modification time of every fixture entry, 2021-01-01 00:00:00 UTC.
+const MTIME_SECS: u64 = 1_609_459_200;
+/// `MTIME_SECS` as rendered in front of entry sizes.
+const MTIME: &str = "2021-01-01T00:00:00Z ";
+/// Rendered instead of sizes, directory sizes depend on the filesystem.
+const SIZE_MASK: &str = "<size>";
+
+/// Sets the modification time of `path` and everything below it to
+/// `MTIME_SECS`.
+fn pin_mtimes(path: &Path) {
+    if path.is_dir() {
+        for entry in fs::read_dir(path).unwrap() {
+            pin_mtimes(&entry.unwrap().path());
+        }
+    }
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(MTIME_SECS);
+    fs::File::open(path).unwrap().set_modified(mtime).unwrap();
+}
+
+/// Replaces the size following each pinned modification time in `frame`
+/// keeping the panel borders in place.
+fn mask_sizes(frame: &str) -> String {
+    let mut masked = String::new();
+    let mut rest = frame;
+    while let Some(i) = rest.find(MTIME) {
+        let (before, after) = rest.split_at(i + MTIME.len());
+        masked.push_str(before);
+        // the size is the last column of the panel
+        let column = after.find(['│', '\n']).unwrap_or(after.len());
+        masked.push_str(&format!("{:width$}", SIZE_MASK, width = column));
+        rest = &after[column..];
+    }
+    masked.push_str(rest);
+    masked
+}
+
+/// Renders `app` once the preview counts are loaded with the status bar made
+/// independent of the user running the tests and the sizes masked.
+fn render(app: &mut TravApp, width: u16, height: u16) -> String {
+    for _ in 0..20 {
+        let frame = app.render_to_string(width, height);
+        if !frame.contains("(...)") {
+            return mask_sizes(&frame.replace("[ROOT] ", ""));
+        }
+        app.handle_event().unwrap();
+    }
+    panic!("the preview counts weren't loaded");
+}
+
+/// Compares `frame` with `tests/snapshots/<name>.txt`.
+fn assert_snapshot(name: &str, frame: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.txt", name));
+    if env::var_os(UPDATE_VAR).is_some() {
+        fs::write(&path, format!("{}\n", frame)).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+    assert!(
+        expected.trim_end_matches('\n') == frame,
+        "{} doesn't match, rerun with {}=1 to update it\nexpected:\n{}\nrendered:\n{}",
+        path.display(),
+        UPDATE_VAR,
+        expected,
+        frame
+    );
+}
+
+#[test]
+fn renders_three_panels() {
+    let fixture = fixture(
+        "render",
+        &[
+            &format!("{}/project/src/main.rs", PARENT),
+            &format!("{}/project/Cargo.toml", PARENT),
+            &format!("{}/project/README.md", PARENT),
+        ],
+    );
+    pin_mtimes(&fixture.0);
+    let config = Config {
+        // shown as `~/...` in the status bar
+        home_dir: Some(fixture.0.clone()),
+        entry_height: 2,
+        ..config()
+    };
+    let dir = fixture.0.join(PARENT).join("project");
+    let mut app = open_with(&dir, config);
+
+    assert_snapshot("three_panels", &render(&mut app, 100, 12));
+}